@@ -23,5 +23,7 @@ def main():
 
     for hello in scout.receiver():
         print(hello)
+        for (protocol, address) in hello.parsed_locators:
+            print(f"    {protocol}: {address}")
 
 main()
@@ -0,0 +1,100 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! Audit logging: a JSON record of every operation made through a session, written from the Rust methods themselves
+//! so that no Python code path can skip it.
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use pyo3::{prelude::*, types::PyDict};
+use zenoh::prelude::Value;
+use zenoh_buffers::buffer::SplitBuffer;
+
+use crate::enums::{_CongestionControl, _Priority, _Reliability};
+use crate::{PyExtract, ToPyErr};
+
+enum Sink {
+    File(Mutex<File>),
+    /// A Python `logging.Logger`, or anything else with an `info` method taking a string.
+    Logger(PyObject),
+}
+
+pub(crate) struct AuditLog(Sink);
+impl AuditLog {
+    /// Appends to the file at `target` if it is a path, or logs through `target.info` otherwise.
+    pub(crate) fn new(target: &PyAny) -> PyResult<Self> {
+        if let Ok(path) = target.extract::<&str>() {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| e.to_pyerr())?;
+            return Ok(AuditLog(Sink::File(Mutex::new(file))));
+        }
+        if !target.hasattr("info")? {
+            return Err(zenoh_core::zerror!(
+                "`audit` must be a file path or a logger, not {}",
+                target.get_type().name()?
+            )
+            .to_pyerr());
+        }
+        Ok(AuditLog(Sink::Logger(target.into())))
+    }
+    /// Records `operation` on `key_expr`, along with the QoS found in `kwargs`.
+    pub(crate) fn record(
+        &self,
+        operation: &str,
+        key_expr: &str,
+        value: Option<&Value>,
+        kwargs: Option<&PyDict>,
+    ) -> PyResult<()> {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let mut record = serde_json::json!({
+            "time": time,
+            "operation": operation,
+            "key_expr": key_expr,
+        });
+        if let Some(value) = value {
+            record["size"] = value.payload.contiguous().len().into();
+        }
+        if let Some(kwargs) = kwargs {
+            if let Ok(priority) = kwargs.extract_item::<_Priority>("priority") {
+                record["priority"] = priority.__str__().into();
+            }
+            if let Ok(congestion_control) =
+                kwargs.extract_item::<_CongestionControl>("congestion_control")
+            {
+                record["congestion_control"] = congestion_control.__str__().into();
+            }
+            if let Ok(reliability) = kwargs.extract_item::<_Reliability>("reliability") {
+                record["reliability"] = reliability.__str__().into();
+            }
+        }
+        let line = record.to_string();
+        match &self.0 {
+            Sink::File(file) => {
+                writeln!(file.lock().unwrap(), "{}", line).map_err(|e| e.to_pyerr())
+            }
+            Sink::Logger(logger) => {
+                Python::with_gil(|py| logger.call_method1(py, "info", (line,)).map(|_| ()))
+            }
+        }
+    }
+}
@@ -12,17 +12,28 @@
 //   ZettaScale Zenoh team, <zenoh@zettascale.tech>
 //
 use std::{
-    convert::TryFrom,
-    sync::{Arc, Mutex},
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 use pyo3::{
     prelude::*,
     types::{PyList, PyTuple},
 };
-use zenoh::prelude::IntoCallbackReceiverPair;
+use zenoh::{
+    handlers::Callback,
+    prelude::{IntoCallbackReceiverPair, Sample},
+};
+
+use crate::value::_Sample;
+use crate::{recv_interruptibly, ToPyErr};
 
-trait CallbackUnwrap {
+pub(crate) trait CallbackUnwrap {
     type Output;
     fn cb_unwrap(self) -> Self::Output;
 }
@@ -103,23 +114,440 @@ where
     }
 }
 
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed set of threads running the callbacks of a session's subscriptions and queryables.
+///
+/// Each callback is bound to one of the threads, in turn, so that its calls keep their order
+/// while a slow callback only delays those bound to the same thread.
+pub(crate) struct CallbackPool {
+    workers: Vec<flume::Sender<Job>>,
+    next: AtomicUsize,
+}
+impl CallbackPool {
+    pub(crate) fn new(threads: usize) -> PyResult<Self> {
+        if threads == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "`callback_threads` must be positive",
+            ));
+        }
+        let workers = (0..threads)
+            .map(|i| {
+                let (sender, receiver) = flume::unbounded::<Job>();
+                std::thread::Builder::new()
+                    .name(format!("zenoh-python-callbacks-{}", i))
+                    .spawn(move || {
+                        // the thread exits once the pool and all the callbacks bound to it are dropped
+                        while let Ok(job) = receiver.recv() {
+                            job()
+                        }
+                    })
+                    .map(|_| sender)
+                    .map_err(|e| e.to_pyerr())
+            })
+            .collect::<PyResult<_>>()?;
+        Ok(CallbackPool {
+            workers,
+            next: AtomicUsize::new(0),
+        })
+    }
+    /// Makes `callback` run on one of the pool's threads rather than on the caller's.
+    pub(crate) fn bind<T: Send + 'static>(
+        &self,
+        callback: Callback<'static, T>,
+    ) -> Callback<'static, T> {
+        let worker =
+            self.workers[self.next.fetch_add(1, Ordering::Relaxed) % self.workers.len()].clone();
+        Arc::new(move |value| {
+            let callback = callback.clone();
+            let _ = worker.send(Box::new(move || callback(value)));
+        })
+    }
+    /// Waits for up to `timeout` for the calls queued so far to have run, returning whether they all did.
+    pub(crate) fn drain(&self, timeout: Duration) -> bool {
+        // timeouts too large to be represented as instants never expire
+        let deadline = Instant::now().checked_add(timeout);
+        let (done, drained) = flume::bounded::<()>(self.workers.len());
+        for worker in &self.workers {
+            let done = done.clone();
+            // runs once the calls queued before it on this thread have
+            let _ = worker.send(Box::new(move || {
+                let _ = done.send(());
+            }));
+        }
+        drop(done);
+        (0..self.workers.len()).all(|_| match deadline {
+            Some(deadline) => drained.recv_deadline(deadline).is_ok(),
+            None => drained.recv().is_ok(),
+        })
+    }
+}
+
+/// A callback that overwrites the same Python `Sample` object for each received sample,
+/// rather than allocating a new one every time.
+pub(crate) struct SampleReusingClosure {
+    closure: PyClosure<(PyObject,)>,
+    slot: Py<PyAny>,
+}
+impl SampleReusingClosure {
+    pub(crate) fn new(callback: &PyAny, slot: &PyAny) -> PyResult<Self> {
+        slot.downcast::<PyCell<_Sample>>()?;
+        Ok(SampleReusingClosure {
+            closure: callback.try_into()?,
+            slot: slot.into(),
+        })
+    }
+}
+impl IntoCallbackReceiverPair<'static, Sample> for SampleReusingClosure {
+    type Receiver = ();
+
+    fn into_cb_receiver_pair(self) -> (zenoh::handlers::Callback<'static, Sample>, Self::Receiver) {
+        (
+            Arc::new(move |sample| {
+                let sample: _Sample = sample.into();
+                Python::with_gil(|py| {
+                    let cell: &PyCell<_Sample> = self.slot.as_ref(py).downcast()?;
+                    // if the slot is still in use, fallback to a new object
+                    let arg = match cell.try_borrow_mut() {
+                        Ok(mut slot) => {
+                            *slot = sample;
+                            self.slot.clone_ref(py)
+                        }
+                        Err(_) => Py::new(py, sample)?.into_py(py),
+                    };
+                    self.closure.pycall.call1(py, (arg,))
+                })
+                .cb_unwrap();
+            }),
+            (),
+        )
+    }
+}
+
+/// A callback that accumulates values on a dedicated thread, delivering them to Python as lists
+/// of up to `max_count` values, at most `max_delay` after the first value of each list was received.
+pub(crate) struct PyBatchingClosure<I> {
+    closure: PyClosure<(Py<PyList>,)>,
+    max_count: usize,
+    max_delay: Duration,
+    _marker: std::marker::PhantomData<I>,
+}
+impl<I> PyBatchingClosure<I> {
+    pub(crate) fn new(callback: &PyAny, max_count: usize, max_delay: f64) -> PyResult<Self> {
+        let max_delay = Duration::try_from_secs_f64(max_delay).ok();
+        let max_delay = match max_delay {
+            Some(max_delay) if max_count > 0 => max_delay,
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "batches must have a positive `max_count` and a non-negative `max_delay`",
+                ))
+            }
+        };
+        Ok(PyBatchingClosure {
+            closure: callback.try_into()?,
+            max_count,
+            max_delay,
+            _marker: std::marker::PhantomData,
+        })
+    }
+}
+impl<T, I> IntoCallbackReceiverPair<'static, T> for PyBatchingClosure<I>
+where
+    T: Into<I>,
+    I: IntoPy<PyObject> + Send + 'static,
+{
+    type Receiver = ();
+
+    fn into_cb_receiver_pair(self) -> (zenoh::handlers::Callback<'static, T>, Self::Receiver) {
+        let (send, recv) = flume::unbounded::<I>();
+        let PyBatchingClosure {
+            closure,
+            max_count,
+            max_delay,
+            ..
+        } = self;
+        // The thread stops once the callback, and thus `send`, is dropped,
+        // delivering the last batch before dropping `closure`.
+        std::thread::spawn(move || {
+            while let Ok(first) = recv.recv() {
+                // delays too large to be represented as instants never expire
+                let deadline = Instant::now().checked_add(max_delay);
+                let mut batch = vec![first];
+                while batch.len() < max_count {
+                    let value = match deadline {
+                        Some(deadline) => recv.recv_deadline(deadline).ok(),
+                        None => recv.recv().ok(),
+                    };
+                    match value {
+                        Some(value) => batch.push(value),
+                        None => break,
+                    }
+                }
+                Python::with_gil(|py| {
+                    let batch = PyList::new(py, batch.into_iter().map(|value| value.into_py(py)));
+                    closure.pycall.call1(py, (batch,))
+                })
+                .cb_unwrap();
+            }
+        });
+        (
+            Arc::new(move |value: T| {
+                let _ = send.send(value.into());
+            }),
+            (),
+        )
+    }
+}
+
+/// Statistics on how long a callback waits for, then holds, the GIL.
+#[pyclass(subclass)]
+#[derive(Default)]
+pub struct _CallbackProfile {
+    calls: u64,
+    gil_wait_total: Duration,
+    gil_wait_max: Duration,
+    gil_held_total: Duration,
+    gil_held_max: Duration,
+}
+#[pymethods]
+impl _CallbackProfile {
+    #[new]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Returns the statistics, durations being expressed in seconds.
+    pub fn stats(&self) -> HashMap<&'static str, f64> {
+        let average = |total: Duration| {
+            if self.calls == 0 {
+                0.
+            } else {
+                total.as_secs_f64() / self.calls as f64
+            }
+        };
+        HashMap::from([
+            ("calls", self.calls as f64),
+            ("gil_wait_avg", average(self.gil_wait_total)),
+            ("gil_wait_max", self.gil_wait_max.as_secs_f64()),
+            ("gil_held_avg", average(self.gil_held_total)),
+            ("gil_held_max", self.gil_held_max.as_secs_f64()),
+        ])
+    }
+}
+impl _CallbackProfile {
+    fn record(&mut self, gil_wait: Duration, gil_held: Duration) {
+        self.calls += 1;
+        self.gil_wait_total += gil_wait;
+        self.gil_wait_max = self.gil_wait_max.max(gil_wait);
+        self.gil_held_total += gil_held;
+        self.gil_held_max = self.gil_held_max.max(gil_held);
+    }
+}
+
+/// A callback that records its GIL usage into a `_CallbackProfile`.
+pub(crate) struct PyProfilingClosure<I> {
+    closure: PyClosure<(I,)>,
+    profile: Py<_CallbackProfile>,
+}
+impl<I> PyProfilingClosure<I> {
+    pub(crate) fn new(callback: &PyAny, profile: Py<_CallbackProfile>) -> PyResult<Self> {
+        Ok(PyProfilingClosure {
+            closure: callback.try_into()?,
+            profile,
+        })
+    }
+}
+impl<T, I> IntoCallbackReceiverPair<'static, T> for PyProfilingClosure<I>
+where
+    T: Into<I>,
+    I: Send + Sync + 'static,
+    (I,): IntoPy<Py<PyTuple>>,
+{
+    type Receiver = ();
+
+    fn into_cb_receiver_pair(self) -> (zenoh::handlers::Callback<'static, T>, Self::Receiver) {
+        (
+            Arc::new(move |value: T| {
+                let value = value.into();
+                let arrived = Instant::now();
+                Python::with_gil(|py| {
+                    let acquired = Instant::now();
+                    let result = self.closure.pycall.call1(py, (value,));
+                    let done = Instant::now();
+                    self.profile
+                        .borrow_mut(py)
+                        .record(acquired - arrived, done - acquired);
+                    result
+                })
+                .cb_unwrap();
+            }),
+            (),
+        )
+    }
+}
+
+/// A Rust-side sink for subscriptions that don't need a Python callback:
+/// it counts the samples it receives, and keeps the latest one if asked to.
+pub(crate) struct SampleSink {
+    count: AtomicU64,
+    latest: Option<Mutex<Option<Sample>>>,
+}
+impl SampleSink {
+    pub(crate) fn new(kind: &str) -> PyResult<Self> {
+        let latest = match kind {
+            "count" => None,
+            "latest" => Some(Mutex::new(None)),
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "unknown sink `{kind}`: expected `count` or `latest`"
+                )))
+            }
+        };
+        Ok(SampleSink {
+            count: AtomicU64::new(0),
+            latest,
+        })
+    }
+    pub(crate) fn receive(&self, sample: Sample) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if let Some(latest) = &self.latest {
+            *latest.lock().unwrap() = Some(sample);
+        }
+    }
+    pub(crate) fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+    pub(crate) fn latest(&self) -> PyResult<Option<_Sample>> {
+        match &self.latest {
+            Some(latest) => Ok(latest.lock().unwrap().clone().map(_Sample::from)),
+            None => Err(zenoh_core::zerror!("this sink doesn't keep the latest sample").to_pyerr()),
+        }
+    }
+}
+
+/// What putting on a full bounded queue does.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Overflow {
+    /// Waits for room.
+    Block,
+    /// Drops the oldest queued value to make room.
+    DropOldest,
+    /// Drops the value being put.
+    DropNewest,
+}
+impl Overflow {
+    pub(crate) fn new(overflow: &str) -> PyResult<Self> {
+        match overflow {
+            "block" => Ok(Overflow::Block),
+            "drop_oldest" => Ok(Overflow::DropOldest),
+            "drop_newest" => Ok(Overflow::DropNewest),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown overflow policy {overflow:?}, expected \"block\", \"drop_oldest\" or \"drop_newest\""
+            ))),
+        }
+    }
+    /// Puts `value` on a bounded channel, counting the values dropped for lack of room in `dropped`.
+    fn put<T>(
+        self,
+        send: &flume::Sender<T>,
+        recv: &flume::Receiver<T>,
+        value: T,
+        dropped: &AtomicU64,
+    ) {
+        match self {
+            Overflow::Block => {
+                let _ = send.send(value);
+            }
+            Overflow::DropOldest => {
+                let mut value = value;
+                // the consumer may take values in between, in which case there is room again
+                while let Err(flume::TrySendError::Full(rejected)) = send.try_send(value) {
+                    if recv.try_recv().is_ok() {
+                        dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    value = rejected;
+                }
+            }
+            Overflow::DropNewest => {
+                if let Err(flume::TrySendError::Full(_)) = send.try_send(value) {
+                    dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
+
+/// Makes `callback` run on a thread of its own, fed by a buffer of `capacity` values that handles overflows
+/// according to `overflow`, counting the values dropped in `dropped`.
+///
+/// The thread exits once the returned callback is dropped and the buffered values are handled.
+pub(crate) fn overflowing<T: Send + 'static>(
+    callback: Callback<'static, T>,
+    capacity: usize,
+    overflow: Overflow,
+    dropped: Arc<AtomicU64>,
+) -> PyResult<Callback<'static, T>> {
+    if capacity == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "subscribers' overflow buffers must hold at least one sample",
+        ));
+    }
+    let (send, recv) = flume::bounded::<T>(capacity);
+    let received = recv.clone();
+    std::thread::Builder::new()
+        .name("zenoh-python-overflow".to_string())
+        .spawn(move || {
+            while let Ok(value) = received.recv() {
+                callback(value)
+            }
+        })
+        .map_err(|e| e.to_pyerr())?;
+    Ok(Arc::new(move |value| {
+        overflow.put(&send, &recv, value, &dropped)
+    }))
+}
+
+/// The instant a `timeout` of `Queue.get` expires at, `None` if it never does.
+fn deadline(timeout: Option<f32>) -> PyResult<Option<Instant>> {
+    let timeout = timeout
+        .map(Duration::try_from_secs_f32)
+        .transpose()
+        .map_err(|_| {
+            pyo3::exceptions::PyValueError::new_err(
+                "`timeout` must be a non-negative number of seconds",
+            )
+        })?;
+    // timeouts too large to be represented as instants never expire
+    Ok(timeout.and_then(|timeout| Instant::now().checked_add(timeout)))
+}
+
 #[pyclass(subclass)]
 pub struct _Queue {
     send: Mutex<Option<flume::Sender<PyObject>>>,
     recv: flume::Receiver<PyObject>,
+    overflow: Overflow,
+    dropped: AtomicU64,
 }
 #[pymethods]
 impl _Queue {
     #[new]
-    pub fn pynew(bound: Option<usize>) -> Self {
+    #[pyo3(signature = (bound = None, overflow = "block"))]
+    pub fn pynew(bound: Option<usize>, overflow: &str) -> PyResult<Self> {
+        let overflow = Overflow::new(overflow)?;
         let (send, recv) = match bound {
             None => flume::unbounded(),
+            Some(0) if overflow != Overflow::Block => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "queues dropping values must hold at least one",
+                ))
+            }
             Some(bound) => flume::bounded(bound),
         };
-        Self {
+        Ok(Self {
             send: Mutex::new(Some(send)),
             recv,
-        }
+            overflow,
+            dropped: AtomicU64::new(0),
+        })
     }
     pub fn close(&self) {
         *self.send.lock().unwrap() = None;
@@ -130,55 +558,54 @@ impl _Queue {
                 "Attempted to put on closed Queue",
             )),
             Some(send) => {
-                send.send(value).unwrap();
+                self.overflow.put(send, &self.recv, value, &self.dropped);
                 Ok(())
             }
         })
     }
+    /// The number of values dropped so far for lack of room.
+    #[getter]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+    #[getter]
+    pub fn capacity(&self) -> Option<usize> {
+        self.recv.capacity()
+    }
     pub fn get(&self, timeout: Option<f32>, py: Python<'_>) -> PyResult<PyObject> {
-        Python::allow_threads(py, || match timeout {
-            None => match self.recv.recv() {
-                Ok(value) => Ok(value),
-                Err(_) => Err(pyo3::exceptions::PyStopIteration::new_err(())),
-            },
-            Some(secs) => match self
-                .recv
-                .recv_timeout(std::time::Duration::from_secs_f32(secs))
-            {
-                Ok(value) => Ok(value),
-                Err(flume::RecvTimeoutError::Timeout) => {
-                    Err(pyo3::exceptions::PyTimeoutError::new_err(()))
-                }
-                Err(flume::RecvTimeoutError::Disconnected) => {
-                    Err(pyo3::exceptions::PyStopIteration::new_err(()))
-                }
-            },
-        })
+        let deadline = deadline(timeout)?;
+        match recv_interruptibly(py, &self.recv, deadline)? {
+            Ok(value) => Ok(value),
+            Err(flume::RecvTimeoutError::Timeout) => Err(crate::RecvTimeoutError::new_err(())),
+            Err(flume::RecvTimeoutError::Disconnected) => {
+                Err(pyo3::exceptions::PyStopIteration::new_err(()))
+            }
+        }
+    }
+    /// Returns the next value if one is already queued, without waiting.
+    pub fn try_get(&self) -> PyResult<Option<PyObject>> {
+        match self.recv.try_recv() {
+            Ok(value) => Ok(Some(value)),
+            Err(flume::TryRecvError::Empty) => Ok(None),
+            Err(flume::TryRecvError::Disconnected) => {
+                Err(pyo3::exceptions::PyStopIteration::new_err(()))
+            }
+        }
     }
     pub fn get_remaining(&self, timeout: Option<f32>, py: Python<'_>) -> PyResult<Py<PyList>> {
-        Python::allow_threads(py, || {
-            let vec = match timeout {
-                None => self.recv.iter().collect::<Vec<_>>(),
-                Some(secs) => {
-                    let deadline =
-                        std::time::Instant::now() + std::time::Duration::from_secs_f32(secs);
-                    let mut vec = Vec::new();
-                    loop {
-                        match self.recv.recv_deadline(deadline) {
-                            Ok(v) => vec.push(v),
-                            Err(flume::RecvTimeoutError::Disconnected) => break,
-                            Err(flume::RecvTimeoutError::Timeout) => {
-                                let list: Py<PyList> =
-                                    Python::with_gil(|py| PyList::new(py, vec).into_py(py));
-                                return Err(pyo3::exceptions::PyTimeoutError::new_err((list,)));
-                            }
-                        }
-                    }
-                    vec
+        let deadline = deadline(timeout)?;
+        let mut vec = Vec::new();
+        loop {
+            match recv_interruptibly(py, &self.recv, deadline)? {
+                Ok(v) => vec.push(v),
+                Err(flume::RecvTimeoutError::Disconnected) => break,
+                Err(flume::RecvTimeoutError::Timeout) => {
+                    let list: Py<PyList> = PyList::new(py, vec).into_py(py);
+                    return Err(crate::RecvTimeoutError::new_err((list,)));
                 }
-            };
-            Ok(Python::with_gil(|py| PyList::new(py, vec).into_py(py)))
-        })
+            }
+        }
+        Ok(PyList::new(py, vec).into_py(py))
     }
     pub fn is_closed(&self) -> bool {
         self.send.lock().unwrap().is_none()
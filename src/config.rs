@@ -14,7 +14,7 @@
 
 #![allow(clippy::borrow_deref_ref)] // false positives with pyo3 macros
 
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyType};
 use validated_struct::ValidatedMap;
 use zenoh::config::{Config, Notifier};
 use zenoh_core::zerror;
@@ -43,14 +43,14 @@ impl PyConfig {
     }
 }
 #[pyclass(subclass)]
+#[derive(Clone)]
 pub struct _Config(pub(crate) PyConfig);
 
 #[pymethods]
 impl _Config {
-    #[allow(clippy::new_without_default)]
     #[new]
-    pub fn new() -> Self {
-        _Config(Default::default())
+    pub fn new(this: Option<Self>) -> Self {
+        this.unwrap_or_else(|| _Config(Default::default()))
     }
     #[staticmethod]
     pub fn from_file(expr: &str) -> PyResult<Self> {
@@ -72,6 +72,13 @@ impl _Config {
         }
     }
 
+    pub fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        match &self.0 {
+            PyConfig::Notifier(c) => _Config(PyConfig::Config(Box::new(c.lock().clone()))),
+            config => _Config(config.clone()),
+        }
+    }
+
     pub fn get_json(&self, path: &str) -> PyResult<String> {
         match &self.0 {
             PyConfig::None => Err(zerror!("Attempted to use a destroyed configuration").to_pyerr()),
@@ -80,6 +87,14 @@ impl _Config {
         }
     }
 
+    pub fn to_json(&self) -> PyResult<String> {
+        match &self.0 {
+            PyConfig::None => Err(zerror!("Attempted to use a destroyed configuration").to_pyerr()),
+            PyConfig::Config(c) => serde_json::to_string(c.as_ref()).to_pyres(),
+            PyConfig::Notifier(c) => serde_json::to_string(&*c.lock()).to_pyres(),
+        }
+    }
+
     pub fn insert_json5(&mut self, path: &str, value: &str) -> PyResult<()> {
         match &mut self.0 {
             PyConfig::None => Err(zerror!("Attempted to use a destroyed configuration").to_pyerr()),
@@ -87,4 +102,135 @@ impl _Config {
             PyConfig::Notifier(c) => c.insert_json5(path, value).map_err(|e| e.to_pyerr()),
         }
     }
+
+    pub fn set_tls(&mut self, tls: &_TlsConfig) -> PyResult<()> {
+        let files = [
+            ("root_ca_certificate", &tls.cafile),
+            ("server_certificate", &tls.certfile),
+            ("server_private_key", &tls.keyfile),
+            ("client_certificate", &tls.certfile),
+            ("client_private_key", &tls.keyfile),
+        ];
+        for (key, file) in files {
+            if let Some(file) = file {
+                let file = serde_json::to_string(file).to_pyres()?;
+                self.insert_json5(&format!("{TLS_KEY}/{key}"), &file)?;
+            }
+        }
+        self.insert_json5(
+            &format!("{TLS_KEY}/server_name_verification"),
+            &tls.check_hostname.to_string(),
+        )?;
+        self.insert_json5(
+            &format!("{TLS_KEY}/client_auth"),
+            &tls.client_auth.to_string(),
+        )
+    }
+}
+
+const TLS_KEY: &str = "transport/link/tls";
+
+/// The TLS material and verification settings written to `transport/link/tls` by `_Config::set_tls`.
+#[pyclass(subclass)]
+#[derive(Clone, Debug)]
+pub struct _TlsConfig {
+    #[pyo3(get)]
+    cafile: Option<String>,
+    #[pyo3(get)]
+    certfile: Option<String>,
+    #[pyo3(get)]
+    keyfile: Option<String>,
+    #[pyo3(get)]
+    check_hostname: bool,
+    #[pyo3(get)]
+    client_auth: bool,
+}
+#[pymethods]
+impl _TlsConfig {
+    #[new]
+    #[pyo3(signature = (cafile = None, certfile = None, keyfile = None, check_hostname = true, client_auth = false))]
+    pub fn new(
+        cafile: Option<String>,
+        certfile: Option<String>,
+        keyfile: Option<String>,
+        check_hostname: bool,
+        client_auth: bool,
+    ) -> PyResult<Self> {
+        if certfile.is_some() != keyfile.is_some() {
+            return Err(PyValueError::new_err(
+                "`certfile` and `keyfile` must be provided together",
+            ));
+        }
+        Ok(_TlsConfig {
+            cafile,
+            certfile,
+            keyfile,
+            check_hostname,
+            client_auth,
+        })
+    }
+    /// Builds an instance of `cls` from the settings of an `ssl.SSLContext`, raising `ValueError` on those zenoh can't honour.
+    #[classmethod]
+    #[pyo3(signature = (context, cafile = None, certfile = None, keyfile = None))]
+    pub fn from_ssl_context<'py>(
+        cls: &'py PyType,
+        context: &PyAny,
+        cafile: Option<String>,
+        certfile: Option<String>,
+        keyfile: Option<String>,
+    ) -> PyResult<&'py PyAny> {
+        let ssl = cls.py().import("ssl")?;
+        let is = |value: &PyAny, name: &str| -> PyResult<bool> { value.eq(ssl.getattr(name)?) };
+        let check_hostname: bool = context.getattr("check_hostname")?.extract()?;
+        let protocol = context.getattr("protocol")?;
+        let verify_mode = context.getattr("verify_mode")?;
+        let client_auth = if is(protocol, "PROTOCOL_TLS_SERVER")? {
+            if is(verify_mode, "CERT_REQUIRED")? {
+                true
+            } else if is(verify_mode, "CERT_NONE")? {
+                false
+            } else if is(verify_mode, "CERT_OPTIONAL")? {
+                return Err(PyValueError::new_err(
+                    "`ssl.CERT_OPTIONAL` is not supported: zenoh's listeners either require client certificates or don't ask for them",
+                ));
+            } else {
+                return Err(PyValueError::new_err(format!(
+                    "unknown verify_mode `{verify_mode}`"
+                )));
+            }
+        } else if is(protocol, "PROTOCOL_TLS_CLIENT")? {
+            // clients treat `CERT_OPTIONAL` as `CERT_REQUIRED`
+            if is(verify_mode, "CERT_REQUIRED")? || is(verify_mode, "CERT_OPTIONAL")? {
+                false
+            } else if is(verify_mode, "CERT_NONE")? {
+                return Err(PyValueError::new_err(
+                    "`ssl.CERT_NONE` is not supported on client contexts: zenoh always verifies the certificates of the nodes it connects to",
+                ));
+            } else {
+                return Err(PyValueError::new_err(format!(
+                    "unknown verify_mode `{verify_mode}`"
+                )));
+            }
+        } else {
+            return Err(PyValueError::new_err(
+                "only `ssl.PROTOCOL_TLS_CLIENT` and `ssl.PROTOCOL_TLS_SERVER` contexts are supported, the others don't tell whether they connect or listen",
+            ));
+        };
+        let crl_checks: i64 = ssl.getattr("VERIFY_CRL_CHECK_LEAF")?.extract::<i64>()?
+            | ssl.getattr("VERIFY_CRL_CHECK_CHAIN")?.extract::<i64>()?;
+        if context.getattr("verify_flags")?.extract::<i64>()? & crl_checks != 0 {
+            return Err(PyValueError::new_err(
+                "certificate revocation checks are not supported by zenoh's TLS links",
+            ));
+        }
+        let tls_1_2: i64 = ssl.getattr("TLSVersion")?.getattr("TLSv1_2")?.extract()?;
+        // negative versions stand for "whatever the library supports"
+        let maximum_version: i64 = context.getattr("maximum_version")?.extract()?;
+        if (0..tls_1_2).contains(&maximum_version) {
+            return Err(PyValueError::new_err(
+                "zenoh's TLS links require TLS 1.2 or later, which `maximum_version` excludes",
+            ));
+        }
+        cls.call1((cafile, certfile, keyfile, check_hostname, client_auth))
+    }
 }
@@ -0,0 +1,106 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! Payload sealing, with AES-256-GCM.
+//!
+//! A sealed payload is [`VERSION`], followed by the random nonce it was sealed with,
+//! followed by the ciphertext and its authentication tag.
+//! The key expression the payload is published on is authenticated along with it,
+//! so that a sealed payload can't be replayed on another key expression.
+use pyo3::{prelude::*, types::PyBytes};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+
+use crate::ToPyErr;
+
+const VERSION: u8 = 1;
+
+fn sealing_key(key: &[u8]) -> PyResult<LessSafeKey> {
+    match UnboundKey::new(&AES_256_GCM, key) {
+        Ok(key) => Ok(LessSafeKey::new(key)),
+        Err(_) => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "sealing keys must be {} bytes long, not {}",
+            AES_256_GCM.key_len(),
+            key.len()
+        ))),
+    }
+}
+
+/// Encrypts `payload` with `key`, authenticating `key_expr` along with it.
+#[pyfunction]
+pub fn seal<'py>(
+    py: Python<'py>,
+    key: &[u8],
+    key_expr: &str,
+    payload: &[u8],
+) -> PyResult<&'py PyBytes> {
+    let key = sealing_key(key)?;
+    let mut nonce = [0; NONCE_LEN];
+    let sealed = py.allow_threads(|| {
+        SystemRandom::new().fill(&mut nonce).ok()?;
+        let mut sealed = Vec::with_capacity(1 + NONCE_LEN + payload.len() + AES_256_GCM.tag_len());
+        sealed.push(VERSION);
+        sealed.extend_from_slice(&nonce);
+        let mut ciphertext = payload.to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(key_expr.as_bytes()),
+            &mut ciphertext,
+        )
+        .ok()?;
+        sealed.extend_from_slice(&ciphertext);
+        Some(sealed)
+    });
+    match sealed {
+        Some(sealed) => Ok(PyBytes::new(py, &sealed)),
+        None => {
+            Err(zenoh_core::zerror!("failed to seal the payload for `{}`", key_expr).to_pyerr())
+        }
+    }
+}
+
+/// Decrypts a payload sealed by [`seal`] for `key_expr`, raising a `ZError` if it wasn't sealed with `key` for `key_expr`,
+/// or was tampered with.
+#[pyfunction]
+pub fn unseal<'py>(
+    py: Python<'py>,
+    key: &[u8],
+    key_expr: &str,
+    sealed: &[u8],
+) -> PyResult<&'py PyBytes> {
+    let key = sealing_key(key)?;
+    let payload = py.allow_threads(|| {
+        let (version, sealed) = sealed.split_first()?;
+        if *version != VERSION || sealed.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+        let mut buffer = ciphertext.to_vec();
+        let len = key
+            .open_in_place(nonce, Aad::from(key_expr.as_bytes()), &mut buffer)
+            .ok()?
+            .len();
+        buffer.truncate(len);
+        Some(buffer)
+    });
+    match payload {
+        Some(payload) => Ok(PyBytes::new(py, &payload)),
+        None => Err(
+            zenoh_core::zerror!("failed to unseal the payload received on `{}`", key_expr)
+                .to_pyerr(),
+        ),
+    }
+}
@@ -0,0 +1,254 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! [DLPack](https://dmlc.github.io/dlpack/latest/) interoperability, for array libraries such as numpy, torch or cupy
+//! to publish their tensors and read payloads without copying them through Python bytes.
+//!
+//! Tensors are published as `application/octet-stream` payloads, whose encoding suffix records their data type and
+//! shape: `;dtype=<code>.<bits>.<lanes>;shape=<d0>,<d1>...`. Payloads are exported as host tensors copied from the
+//! received buffers, which consumers may write to, with that data type and shape if their encoding has such a suffix,
+//! or as bytes otherwise.
+use std::{ffi::c_void, os::raw::c_char};
+
+use pyo3::{ffi, prelude::*};
+use zenoh::prelude::{Encoding, KnownEncoding};
+use zenoh_buffers::buffer::SplitBuffer;
+
+use crate::value::Payload;
+
+const DLTENSOR: &[u8] = b"dltensor\0";
+const DL_CPU: i32 = 1;
+const DL_CUDA: i32 = 2;
+const DL_CUDA_HOST: i32 = 3;
+const DL_CUDA_MANAGED: i32 = 13;
+const DL_UINT: u8 = 1;
+/// The `__dlpack_device__` of exported tensors.
+pub(crate) const HOST: (i32, i32) = (DL_CPU, 0);
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DLDevice {
+    device_type: i32,
+    device_id: i32,
+}
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DLDataType {
+    code: u8,
+    bits: u8,
+    lanes: u16,
+}
+#[repr(C)]
+struct DLTensor {
+    data: *mut c_void,
+    device: DLDevice,
+    ndim: i32,
+    dtype: DLDataType,
+    shape: *mut i64,
+    strides: *mut i64,
+    byte_offset: u64,
+}
+#[repr(C)]
+struct DLManagedTensor {
+    dl_tensor: DLTensor,
+    manager_ctx: *mut c_void,
+    deleter: Option<unsafe extern "C" fn(*mut DLManagedTensor)>,
+}
+
+fn invalid(message: String) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(message)
+}
+
+/// The number of bytes of a tensor of `dtype` and `shape`, `None` if a dimension is negative or the size overflows.
+fn size(dtype: DLDataType, shape: &[i64]) -> Option<usize> {
+    let itemsize = (dtype.bits as usize * dtype.lanes as usize + 7) / 8;
+    shape.iter().try_fold(itemsize, |size, &dim| {
+        size.checked_mul(usize::try_from(dim).ok()?)
+    })
+}
+
+/// Calls `f` with the bytes of `tensor`, a DLPack capsule or an object implementing `__dlpack__`, and the encoding
+/// recording its data type and shape.
+///
+/// The capsule isn't consumed: its producer frees the tensor once the capsule is garbage collected.
+pub(crate) fn with_tensor<R>(
+    tensor: &PyAny,
+    f: impl FnOnce(&[u8], Encoding) -> PyResult<R>,
+) -> PyResult<R> {
+    let capsule = if unsafe { ffi::PyCapsule_CheckExact(tensor.as_ptr()) } != 0 {
+        tensor
+    } else if tensor.hasattr("__dlpack__")? {
+        tensor.call_method0("__dlpack__")?
+    } else {
+        return Err(pyo3::exceptions::PyTypeError::new_err(
+            "expected a DLPack capsule, or an object implementing `__dlpack__`",
+        ));
+    };
+    let managed =
+        unsafe { ffi::PyCapsule_GetPointer(capsule.as_ptr(), DLTENSOR.as_ptr() as *const c_char) }
+            as *const DLManagedTensor;
+    if managed.is_null() {
+        return Err(PyErr::take(capsule.py())
+            .unwrap_or_else(|| invalid("the DLPack capsule was already consumed".to_string())));
+    }
+    let tensor = unsafe { &(*managed).dl_tensor };
+    match tensor.device.device_type {
+        DL_CPU | DL_CUDA_HOST | DL_CUDA_MANAGED => {}
+        DL_CUDA => return Err(invalid(
+            "tensors in CUDA device memory must be copied to the host first, with `tensor.cpu()` or `tensor.get()` for instance: these bindings don't link the CUDA runtime".to_string(),
+        )),
+        other => return Err(invalid(format!(
+            "tensors on DLPack devices of type {other} can't be read from the host"
+        ))),
+    }
+    let shape: &[i64] = match tensor.ndim {
+        0 => &[],
+        ndim => unsafe { std::slice::from_raw_parts(tensor.shape, ndim as usize) },
+    };
+    let dtype = tensor.dtype;
+    let len = size(dtype, shape)
+        .ok_or_else(|| invalid("the tensor's shape is invalid or too large".to_string()))?;
+    if !tensor.strides.is_null() {
+        let strides = unsafe { std::slice::from_raw_parts(tensor.strides, shape.len()) };
+        let mut expected = 1;
+        for (&dim, &stride) in shape.iter().zip(strides).rev() {
+            if dim != 1 && stride != expected {
+                return Err(invalid(
+                    "only C-contiguous tensors can be published: make them contiguous first"
+                        .to_string(),
+                ));
+            }
+            expected *= dim;
+        }
+    }
+    let bytes = match len {
+        0 => &[][..],
+        len => unsafe {
+            std::slice::from_raw_parts(
+                (tensor.data as *const u8).add(tensor.byte_offset as usize),
+                len,
+            )
+        },
+    };
+    let shape = shape
+        .iter()
+        .map(|dim| dim.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let encoding = Encoding::Exact(KnownEncoding::AppOctetStream)
+        .with_suffix(format!(
+            ";dtype={}.{}.{};shape={shape}",
+            dtype.code, dtype.bits, dtype.lanes
+        ))
+        .map_err(|e| invalid(e.to_string()))?;
+    f(bytes, encoding)
+}
+
+/// The data type and shape recorded in `encoding` by `with_tensor`, if they match the `len` bytes of the payload.
+fn layout(encoding: &Encoding, len: usize) -> Option<(DLDataType, Vec<i64>)> {
+    let mut dtype = None;
+    let mut shape = None;
+    for parameter in encoding.suffix().split(';') {
+        if let Some(value) = parameter.strip_prefix("dtype=") {
+            let mut fields = value.split('.');
+            dtype = Some(DLDataType {
+                code: fields.next()?.parse().ok()?,
+                bits: fields.next()?.parse().ok()?,
+                lanes: fields.next()?.parse().ok()?,
+            });
+        } else if let Some(value) = parameter.strip_prefix("shape=") {
+            shape = Some(match value {
+                "" => Vec::new(),
+                value => value
+                    .split(',')
+                    .map(|dim| dim.parse().ok())
+                    .collect::<Option<Vec<i64>>>()?,
+            });
+        }
+    }
+    let (dtype, shape) = (dtype?, shape?);
+    (size(dtype, &shape)? == len).then_some((dtype, shape))
+}
+
+struct Context {
+    _data: Vec<u8>,
+    shape: Vec<i64>,
+}
+
+unsafe extern "C" fn delete(managed: *mut DLManagedTensor) {
+    let managed = Box::from_raw(managed);
+    drop(Box::from_raw(managed.manager_ctx as *mut Context));
+}
+
+unsafe extern "C" fn destroy_capsule(capsule: *mut ffi::PyObject) {
+    // consumers rename the capsules they take ownership of
+    let name = DLTENSOR.as_ptr() as *const c_char;
+    if ffi::PyCapsule_IsValid(capsule, name) != 0 {
+        let managed = ffi::PyCapsule_GetPointer(capsule, name) as *mut DLManagedTensor;
+        if let Some(deleter) = (*managed).deleter {
+            deleter(managed)
+        }
+    }
+}
+
+/// Exports a copy of `payload` as a DLPack capsule holding a host tensor.
+///
+/// The tensor is copied since consumers may write to it: received buffers may be shared by other samples,
+/// or be segments of shared memory, and Python bytes are immutable.
+pub(crate) fn export(py: Python, payload: &Payload, encoding: &Encoding) -> PyResult<PyObject> {
+    let mut data = match payload {
+        Payload::Zenoh(buf) => buf.contiguous().into_owned(),
+        Payload::Python(bytes) => bytes.as_bytes(py).to_vec(),
+    };
+    let len = data.len();
+    let (dtype, shape) = layout(encoding, len).unwrap_or((
+        DLDataType {
+            code: DL_UINT,
+            bits: 8,
+            lanes: 1,
+        },
+        vec![len as i64],
+    ));
+    let data_ptr = data.as_mut_ptr();
+    let mut context = Box::new(Context { _data: data, shape });
+    let managed = Box::new(DLManagedTensor {
+        dl_tensor: DLTensor {
+            data: data_ptr as *mut c_void,
+            device: DLDevice {
+                device_type: DL_CPU,
+                device_id: 0,
+            },
+            ndim: context.shape.len() as i32,
+            dtype,
+            shape: context.shape.as_mut_ptr(),
+            strides: std::ptr::null_mut(),
+            byte_offset: 0,
+        },
+        manager_ctx: std::ptr::null_mut(),
+        deleter: Some(delete),
+    });
+    let managed = Box::into_raw(managed);
+    unsafe {
+        (*managed).manager_ctx = Box::into_raw(context) as *mut c_void;
+        let capsule = ffi::PyCapsule_New(
+            managed as *mut c_void,
+            DLTENSOR.as_ptr() as *const c_char,
+            Some(destroy_capsule),
+        );
+        if capsule.is_null() {
+            delete(managed);
+            return Err(PyErr::fetch(py));
+        }
+        Ok(PyObject::from_owned_ptr(py, capsule))
+    }
+}
@@ -16,6 +16,7 @@ use pyo3::prelude::*;
 use zenoh::prelude::{Encoding, KnownEncoding, Priority, SampleKind};
 use zenoh::publication::CongestionControl;
 use zenoh::query::{ConsolidationMode, QueryTarget};
+use zenoh::sample::Locality;
 use zenoh::subscriber::Reliability;
 
 #[pyclass(subclass)]
@@ -186,6 +187,52 @@ impl core::fmt::Debug for _SampleKind {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ChangeKind {
+    Put,
+    Patch,
+    Delete,
+}
+impl From<SampleKind> for ChangeKind {
+    fn from(kind: SampleKind) -> Self {
+        match kind {
+            SampleKind::Put => ChangeKind::Put,
+            SampleKind::Delete => ChangeKind::Delete,
+        }
+    }
+}
+
+#[pyclass(subclass)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct _ChangeKind(pub(crate) ChangeKind);
+#[pymethods]
+impl _ChangeKind {
+    #[new]
+    pub fn new(this: Self) -> Self {
+        this
+    }
+    #[classattr]
+    pub const PUT: Self = Self(ChangeKind::Put);
+    #[classattr]
+    pub const PATCH: Self = Self(ChangeKind::Patch);
+    #[classattr]
+    pub const DELETE: Self = Self(ChangeKind::Delete);
+    pub fn __str__(&self) -> &'static str {
+        match self.0 {
+            ChangeKind::Put => "PUT",
+            ChangeKind::Patch => "PATCH",
+            ChangeKind::Delete => "DELETE",
+        }
+    }
+    fn __richcmp__(&self, other: &Self, op: pyo3::pyclass::CompareOp) -> PyResult<bool> {
+        match op {
+            pyo3::pyclass::CompareOp::Eq => Ok(self == other),
+            pyo3::pyclass::CompareOp::Ne => Ok(self != other),
+            _ => Err(zenoh_core::zerror!("ChangeKind does not support comparison").to_pyerr()),
+        }
+    }
+}
+
 #[pyclass(subclass)]
 #[derive(Clone, PartialEq, Eq)]
 pub struct _CongestionControl(pub(crate) CongestionControl);
@@ -244,6 +291,37 @@ impl _Reliability {
     }
 }
 
+#[pyclass(subclass)]
+#[derive(Clone, PartialEq, Eq)]
+pub struct _Locality(pub(crate) Locality);
+#[pymethods]
+impl _Locality {
+    #[new]
+    pub fn new(this: Self) -> Self {
+        this
+    }
+    fn __richcmp__(&self, other: &Self, op: pyo3::pyclass::CompareOp) -> PyResult<bool> {
+        match op {
+            pyo3::pyclass::CompareOp::Eq => Ok(self == other),
+            pyo3::pyclass::CompareOp::Ne => Ok(self != other),
+            _ => Err(zenoh_core::zerror!("Locality does not support comparison").to_pyerr()),
+        }
+    }
+    #[classattr]
+    pub const SESSION_LOCAL: Self = Self(Locality::SessionLocal);
+    #[classattr]
+    pub const REMOTE: Self = Self(Locality::Remote);
+    #[classattr]
+    pub const ANY: Self = Self(Locality::Any);
+    pub fn __str__(&self) -> &'static str {
+        match self.0 {
+            Locality::SessionLocal => "SESSION_LOCAL",
+            Locality::Remote => "REMOTE",
+            Locality::Any => "ANY",
+        }
+    }
+}
+
 #[pyclass(subclass)]
 #[derive(Clone, PartialEq, Eq)]
 pub struct _QueryTarget(pub(crate) QueryTarget);
@@ -0,0 +1,289 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! File transfers over queryables, in chunks that let interrupted transfers resume.
+//!
+//! The files of a served directory are exposed below its key expression, each component of their relative path being
+//! a chunk of their key. Querying a file's key with `stat` replies with a JSON object holding its `size` and `modified`
+//! time, in seconds since the UNIX epoch, while querying it with `offset=<n>;length=<m>` replies with up to `m` of its
+//! bytes from offset `n`, fewer if the server caps the size of its chunks or the file ends. Failures are error replies,
+//! whose payloads describe them.
+use std::{
+    fs::{File, OpenOptions},
+    io::{ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use pyo3::prelude::*;
+use zenoh::{
+    prelude::{sync::SyncResolve, Encoding, KnownEncoding, Sample, SessionDeclarations, Value},
+    queryable::Query,
+    selector::Parameters,
+    Session,
+};
+use zenoh_buffers::buffer::SplitBuffer;
+
+use crate::{
+    keyexpr::_KeyExpr, queryable::_Queryable, run_interruptibly, session::_Session, stats::Entity,
+    DropWithoutGil, ToPyErr,
+};
+
+fn other(message: String) -> std::io::Error {
+    std::io::Error::new(ErrorKind::Other, message)
+}
+
+/// The file `relative` designates below `root`, refusing paths that would escape it.
+fn resolve(root: &Path, relative: &str) -> std::io::Result<PathBuf> {
+    let mut path = root.to_path_buf();
+    for component in relative.split('/') {
+        if component == "." || component == ".." || component.contains('\\') {
+            return Err(other(format!("`{relative}` isn't a valid file path")));
+        }
+        path.push(component);
+    }
+    // symbolic links may still lead out of the served directory
+    let path = path
+        .canonicalize()
+        .map_err(|e| std::io::Error::new(e.kind(), format!("`{relative}`: {e}")))?;
+    if !path.starts_with(root) {
+        return Err(other(format!(
+            "`{relative}` is outside the served directory"
+        )));
+    }
+    Ok(path)
+}
+
+fn answer(root: &Path, prefix: &str, max_chunk_size: u64, query: &Query) -> std::io::Result<Value> {
+    let key_expr = query.key_expr().as_str();
+    let relative = key_expr
+        .strip_prefix(prefix)
+        .and_then(|relative| relative.strip_prefix('/'))
+        .ok_or_else(|| other(format!("`{key_expr}` designates no file")))?;
+    let path = resolve(root, relative)?;
+    let mut file = File::open(&path)?;
+    let metadata = file.metadata()?;
+    if !metadata.is_file() {
+        return Err(other(format!("`{relative}` isn't a file")));
+    }
+    let mut offset = None;
+    let mut length = None;
+    let mut stat = false;
+    for (name, value) in query.parameters().decode() {
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| other(format!("`{name}` must be a non-negative integer")))
+        };
+        match name.as_ref() {
+            "stat" => stat = true,
+            "offset" => offset = Some(number()?),
+            "length" => length = Some(number()?),
+            _ => {}
+        }
+    }
+    if stat {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0., |time| time.as_secs_f64());
+        let stat = serde_json::json!({ "size": metadata.len(), "modified": modified });
+        return Ok(Value::from(stat.to_string()).encoding(Encoding::Exact(KnownEncoding::AppJson)));
+    }
+    let offset = offset.unwrap_or(0);
+    let length = length.unwrap_or(max_chunk_size).min(max_chunk_size);
+    let mut chunk = Vec::new();
+    file.seek(SeekFrom::Start(offset))?;
+    file.take(length).read_to_end(&mut chunk)?;
+    Ok(Value::from(chunk).encoding(Encoding::Exact(KnownEncoding::AppOctetStream)))
+}
+
+/// Declares a queryable serving the files below `path` on `key_expr`, in chunks of up to `max_chunk_size` bytes.
+#[pyfunction]
+#[pyo3(signature = (session, key_expr, path, max_chunk_size = 1 << 20))]
+pub fn serve_directory(
+    py: Python,
+    session: &_Session,
+    key_expr: &_KeyExpr,
+    path: &str,
+    max_chunk_size: u64,
+) -> PyResult<_Queryable> {
+    if max_chunk_size == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "`max_chunk_size` must be positive",
+        ));
+    }
+    let root = PathBuf::from(path)
+        .canonicalize()
+        .map_err(|e| e.to_pyerr())?;
+    if !root.is_dir() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "`{path}` isn't a directory"
+        )));
+    }
+    let prefix = session.2.outgoing(&key_expr.0);
+    let served = prefix.join("**").map_err(|e| e.to_pyerr())?;
+    let prefix = prefix.as_str().to_owned();
+    let queryable = py
+        .allow_threads(|| {
+            session
+                .0
+                .declare_queryable(served)
+                .callback(move |query: Query| {
+                    // wildcard queries are left to the other queryables, a transfer being about a single file
+                    if query.key_expr().is_wild() {
+                        return;
+                    }
+                    let reply = match answer(&root, &prefix, max_chunk_size, &query) {
+                        Ok(value) => Ok(Sample::new(query.key_expr().clone(), value)),
+                        Err(e) => Err(Value::from(e.to_string())),
+                    };
+                    let _ = query.reply(reply).res_sync();
+                })
+                .res_sync()
+        })
+        .map_err(|e| e.to_pyerr())?;
+    Ok(_Queryable(
+        DropWithoutGil::new(queryable),
+        session.1.declare(Entity::Queryable),
+        None,
+    ))
+}
+
+/// Sends `selector`, returning the payload of its first reply.
+fn query(session: &Session, selector: String, timeout: Duration) -> std::io::Result<Vec<u8>> {
+    let replies = session
+        .get(selector.as_str())
+        .timeout(timeout)
+        .res_sync()
+        .map_err(|e| other(e.to_string()))?;
+    match replies.recv() {
+        Ok(reply) => match reply.sample {
+            Ok(sample) => Ok(sample.value.payload.contiguous().into_owned()),
+            Err(value) => Err(other(format!(
+                "`{selector}` failed: {}",
+                String::from_utf8_lossy(&value.payload.contiguous())
+            ))),
+        },
+        Err(_) => Err(std::io::Error::new(
+            ErrorKind::TimedOut,
+            format!("`{selector}` received no reply"),
+        )),
+    }
+}
+
+/// The size and modification time in a reply to a `stat` query.
+fn parse_stat(stat: &[u8]) -> Option<(u64, f64)> {
+    let stat = serde_json::from_slice::<serde_json::Value>(stat).ok()?;
+    Some((stat.get("size")?.as_u64()?, stat.get("modified")?.as_f64()?))
+}
+
+fn download(
+    session: &Session,
+    key_expr: &str,
+    dest: &Path,
+    resume: bool,
+    chunk_size: u64,
+    timeout: Duration,
+) -> std::io::Result<u64> {
+    let stat = query(session, format!("{key_expr}?stat"), timeout)?;
+    let served = parse_stat(&stat)
+        .ok_or_else(|| other(format!("`{key_expr}` replied with an invalid stat")))?;
+    let size = served.0;
+    let mut partial = dest.to_path_buf().into_os_string();
+    partial.push(".part");
+    // the stat of the version of the file the partial one holds the beginning of
+    let mut version = partial.clone();
+    version.push(".stat");
+    let partial = PathBuf::from(partial);
+    let version = PathBuf::from(version);
+    let resumed = resume
+        && std::fs::read(&version)
+            .ok()
+            .and_then(|version| parse_stat(&version))
+            == Some(served);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(!resumed)
+        .open(&partial)?;
+    if !resumed {
+        std::fs::write(&version, &stat)?;
+    }
+    let mut offset = file.metadata()?.len();
+    if offset > size {
+        // the partial file was altered since
+        file.set_len(0)?;
+        offset = 0;
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    while offset < size {
+        let length = chunk_size.min(size - offset);
+        let chunk = query(
+            session,
+            format!("{key_expr}?offset={offset};length={length}"),
+            timeout,
+        )?;
+        if chunk.is_empty() {
+            return Err(other(format!(
+                "`{key_expr}` ended at {offset} bytes, rather than {size}"
+            )));
+        }
+        file.write_all(&chunk)?;
+        offset += chunk.len() as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&partial, dest)?;
+    match std::fs::remove_file(&version) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(size),
+    }
+}
+
+/// Downloads the file served on `key_expr` to `dest`, in chunks of up to `chunk_size` bytes, returning its size.
+///
+/// The chunks are written to `dest` with a `.part` suffix until the file is complete, which lets `resume`
+/// carry on with the chunks received by previous attempts, unless the served file changed since: its size and
+/// modification time are kept with a `.part.stat` suffix meanwhile.
+#[pyfunction]
+#[pyo3(signature = (session, key_expr, dest, resume = true, chunk_size = 1 << 20, timeout = 10.0))]
+pub fn fetch(
+    py: Python,
+    session: &_Session,
+    key_expr: &_KeyExpr,
+    dest: &str,
+    resume: bool,
+    chunk_size: u64,
+    timeout: f64,
+) -> PyResult<u64> {
+    if chunk_size == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "`chunk_size` must be positive",
+        ));
+    }
+    let timeout = Duration::try_from_secs_f64(timeout).map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err(
+            "`timeout` must be a non-negative number of seconds",
+        )
+    })?;
+    let shared: Arc<Session> = session.0.clone();
+    let key_expr = session.2.outgoing(&key_expr.0).as_str().to_owned();
+    let dest = PathBuf::from(dest);
+    run_interruptibly(py, move || {
+        download(&shared, &key_expr, &dest, resume, chunk_size, timeout)
+    })?
+    .map_err(|e| e.to_pyerr())
+}
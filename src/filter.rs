@@ -0,0 +1,119 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+use std::sync::Arc;
+
+use pyo3::{prelude::*, types::PyBytes};
+use zenoh::prelude::{IntoCallbackReceiverPair, Sample};
+use zenoh_buffers::buffer::SplitBuffer;
+
+use crate::ToPyErr;
+
+#[derive(Clone, Debug)]
+enum Filter {
+    Prefix(Vec<u8>),
+    JsonField {
+        path: Vec<String>,
+        value: serde_json::Value,
+    },
+}
+
+/// A predicate on sample payloads, evaluated before the samples are handed to Python.
+#[pyclass(subclass)]
+#[derive(Clone, Debug)]
+pub struct _SampleFilter(Filter);
+#[pymethods]
+impl _SampleFilter {
+    #[new]
+    pub fn pynew(this: Self) -> Self {
+        this
+    }
+    #[staticmethod]
+    pub fn prefix(prefix: &PyBytes) -> Self {
+        _SampleFilter(Filter::Prefix(prefix.as_bytes().to_owned()))
+    }
+    #[staticmethod]
+    pub fn json_field(path: &str, value: &str) -> PyResult<Self> {
+        let value = serde_json::from_str(value).map_err(|e| e.to_pyerr())?;
+        Ok(_SampleFilter(Filter::JsonField {
+            path: path.split('.').map(str::to_owned).collect(),
+            value,
+        }))
+    }
+    fn __str__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+impl _SampleFilter {
+    pub(crate) fn matches(&self, sample: &Sample) -> bool {
+        let payload = sample.value.payload.contiguous();
+        match &self.0 {
+            Filter::Prefix(prefix) => payload.starts_with(prefix),
+            Filter::JsonField { path, value } => {
+                let json = match serde_json::from_slice::<serde_json::Value>(&payload) {
+                    Ok(json) => json,
+                    Err(_) => return false,
+                };
+                let mut field = &json;
+                for segment in path {
+                    field = match field {
+                        serde_json::Value::Object(map) => match map.get(segment) {
+                            Some(field) => field,
+                            None => return false,
+                        },
+                        serde_json::Value::Array(array) => {
+                            match segment.parse::<usize>().ok().and_then(|i| array.get(i)) {
+                                Some(field) => field,
+                                None => return false,
+                            }
+                        }
+                        _ => return false,
+                    };
+                }
+                field == value
+            }
+        }
+    }
+}
+
+/// Wraps a subscriber's handler so that only the samples matching `filter` reach it.
+pub(crate) struct Filtered<H> {
+    handler: H,
+    filter: Option<_SampleFilter>,
+}
+impl<H> Filtered<H> {
+    pub(crate) fn new(handler: H, filter: Option<_SampleFilter>) -> Self {
+        Filtered { handler, filter }
+    }
+}
+impl<H> IntoCallbackReceiverPair<'static, Sample> for Filtered<H>
+where
+    H: IntoCallbackReceiverPair<'static, Sample>,
+{
+    type Receiver = H::Receiver;
+
+    fn into_cb_receiver_pair(self) -> (zenoh::handlers::Callback<'static, Sample>, Self::Receiver) {
+        let (callback, receiver) = self.handler.into_cb_receiver_pair();
+        match self.filter {
+            None => (callback, receiver),
+            Some(filter) => (
+                Arc::new(move |sample: Sample| {
+                    if filter.matches(&sample) {
+                        callback(sample)
+                    }
+                }),
+                receiver,
+            ),
+        }
+    }
+}
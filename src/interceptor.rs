@@ -0,0 +1,314 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! Session-wide hooks on the samples a session publishes (egress) and receives through its subscribers (ingress).
+//!
+//! A hook selects samples by key expression and payload filter in Rust. The selected samples are dropped,
+//! unless the hook has a Python callback, which decides their fate instead, or simulates a fault.
+//! Callbacks are passed the samples' attachments as `dict`s of `bytes`, and may return the attachments to carry instead:
+//! payloads are left as they are.
+//!
+//! The samples a fault delays on ingress are handed over to a timer task of the async runtime, which runs the remaining
+//! hooks and delivers them once due: zenoh's reception tasks are never held up. On egress, the delay is spent by
+//! the thread publishing the sample, as a slow link would.
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyDict},
+};
+use zenoh::{
+    prelude::{KeyExpr, Sample, SampleKind, Value},
+    sample::Attachment,
+};
+
+use crate::filter::_SampleFilter;
+use crate::value::_Sample;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Ingress,
+    Egress,
+}
+
+/// A simulated failure, for resilience tests.
+#[derive(Clone, Copy)]
+pub(crate) struct Fault {
+    /// The probability for each sample to be lost.
+    pub(crate) drop: f64,
+    /// How long each sample is held back, plus up to `jitter`.
+    pub(crate) delay: Duration,
+    pub(crate) jitter: Duration,
+}
+
+/// What a hook does to the samples it selects.
+pub(crate) enum Action {
+    Drop,
+    Callback(PyObject),
+    Fault(Fault),
+}
+
+thread_local! {
+    static RANDOM_STATE: Cell<u64> = Cell::new({
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64;
+        // differ between the threads that start together
+        let local = 0u8;
+        (nanos ^ (&local as *const u8 as u64)) | 1
+    });
+}
+
+/// A uniform number in `[0, 1)`, from a xorshift generator: faults need to be unpredictable, not secure.
+fn random() -> f64 {
+    RANDOM_STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        state.set(x);
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    })
+}
+
+pub(crate) fn attachment_to_dict(
+    py: Python,
+    attachment: Option<&Attachment>,
+) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new(py);
+    if let Some(attachment) = attachment {
+        for (name, value) in attachment.iter() {
+            dict.set_item(
+                PyBytes::new(py, name.as_ref()),
+                PyBytes::new(py, value.as_ref()),
+            )?;
+        }
+    }
+    Ok(dict.into())
+}
+
+/// The attachment a callback returned, `None` if empty. Names and values may be `bytes` or `str`.
+pub(crate) fn attachment_from_dict(dict: &PyDict) -> PyResult<Option<Attachment>> {
+    if dict.is_empty() {
+        return Ok(None);
+    }
+    let bytes = |item: &'_ PyAny| -> PyResult<Vec<u8>> {
+        match item.extract::<&[u8]>() {
+            Ok(bytes) => Ok(bytes.to_vec()),
+            Err(_) => Ok(item.extract::<&str>()?.as_bytes().to_vec()),
+        }
+    };
+    let mut attachment = Attachment::new();
+    for (name, value) in dict {
+        attachment.insert(&bytes(name)?, &bytes(value)?);
+    }
+    Ok(Some(attachment))
+}
+
+/// What running a hook on a sample comes down to.
+enum Verdict {
+    Keep(Sample),
+    Drop,
+    Delay(Sample, Duration),
+}
+
+struct Hook {
+    id: u64,
+    ingress: bool,
+    egress: bool,
+    key_expr: Option<KeyExpr<'static>>,
+    filter: Option<_SampleFilter>,
+    action: Action,
+}
+impl Hook {
+    fn applies(&self, direction: Direction) -> bool {
+        match direction {
+            Direction::Ingress => self.ingress,
+            Direction::Egress => self.egress,
+        }
+    }
+    fn selects(&self, sample: &Sample) -> bool {
+        self.key_expr
+            .as_ref()
+            .map_or(true, |k| k.intersects(&sample.key_expr))
+            && self.filter.as_ref().map_or(true, |f| f.matches(sample))
+    }
+    fn run(&self, mut sample: Sample) -> Verdict {
+        let callback = match &self.action {
+            Action::Drop => return Verdict::Drop,
+            Action::Fault(fault) => {
+                if fault.drop > 0. && random() < fault.drop {
+                    return Verdict::Drop;
+                }
+                let delay = fault.delay + fault.jitter.mul_f64(random());
+                return if delay.is_zero() {
+                    Verdict::Keep(sample)
+                } else {
+                    Verdict::Delay(sample, delay)
+                };
+            }
+            Action::Callback(callback) => callback,
+        };
+        Python::with_gil(|py| {
+            let verdict = attachment_to_dict(py, sample.attachment.as_ref())
+                .and_then(|attachment| {
+                    callback.call1(py, (_Sample::from(sample.clone()), attachment))
+                })
+                .and_then(|result| {
+                    let result = result.as_ref(py);
+                    if result.is_none() {
+                        Ok(true)
+                    } else if let Ok(keep) = result.extract::<bool>() {
+                        Ok(keep)
+                    } else {
+                        sample.attachment = attachment_from_dict(result.downcast()?)?;
+                        Ok(true)
+                    }
+                });
+            match verdict {
+                Ok(true) => Verdict::Keep(sample),
+                Ok(false) => Verdict::Drop,
+                Err(e) => {
+                    // a failing hook must not stop the traffic: report it and let the sample through
+                    e.print(py);
+                    Verdict::Keep(sample)
+                }
+            }
+        })
+    }
+}
+
+/// Runs `hooks[from..]` on `sample`, up to the first one delaying it, returning the verdict and the index of the hook
+/// to resume from.
+fn run_hooks(hooks: &[Arc<Hook>], from: usize, mut sample: Sample) -> (Verdict, usize) {
+    for (at, hook) in hooks.iter().enumerate().skip(from) {
+        if hook.selects(&sample) {
+            match hook.run(sample) {
+                Verdict::Keep(kept) => sample = kept,
+                verdict => return (verdict, at + 1),
+            }
+        }
+    }
+    (Verdict::Keep(sample), hooks.len())
+}
+
+/// Runs `hooks[from..]` on `sample`, then passes it to `deliver` unless dropped, from a timer task once delayed.
+fn resume(
+    hooks: Vec<Arc<Hook>>,
+    from: usize,
+    sample: Sample,
+    deliver: impl FnOnce(Sample) + Send + 'static,
+) {
+    match run_hooks(&hooks, from, sample) {
+        (Verdict::Keep(sample), _) => deliver(sample),
+        (Verdict::Drop, _) => {}
+        (Verdict::Delay(sample, delay), next) => {
+            async_std::task::spawn(async move {
+                async_std::task::sleep(delay).await;
+                resume(hooks, next, sample, deliver)
+            });
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Interceptors {
+    hooks: RwLock<Vec<Arc<Hook>>>,
+    next_id: AtomicU64,
+}
+impl Interceptors {
+    pub(crate) fn add(
+        &self,
+        ingress: bool,
+        egress: bool,
+        key_expr: Option<KeyExpr<'static>>,
+        filter: Option<_SampleFilter>,
+        action: Action,
+    ) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.hooks.write().unwrap().push(Arc::new(Hook {
+            id,
+            ingress,
+            egress,
+            key_expr,
+            filter,
+            action,
+        }));
+        id
+    }
+    pub(crate) fn remove(&self, id: u64) -> bool {
+        let mut hooks = self.hooks.write().unwrap();
+        let count = hooks.len();
+        hooks.retain(|hook| hook.id != id);
+        hooks.len() != count
+    }
+    fn hooks(&self, direction: Direction) -> Vec<Arc<Hook>> {
+        self.hooks
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|hook| hook.applies(direction))
+            .cloned()
+            .collect()
+    }
+    /// Runs the ingress hooks on a received `sample`, in the order they were added, then passes it to `deliver`
+    /// unless one of them dropped it. Samples delayed by a fault are delivered from a timer task.
+    pub(crate) fn intercept_ingress(
+        &self,
+        sample: Sample,
+        deliver: impl FnOnce(Sample) + Send + 'static,
+    ) {
+        resume(self.hooks(Direction::Ingress), 0, sample, deliver)
+    }
+    /// Runs the egress hooks on `sample`, in the order they were added.
+    ///
+    /// Returns `None` if one of them dropped the sample. The GIL is only taken if a hook with a callback selects the sample,
+    /// so callers holding it should release it first, in case some fault delays the sample.
+    fn intercept_egress(&self, mut sample: Sample) -> Option<Sample> {
+        let hooks = self.hooks(Direction::Egress);
+        let mut from = 0;
+        loop {
+            match run_hooks(&hooks, from, sample) {
+                (Verdict::Keep(sample), _) => return Some(sample),
+                (Verdict::Drop, _) => return None,
+                (Verdict::Delay(delayed, delay), next) => {
+                    std::thread::sleep(delay);
+                    sample = delayed;
+                    from = next;
+                }
+            }
+        }
+    }
+    /// Intercepts a value about to be put on `key_expr` with `attachment`, returning the sample to send, attachment included, if any.
+    pub(crate) fn intercept_put(
+        &self,
+        key_expr: &KeyExpr<'_>,
+        value: Value,
+        attachment: Option<Attachment>,
+    ) -> Option<Sample> {
+        let mut sample = Sample::new(key_expr.clone().into_owned(), value);
+        sample.attachment = attachment;
+        self.intercept_egress(sample)
+    }
+    /// Intercepts a deletion about to be sent on `key_expr`, returning the sample to send, attachment included, if any.
+    pub(crate) fn intercept_delete(&self, key_expr: &KeyExpr<'_>) -> Option<Sample> {
+        let mut sample = Sample::new(key_expr.clone().into_owned(), Value::empty());
+        sample.kind = SampleKind::Delete;
+        self.intercept_egress(sample)
+    }
+}
@@ -12,15 +12,32 @@
 //   ZettaScale Zenoh team, <zenoh@zettascale.tech>
 //
 use pyo3::{prelude::*, types::PyDict, ToPyObject};
+mod audit;
 mod closures;
 mod config;
+mod crypto;
+mod dlpack;
 mod enums;
+mod filetransfer;
+mod filter;
+mod interceptor;
 mod keyexpr;
+mod offline;
+mod outbox;
 mod queryable;
+mod ratelimit;
+mod record;
+mod replies;
+mod rewrite;
+mod schema;
 mod session;
+#[cfg(feature = "shared-memory")]
+mod shm;
+mod stats;
 mod value;
 
 pyo3::create_exception!(zenoh, ZError, pyo3::exceptions::PyException);
+pyo3::create_exception!(zenoh, RecvTimeoutError, pyo3::exceptions::PyTimeoutError);
 
 pub(crate) trait ToPyErr {
     fn to_pyerr(self) -> PyErr;
@@ -39,6 +56,149 @@ impl<T, E: ToPyErr> ToPyResult<T> for Result<T, E> {
     }
 }
 
+/// How often blocking calls wake up to let Python run its signal handlers, so that Ctrl-C raises `KeyboardInterrupt`.
+const SIGNALS_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Receives from `receiver` without holding the GIL, until `deadline` if any,
+/// returning early with the signal handlers' exception if they raise one meanwhile.
+pub(crate) fn recv_interruptibly<T: Send>(
+    py: Python,
+    receiver: &flume::Receiver<T>,
+    deadline: Option<std::time::Instant>,
+) -> PyResult<Result<T, flume::RecvTimeoutError>> {
+    loop {
+        let wake = std::time::Instant::now() + SIGNALS_CHECK_INTERVAL;
+        let until = deadline.map_or(wake, |deadline| deadline.min(wake));
+        match py.allow_threads(|| receiver.recv_deadline(until)) {
+            Err(flume::RecvTimeoutError::Timeout)
+                if deadline.map_or(true, |deadline| until < deadline) =>
+            {
+                py.check_signals()?
+            }
+            result => return Ok(result),
+        }
+    }
+}
+
+/// Runs `f` on a thread of its own, waiting for its result in a way that signals may interrupt.
+///
+/// An interrupted `f` still runs to completion in the background, its result then being dropped.
+pub(crate) fn run_interruptibly<T: Send + 'static>(
+    py: Python,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> PyResult<T> {
+    let (sender, receiver) = flume::bounded(1);
+    std::thread::spawn(move || {
+        let _ = sender.send(f());
+    });
+    match recv_interruptibly(py, &receiver, None)? {
+        Ok(result) => Ok(result),
+        Err(_) => Err(zenoh_core::zerror!("the blocking operation panicked").to_pyerr()),
+    }
+}
+
+/// Holds a zenoh entity so that it is undeclared without the GIL when dropped.
+///
+/// Undeclaring may wait for zenoh's threads, which may themselves be waiting for the GIL to call into Python:
+/// dropping the entity while holding it, as Python does, could then deadlock.
+pub(crate) struct DropWithoutGil<T: Send>(Option<T>);
+impl<T: Send> DropWithoutGil<T> {
+    pub(crate) fn new(inner: T) -> Self {
+        DropWithoutGil(Some(inner))
+    }
+}
+impl<T: Send + Clone> Clone for DropWithoutGil<T> {
+    fn clone(&self) -> Self {
+        DropWithoutGil(self.0.clone())
+    }
+}
+impl<T: Send> std::ops::Deref for DropWithoutGil<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.0.as_ref().unwrap()
+    }
+}
+impl<T: Send> Drop for DropWithoutGil<T> {
+    fn drop(&mut self) {
+        if let Some(inner) = self.0.take() {
+            Python::with_gil(|py| py.allow_threads(move || drop(inner)))
+        }
+    }
+}
+
+/// Whether the async runtime backing zenoh was started, after which it can't be resized.
+static RUNTIME_STARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+/// The environment variables async-std reads its executor and blocking pool sizes from, when it starts.
+const RUNTIME_THREADS_VARS: [&str; 2] = ["ASYNC_STD_THREAD_COUNT", "BLOCKING_MAX_THREADS"];
+
+/// Marks the runtime as started, before an operation that may start it.
+pub(crate) fn start_runtime() {
+    RUNTIME_STARTED.store(true, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Sizes the async runtime backing the sessions: ``worker_threads`` run zenoh's tasks, and up to ``blocking_threads``
+/// are spawned for its blocking operations, such as file accesses. They default to the number of CPUs and to 500,
+/// unless set through the ``ASYNC_STD_THREAD_COUNT`` and ``BLOCKING_MAX_THREADS`` environment variables,
+/// which this sets. Small embedded devices may run with a single worker thread.
+///
+/// As the runtime is shared by all the sessions of the process, it must be sized before the first one is opened
+/// (or the first scout started): afterwards, this raises a ``ZError`` unless the sizes requested are those in use.
+///
+/// async-std has no API to size its runtime but these environment variables, and setting environment variables
+/// races with any other thread reading them: this raises a ``ZError`` as well if other Python threads are running,
+/// so it should be called first thing, from the main thread.
+///
+/// :Example:
+///
+/// >>> import zenoh
+/// >>> zenoh.configure_runtime(worker_threads=1)
+/// >>> s = zenoh.open({})
+#[pyfunction]
+#[pyo3(signature = (worker_threads = None, blocking_threads = None))]
+fn configure_runtime(
+    py: Python,
+    worker_threads: Option<usize>,
+    blocking_threads: Option<usize>,
+) -> PyResult<()> {
+    let single_threaded = py
+        .import("threading")?
+        .call_method0("active_count")?
+        .extract::<usize>()?
+        == 1;
+    for (var, threads) in RUNTIME_THREADS_VARS
+        .iter()
+        .zip([worker_threads, blocking_threads])
+    {
+        let threads = match threads {
+            Some(0) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "the runtime needs at least one thread of each kind",
+                ))
+            }
+            Some(threads) => threads.to_string(),
+            None => continue,
+        };
+        if std::env::var(var).ok().as_ref() == Some(&threads) {
+            continue;
+        }
+        if RUNTIME_STARTED.load(std::sync::atomic::Ordering::Relaxed) {
+            return Err(zenoh_core::zerror!(
+                "the runtime was already started: size it before opening the first session"
+            )
+            .to_pyerr());
+        }
+        if !single_threaded {
+            return Err(zenoh_core::zerror!(
+                "the runtime must be sized before other threads are started"
+            )
+            .to_pyerr());
+        }
+        // no other thread may read the environment meanwhile, as checked above
+        std::env::set_var(var, threads);
+    }
+    Ok(())
+}
+
 enum ExtractError {
     Unavailable(Option<PyErr>),
     Other(PyErr),
@@ -69,20 +229,33 @@ impl<K: ToPyObject> PyExtract<K> for PyDict {
 }
 
 #[pymodule]
-fn zenoh(_py: Python, m: &PyModule) -> PyResult<()> {
+fn zenoh(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("ZError", py.get_type::<ZError>())?;
+    m.add("RecvTimeoutError", py.get_type::<RecvTimeoutError>())?;
     m.add_class::<config::_Config>()?;
+    m.add_class::<config::_TlsConfig>()?;
     m.add_class::<closures::_Queue>()?;
+    m.add_class::<closures::_CallbackProfile>()?;
     m.add_class::<keyexpr::_KeyExpr>()?;
     m.add_class::<keyexpr::_Selector>()?;
     m.add_class::<session::_Session>()?;
     m.add_class::<session::_Publisher>()?;
+    m.add_class::<session::_PeriodicPublisher>()?;
     m.add_class::<session::_Subscriber>()?;
     m.add_class::<session::_PullSubscriber>()?;
+    m.add_class::<session::_Dispatcher>()?;
     m.add_class::<session::_Scout>()?;
+    m.add_class::<stats::_MetricsExporter>()?;
     m.add_class::<queryable::_Query>()?;
     m.add_class::<queryable::_Queryable>()?;
+    m.add_class::<record::_Recorder>()?;
+    m.add_class::<offline::_OfflineCache>()?;
+    #[cfg(feature = "shared-memory")]
+    m.add_class::<shm::_ShmManager>()?;
     m.add_class::<value::_Value>()?;
     m.add_class::<value::_Sample>()?;
+    m.add_class::<value::_Change>()?;
+    m.add_class::<filter::_SampleFilter>()?;
     m.add_class::<value::_Reply>()?;
     m.add_class::<value::_Timestamp>()?;
     m.add_class::<value::_Hello>()?;
@@ -91,11 +264,25 @@ fn zenoh(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<enums::_Encoding>()?;
     m.add_class::<enums::_Priority>()?;
     m.add_class::<enums::_SampleKind>()?;
+    m.add_class::<enums::_ChangeKind>()?;
     m.add_class::<enums::_Reliability>()?;
+    m.add_class::<enums::_Locality>()?;
     m.add_class::<enums::_QueryConsolidation>()?;
     m.add_class::<enums::_QueryTarget>()?;
     m.add_wrapped(wrap_pyfunction!(init_logger))?;
+    m.add_wrapped(wrap_pyfunction!(configure_runtime))?;
     m.add_wrapped(wrap_pyfunction!(session::scout))?;
+    m.add_wrapped(wrap_pyfunction!(record::record))?;
+    m.add_wrapped(wrap_pyfunction!(record::replay))?;
+    m.add_wrapped(wrap_pyfunction!(offline::offline_cache))?;
+    m.add_wrapped(wrap_pyfunction!(crypto::seal))?;
+    m.add_wrapped(wrap_pyfunction!(crypto::unseal))?;
+    m.add_wrapped(wrap_pyfunction!(filetransfer::serve_directory))?;
+    m.add_wrapped(wrap_pyfunction!(filetransfer::fetch))?;
+    m.add_wrapped(wrap_pyfunction!(schema::register_schema))?;
+    m.add_wrapped(wrap_pyfunction!(schema::schema_of))?;
+    m.add_wrapped(wrap_pyfunction!(replies::merge_sorted))?;
+    m.add_wrapped(wrap_pyfunction!(replies::dedup_latest))?;
     Ok(())
 }
 
@@ -0,0 +1,251 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! Persistent last values, for queries to be answered locally while the network is down.
+//!
+//! A cache file starts with [`MAGIC`], followed by one record per received sample, written by [`crate::record`]
+//! as those of captures are, except that reception times are nanoseconds since the UNIX epoch.
+//! Records are appended as samples arrive, and the file is compacted down to a record per key when opened,
+//! as well as whenever superseded records come to outnumber live ones.
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use pyo3::prelude::*;
+use zenoh::{
+    prelude::{sync::SyncResolve, Sample, SampleKind, SessionDeclarations, ZenohId},
+    subscriber::Subscriber,
+};
+
+use crate::{
+    keyexpr::_KeyExpr,
+    record::{invalid, read_record, write_record},
+    session::_Session,
+    value::{_Reply, _ZenohId},
+    DropWithoutGil, ToPyErr,
+};
+
+const MAGIC: &[u8; 8] = b"ZPYOFF01";
+/// Files with fewer records than this aren't compacted while running, whatever their share of superseded ones.
+const COMPACTION_THRESHOLD: u64 = 1024;
+
+pub(crate) struct Entry {
+    pub(crate) sample: Sample,
+    pub(crate) received: SystemTime,
+}
+
+pub(crate) fn write_entry(writer: &mut impl Write, entry: &Entry) -> std::io::Result<()> {
+    let received = entry
+        .received
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    write_record(writer, received, &entry.sample)
+}
+
+/// Reads the next entry of a cache file, returning `None` at the end of the file.
+pub(crate) fn read_entry(reader: &mut impl Read) -> std::io::Result<Option<Entry>> {
+    Ok(read_record(reader)?.map(|(received, sample)| Entry {
+        sample,
+        received: UNIX_EPOCH + received,
+    }))
+}
+
+/// The last value of each key, along with the file they are persisted to.
+struct Store {
+    path: PathBuf,
+    entries: HashMap<String, Entry>,
+    writer: Option<BufWriter<File>>,
+    /// The number of records in the file, superseded ones included.
+    records: u64,
+    error: Option<std::io::Error>,
+}
+impl Store {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        let mut store = Store {
+            path,
+            entries: HashMap::new(),
+            writer: None,
+            records: 0,
+            error: None,
+        };
+        match File::open(&store.path) {
+            Ok(file) => {
+                let mut reader = BufReader::new(file);
+                let mut magic = [0; 8];
+                reader.read_exact(&mut magic)?;
+                if &magic != MAGIC {
+                    return Err(invalid("not a zenoh-python offline cache"));
+                }
+                loop {
+                    match read_entry(&mut reader) {
+                        Ok(Some(entry)) => store.apply(entry),
+                        Ok(None) => break,
+                        // the last record was cut short, by a crash for instance: the previous ones are still valid
+                        Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        store.compact()?;
+        Ok(store)
+    }
+    fn apply(&mut self, entry: Entry) {
+        let key = entry.sample.key_expr.as_str().to_owned();
+        match entry.sample.kind {
+            SampleKind::Put => {
+                self.entries.insert(key, entry);
+            }
+            SampleKind::Delete => {
+                self.entries.remove(&key);
+            }
+        }
+    }
+    /// Rewrites the file with the live entries only, replacing the previous one atomically.
+    fn compact(&mut self) -> std::io::Result<()> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        writer.write_all(MAGIC)?;
+        for entry in self.entries.values() {
+            write_entry(&mut writer, entry)?;
+        }
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        std::fs::rename(&tmp, &self.path)?;
+        self.writer = Some(BufWriter::new(
+            OpenOptions::new().append(true).open(&self.path)?,
+        ));
+        self.records = self.entries.len() as u64;
+        Ok(())
+    }
+    fn record(&mut self, sample: Sample) {
+        let entry = Entry {
+            sample,
+            received: SystemTime::now(),
+        };
+        if self.error.is_none() {
+            // flushed right away, for the values to survive the process
+            let written = match self.writer.as_mut() {
+                Some(writer) => write_entry(writer, &entry).and_then(|()| writer.flush()),
+                None => Ok(()),
+            };
+            if let Err(e) = written {
+                self.error = Some(e);
+            }
+            self.records += 1;
+        }
+        self.apply(entry);
+        if self.error.is_none()
+            && self.records > COMPACTION_THRESHOLD
+            && self.records > 2 * self.entries.len() as u64
+        {
+            if let Err(e) = self.compact() {
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+/// A running offline cache, recording the last value of each key received on a key expression.
+#[pyclass(subclass)]
+pub struct _OfflineCache {
+    subscriber: Option<DropWithoutGil<Subscriber<'static, ()>>>,
+    store: Arc<Mutex<Store>>,
+    zid: ZenohId,
+}
+#[pymethods]
+impl _OfflineCache {
+    /// The cached values whose keys intersect `key_expr`, sorted by key, as `ok` replies
+    /// along with the time they were received at, in seconds since the UNIX epoch.
+    fn lookup(&self, key_expr: &_KeyExpr) -> Vec<(_Reply, f64)> {
+        let store = self.store.lock().unwrap();
+        let mut entries: Vec<_> = store
+            .entries
+            .values()
+            .filter(|entry| key_expr.0.intersects(&entry.sample.key_expr))
+            .collect();
+        entries.sort_unstable_by(|a, b| a.sample.key_expr.as_str().cmp(b.sample.key_expr.as_str()));
+        entries
+            .into_iter()
+            .map(|entry| {
+                let reply = _Reply {
+                    replier_id: _ZenohId(self.zid),
+                    reply: Ok(entry.sample.clone().into()),
+                };
+                let received = entry
+                    .received
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64();
+                (reply, received)
+            })
+            .collect()
+    }
+    fn __len__(&self) -> usize {
+        self.store.lock().unwrap().entries.len()
+    }
+    /// Stops recording, flushing the file. The values cached so far remain available to `lookup`.
+    fn close(&mut self, py: Python) -> PyResult<()> {
+        self.subscriber = None;
+        py.allow_threads(|| {
+            let mut store = self.store.lock().unwrap();
+            if let Some(e) = store.error.take() {
+                return Err(e);
+            }
+            match store.writer.as_mut() {
+                Some(writer) => writer.flush(),
+                None => Ok(()),
+            }
+        })
+        .map_err(|e| e.to_pyerr())
+    }
+}
+
+#[pyfunction]
+pub fn offline_cache(
+    py: Python,
+    session: &_Session,
+    key_expr: &_KeyExpr,
+    path: &str,
+) -> PyResult<_OfflineCache> {
+    let path = PathBuf::from(path);
+    let store = Arc::new(Mutex::new(
+        py.allow_threads(|| Store::open(path))
+            .map_err(|e| e.to_pyerr())?,
+    ));
+    let recorded = store.clone();
+    let subscriber = py
+        .allow_threads(|| {
+            session
+                .0
+                .declare_subscriber(&key_expr.0)
+                .callback(move |sample: Sample| recorded.lock().unwrap().record(sample))
+                .res_sync()
+        })
+        .map_err(|e| e.to_pyerr())?;
+    Ok(_OfflineCache {
+        subscriber: Some(DropWithoutGil::new(subscriber)),
+        store,
+        zid: session.0.zid(),
+    })
+}
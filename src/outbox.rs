@@ -0,0 +1,338 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! Store-and-forward of the publications issued while a session reaches no router nor peer.
+//!
+//! An outbox file starts with [`MAGIC`], followed by one record per publication held back, laid out as those of
+//! offline caches (see [`crate::offline`]), their reception times being the times the publications were issued at.
+//! Records are appended as publications are held back, and the file is rewritten once they are forwarded,
+//! as well as whenever evicted records come to outnumber held ones.
+//!
+//! Publications are timestamped when held back, and forwarded with that timestamp rather than the time they are sent at.
+//! Their attachments are forwarded as well, but aren't written to the file: those restored from it have none.
+use std::{
+    collections::{HashSet, VecDeque},
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use uhlc::{Timestamp, HLC};
+use zenoh::{
+    prelude::{sync::SyncResolve, Priority, Sample, SampleKind, SessionDeclarations},
+    publication::CongestionControl,
+    subscriber::Subscriber,
+    Session,
+};
+
+use crate::offline::{read_entry, write_entry, Entry};
+use crate::record::invalid;
+use crate::session::source_info;
+use crate::DropWithoutGil;
+
+const MAGIC: &[u8; 8] = b"ZPYOBX01";
+/// How often an outbox holding publications back checks whether they can be forwarded.
+const FORWARD_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The routers and peers a session has a transport to, kept up to date by the transport events of its admin space
+/// rather than queried for every publication.
+struct Transports {
+    zids: Arc<Mutex<HashSet<String>>>,
+    _events: DropWithoutGil<Subscriber<'static, ()>>,
+}
+impl Transports {
+    fn watch(session: &Arc<Session>) -> zenoh::Result<Self> {
+        let zids = Arc::new(Mutex::new(HashSet::new()));
+        let updated = zids.clone();
+        let events = session
+            .declare_subscriber(format!("@/session/{}/transport/unicast/*", session.zid()))
+            .callback(move |sample: Sample| {
+                let zid = match sample.key_expr.as_str().rsplit('/').next() {
+                    Some(zid) => zid.to_owned(),
+                    None => return,
+                };
+                let mut zids = updated.lock().unwrap();
+                match sample.kind {
+                    SampleKind::Put => zids.insert(zid),
+                    SampleKind::Delete => zids.remove(&zid),
+                };
+            })
+            .res_sync()?;
+        // the transports opened before the subscriber was declared
+        let info = session.info();
+        zids.lock().unwrap().extend(
+            info.routers_zid()
+                .res_sync()
+                .chain(info.peers_zid().res_sync())
+                .map(|zid| zid.to_string()),
+        );
+        Ok(Transports {
+            zids,
+            _events: DropWithoutGil::new(events),
+        })
+    }
+    /// Whether the session has any router or peer to send publications to.
+    fn reachable(&self) -> bool {
+        !self.zids.lock().unwrap().is_empty()
+    }
+}
+
+struct State {
+    entries: VecDeque<Entry>,
+    path: Option<PathBuf>,
+    writer: Option<BufWriter<File>>,
+    /// The number of records in the file, evicted ones included.
+    records: u64,
+    dropped: u64,
+    error: Option<std::io::Error>,
+}
+
+/// The publications of a publisher held back until its session reaches the network again, oldest first.
+pub(crate) struct Outbox {
+    session: Arc<Session>,
+    priority: Priority,
+    congestion: CongestionControl,
+    capacity: usize,
+    max_age: Option<Duration>,
+    transports: Transports,
+    /// Timestamps the publications held back if the session's own clock is disabled.
+    hlc: HLC,
+    state: Mutex<State>,
+}
+impl Outbox {
+    /// Opens an outbox, restoring the publications held in the file at `path` if any,
+    /// and starts the thread forwarding them once `session` reaches the network.
+    pub(crate) fn open(
+        session: Arc<Session>,
+        priority: Priority,
+        congestion: CongestionControl,
+        path: Option<PathBuf>,
+        capacity: usize,
+        max_age: Option<Duration>,
+    ) -> std::io::Result<Arc<Self>> {
+        let mut entries = VecDeque::new();
+        if let Some(path) = &path {
+            match File::open(path) {
+                Ok(file) => {
+                    let mut reader = BufReader::new(file);
+                    let mut magic = [0; 8];
+                    reader.read_exact(&mut magic)?;
+                    if &magic != MAGIC {
+                        return Err(invalid("not a zenoh-python outbox"));
+                    }
+                    loop {
+                        match read_entry(&mut reader) {
+                            Ok(Some(entry)) => entries.push_back(entry),
+                            Ok(None) => break,
+                            // the last record was cut short, by a crash for instance: the previous ones are still valid
+                            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                            Err(e) => return Err(e),
+                        }
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let transports = Transports::watch(&session)
+            .map_err(|e| std::io::Error::new(ErrorKind::Other, e.to_string()))?;
+        let outbox = Arc::new(Outbox {
+            session,
+            priority,
+            congestion,
+            capacity,
+            max_age,
+            transports,
+            hlc: HLC::default(),
+            state: Mutex::new(State {
+                entries,
+                path,
+                writer: None,
+                records: 0,
+                dropped: 0,
+                error: None,
+            }),
+        });
+        {
+            let mut state = outbox.state.lock().unwrap();
+            outbox.expire(&mut state);
+            while state.entries.len() > capacity {
+                state.entries.pop_front();
+                state.dropped += 1;
+            }
+            Self::rewrite(&mut state)?;
+        }
+        let forwarded = Arc::downgrade(&outbox);
+        std::thread::spawn(move || loop {
+            std::thread::sleep(FORWARD_INTERVAL);
+            match forwarded.upgrade() {
+                Some(outbox) => {
+                    outbox.forward();
+                }
+                None => break,
+            }
+        });
+        Ok(outbox)
+    }
+    /// Holds `sample` back if the session reaches no one, or if earlier publications are still held back,
+    /// for publications to be forwarded in the order they were issued.
+    ///
+    /// Returns `sample` if it may be sent right away instead.
+    pub(crate) fn hold(&self, sample: Sample) -> Option<Sample> {
+        let mut state = self.state.lock().unwrap();
+        if state.entries.is_empty() && self.transports.reachable() {
+            return Some(sample);
+        }
+        self.push(&mut state, sample);
+        self.forward_locked(&mut state);
+        None
+    }
+    /// Sends the publications held back if the session reaches the network, returning how many were.
+    pub(crate) fn forward(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        self.forward_locked(&mut state)
+    }
+    pub(crate) fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+    /// The number of publications evicted so far, for lack of room or because they expired.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+    /// The error that stopped the outbox from writing its file, if any: the publications are still held in memory.
+    pub(crate) fn take_error(&self) -> Option<std::io::Error> {
+        self.state.lock().unwrap().error.take()
+    }
+
+    fn new_timestamp(&self) -> Timestamp {
+        self.session.hlc().unwrap_or(&self.hlc).new_timestamp()
+    }
+    fn push(&self, state: &mut State, mut sample: Sample) {
+        if sample.timestamp.is_none() {
+            sample.timestamp = Some(self.new_timestamp());
+        }
+        let entry = Entry {
+            sample,
+            received: SystemTime::now(),
+        };
+        if state.error.is_none() {
+            if let Some(writer) = state.writer.as_mut() {
+                // flushed right away, for the publications to survive the process
+                if let Err(e) = write_entry(writer, &entry).and_then(|()| writer.flush()) {
+                    state.error = Some(e);
+                }
+                state.records += 1;
+            }
+        }
+        state.entries.push_back(entry);
+        if state.entries.len() > self.capacity {
+            state.entries.pop_front();
+            state.dropped += 1;
+        }
+        if state.error.is_none() && state.records > 2 * self.capacity as u64 {
+            if let Err(e) = Self::rewrite(state) {
+                state.error = Some(e);
+            }
+        }
+    }
+    fn expire(&self, state: &mut State) {
+        if let Some(max_age) = self.max_age {
+            let now = SystemTime::now();
+            while let Some(entry) = state.entries.front() {
+                if now.duration_since(entry.received).unwrap_or_default() <= max_age {
+                    break;
+                }
+                state.entries.pop_front();
+                state.dropped += 1;
+            }
+        }
+    }
+    fn forward_locked(&self, state: &mut State) -> usize {
+        self.expire(state);
+        if state.entries.is_empty() || !self.transports.reachable() {
+            return 0;
+        }
+        let mut count = 0;
+        while let Some(entry) = state.entries.front() {
+            let sample = &entry.sample;
+            let result = match sample.kind {
+                SampleKind::Put => {
+                    let mut put = self
+                        .session
+                        .put(&sample.key_expr, sample.value.clone())
+                        .with_source_info(source_info(self.session.zid()))
+                        .priority(self.priority)
+                        .congestion_control(self.congestion);
+                    if let Some(timestamp) = sample.timestamp {
+                        put = put.with_timestamp(timestamp);
+                    }
+                    if let Some(attachment) = sample.attachment.clone() {
+                        put = put.with_attachment(attachment);
+                    }
+                    put.res_sync()
+                }
+                SampleKind::Delete => {
+                    let mut delete = self
+                        .session
+                        .delete(&sample.key_expr)
+                        .with_source_info(source_info(self.session.zid()))
+                        .priority(self.priority)
+                        .congestion_control(self.congestion);
+                    if let Some(timestamp) = sample.timestamp {
+                        delete = delete.with_timestamp(timestamp);
+                    }
+                    if let Some(attachment) = sample.attachment.clone() {
+                        delete = delete.with_attachment(attachment);
+                    }
+                    delete.res_sync()
+                }
+            };
+            if result.is_err() {
+                // kept for the next attempt
+                break;
+            }
+            state.entries.pop_front();
+            count += 1;
+        }
+        if count > 0 && state.error.is_none() {
+            if let Err(e) = Self::rewrite(state) {
+                state.error = Some(e);
+            }
+        }
+        count
+    }
+    /// Rewrites the file with the publications still held back, replacing the previous one atomically.
+    fn rewrite(state: &mut State) -> std::io::Result<()> {
+        let path = match &state.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        writer.write_all(MAGIC)?;
+        for entry in &state.entries {
+            write_entry(&mut writer, entry)?;
+        }
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        state.writer = Some(BufWriter::new(OpenOptions::new().append(true).open(path)?));
+        state.records = state.entries.len() as u64;
+        Ok(())
+    }
+}
@@ -11,24 +11,33 @@
 // Contributors:
 //   ZettaScale Zenoh team, <zenoh@zettascale.tech>
 //
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
-use pyo3::prelude::*;
+use pyo3::{prelude::*, types::PyTuple};
 use zenoh::{
-    prelude::sync::SyncResolve,
+    prelude::{sync::SyncResolve, Sample, Value},
     queryable::{Query, Queryable},
     selector::Parameters,
 };
 
 use crate::{
     keyexpr::{_KeyExpr, _Selector},
+    rewrite::Rewrites,
     value::{_Sample, _Value},
-    ToPyErr,
+    PyAnyToValue, ToPyErr,
 };
 
 #[pyclass(subclass)]
 #[derive(Clone)]
-pub struct _Query(pub(crate) Arc<Query>);
+pub struct _Query(
+    pub(crate) Arc<Query>,
+    pub(crate) Arc<Rewrites>,
+    pub(crate) Option<Arc<Recording>>,
+);
 #[pymethods]
 impl _Query {
     #[new]
@@ -37,7 +46,7 @@ impl _Query {
     }
     #[getter]
     pub fn key_expr(&self) -> _KeyExpr {
-        _KeyExpr(self.0.key_expr().clone())
+        _KeyExpr(self.1.incoming(self.0.key_expr()))
     }
     #[getter]
     pub fn parameters(&self) -> &str {
@@ -65,33 +74,223 @@ impl _Query {
     }
     #[getter]
     pub fn selector(&self) -> _Selector {
-        _Selector(self.0.selector().clone().into_owned())
+        _Selector(self.1.incoming_selector(&self.0.selector()))
     }
     #[getter]
     pub fn value(&self) -> Option<_Value> {
         self.0.value().map(|v| v.clone().into())
     }
-    pub fn reply(&self, sample: _Sample) -> PyResult<()> {
-        self.0
-            .reply(Ok(sample.into()))
-            .res_sync()
-            .map_err(|e| e.to_pyerr())
+    pub fn reply(&self, py: Python, sample: _Sample) -> PyResult<()> {
+        let mut sample: Sample = sample.into();
+        sample.key_expr = self.1.outgoing(&sample.key_expr);
+        self.send(py, sample)
+    }
+    /// Replies with each item of `replies`: samples as they are, and other values on the query's key expression,
+    /// once encoded by `autoencode`.
+    pub fn reply_all(&self, py: Python, replies: &PyAny, autoencode: &PyAny) -> PyResult<()> {
+        for reply in replies.iter()? {
+            let reply = reply?;
+            match reply.extract::<_Sample>() {
+                Ok(sample) => self.reply(py, sample)?,
+                Err(_) => {
+                    let value = autoencode.call1((reply,))?.to_value()?;
+                    self.send(py, Sample::new(self.0.key_expr().clone(), value))?
+                }
+            }
+        }
+        Ok(())
     }
-    pub fn reply_err(&self, value: _Value) -> PyResult<()> {
-        self.0
-            .reply(Err(value.into()))
-            .res_sync()
+    /// Replies with the result of `eval` called with the query's decoded parameters, as `reply_all` would
+    /// with the items it yields if `generator` is set, or else with the value it returns unless `None`.
+    /// Exceptions raised meanwhile are sent back as errors instead.
+    pub fn reply_eval(
+        &self,
+        py: Python,
+        eval: &PyAny,
+        generator: bool,
+        autoencode: &PyAny,
+    ) -> PyResult<()> {
+        let replied = self
+            .decode_parameters()
+            .and_then(|parameters| eval.call1((parameters,)))
+            .and_then(|result| {
+                if generator {
+                    self.reply_all(py, result, autoencode)
+                } else if result.is_none() {
+                    Ok(())
+                } else {
+                    self.reply_all(py, PyTuple::new(py, [result]), autoencode)
+                }
+            });
+        match replied {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                let message = e.value(py).str()?.to_string();
+                self.reply_err(py, Value::from(message).into())
+            }
+        }
+    }
+    pub fn reply_err(&self, py: Python, value: _Value) -> PyResult<()> {
+        let value: Value = value.into();
+        if let Some(recording) = &self.2 {
+            recording.record(Err(value.clone()));
+        }
+        py.allow_threads(|| self.0.reply(Err(value)).res_sync())
             .map_err(|e| e.to_pyerr())
     }
     pub fn __str__(&self) -> String {
         self.0.to_string()
     }
 }
-impl From<Query> for _Query {
-    fn from(q: Query) -> Self {
-        Self(Arc::new(q))
+impl _Query {
+    /// Sends `sample`, whose key expression was already rewritten for the network.
+    fn send(&self, py: Python, sample: Sample) -> PyResult<()> {
+        if let Some(recording) = &self.2 {
+            recording.record(Ok(sample.clone()));
+        }
+        py.allow_threads(|| self.0.reply(Ok(sample)).res_sync())
+            .map_err(|e| e.to_pyerr())
+    }
+    pub(crate) fn new(query: Query, rewrites: Arc<Rewrites>) -> Self {
+        Self(Arc::new(query), rewrites, None)
+    }
+    /// A query whose replies are stored in `cache` once it is finalized.
+    pub(crate) fn recorded(
+        query: Query,
+        rewrites: Arc<Rewrites>,
+        cache: Arc<QueryCache>,
+        key: String,
+    ) -> Self {
+        let recording = Recording {
+            cache,
+            key,
+            replies: Default::default(),
+        };
+        Self(Arc::new(query), rewrites, Some(Arc::new(recording)))
+    }
+}
+impl Drop for _Query {
+    fn drop(&mut self) {
+        // the replies must be cached before the query is finalized, for the queries that follow to find them
+        self.2.take();
+    }
+}
+
+type Replies = Vec<Result<Sample, Value>>;
+
+struct Cached {
+    stored: Instant,
+    used: u64,
+    replies: Replies,
+}
+
+/// The replies of a queryable's Python handler, by selector, so that repeated queries are answered from Rust.
+pub(crate) struct QueryCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<(HashMap<String, Cached>, u64)>,
+}
+impl QueryCache {
+    pub(crate) fn new(ttl: f64, capacity: usize) -> PyResult<Self> {
+        let ttl = Duration::try_from_secs_f64(ttl).map_err(|_| {
+            pyo3::exceptions::PyValueError::new_err(
+                "the cache's `ttl` must be a non-negative number of seconds",
+            )
+        })?;
+        if capacity == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "the cache's `max_entries` must be positive",
+            ));
+        }
+        Ok(QueryCache {
+            ttl,
+            capacity,
+            entries: Default::default(),
+        })
+    }
+    /// The key queries are cached by, if they can be: queries carrying a value may differ by more than their selector.
+    pub(crate) fn key(query: &Query) -> Option<String> {
+        query
+            .value()
+            .is_none()
+            .then(|| query.selector().to_string())
+    }
+    /// The unexpired replies cached for `key`, if any.
+    pub(crate) fn lookup(&self, key: &str) -> Option<Replies> {
+        let mut entries = self.entries.lock().unwrap();
+        let (map, tick) = &mut *entries;
+        *tick += 1;
+        match map.get_mut(key) {
+            Some(cached) if cached.stored.elapsed() < self.ttl => {
+                cached.used = *tick;
+                Some(cached.replies.clone())
+            }
+            Some(_) => {
+                map.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+    fn store(&self, key: String, replies: Replies) {
+        let mut entries = self.entries.lock().unwrap();
+        let (map, tick) = &mut *entries;
+        *tick += 1;
+        if map.len() >= self.capacity && !map.contains_key(&key) {
+            // evict the least recently used entry, preferring expired ones
+            let ttl = self.ttl;
+            let evicted = map
+                .iter()
+                .min_by_key(|(_, cached)| (cached.stored.elapsed() < ttl, cached.used))
+                .map(|(key, _)| key.clone());
+            if let Some(evicted) = evicted {
+                map.remove(&evicted);
+            }
+        }
+        map.insert(
+            key,
+            Cached {
+                stored: Instant::now(),
+                used: *tick,
+                replies,
+            },
+        );
+    }
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().0.clear();
+    }
+}
+
+/// The replies sent to a query, stored in the cache once the query is finalized, that is when its last reference is dropped.
+pub(crate) struct Recording {
+    cache: Arc<QueryCache>,
+    key: String,
+    replies: Mutex<Replies>,
+}
+impl Recording {
+    fn record(&self, reply: Result<Sample, Value>) {
+        self.replies.lock().unwrap().push(reply);
+    }
+}
+impl Drop for Recording {
+    fn drop(&mut self) {
+        let replies = std::mem::take(self.replies.get_mut().unwrap());
+        self.cache.store(std::mem::take(&mut self.key), replies);
     }
 }
 
 #[pyclass(subclass)]
-pub struct _Queryable(pub(crate) Queryable<'static, ()>);
+pub struct _Queryable(
+    pub(crate) crate::DropWithoutGil<Queryable<'static, ()>>,
+    pub(crate) crate::stats::Declared,
+    pub(crate) Option<Arc<QueryCache>>,
+);
+#[pymethods]
+impl _Queryable {
+    /// Forgets the replies cached so far, for the following queries to reach the handler again.
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.2 {
+            cache.clear()
+        }
+    }
+}
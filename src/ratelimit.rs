@@ -0,0 +1,80 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! An artificial limit on the outgoing bandwidth of a session, to test applications under congestion.
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// A token bucket, holding up to `burst` bytes worth of tokens, refilled at `rate` bytes per second.
+struct Bucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+#[derive(Default)]
+pub(crate) struct RateLimiter {
+    bucket: Mutex<Option<Bucket>>,
+}
+impl RateLimiter {
+    /// Limits the outgoing bandwidth to `rate` bytes per second, allowing bursts of `burst` bytes; `None` lifts the limit.
+    pub(crate) fn set(&self, limit: Option<(f64, f64)>) {
+        *self.bucket.lock().unwrap() = limit.map(|(rate, burst)| Bucket {
+            rate,
+            burst,
+            tokens: burst,
+            last: Instant::now(),
+        });
+    }
+    pub(crate) fn get(&self) -> Option<(f64, f64)> {
+        let bucket = self.bucket.lock().unwrap();
+        bucket.as_ref().map(|bucket| (bucket.rate, bucket.burst))
+    }
+    /// Returns whether `size` bytes may be sent.
+    ///
+    /// Once the bucket is exhausted, messages are refused, as zenoh's `Drop` congestion control would;
+    /// unless `block` is set, in which case the call sleeps until the bucket has refilled enough, as with `Block`.
+    /// Messages larger than the burst size are let through once the bucket is full, so that they don't starve.
+    pub(crate) fn admit(&self, size: usize, block: bool) -> bool {
+        let wait = {
+            let mut bucket = self.bucket.lock().unwrap();
+            let bucket = match bucket.as_mut() {
+                Some(bucket) => bucket,
+                None => return true,
+            };
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.last).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * bucket.rate).min(bucket.burst);
+            bucket.last = now;
+            let size = size as f64;
+            let needed = size.min(bucket.burst);
+            if bucket.tokens >= needed {
+                bucket.tokens -= size;
+                return true;
+            }
+            if !block {
+                return false;
+            }
+            // take the tokens ahead of time, so that the callers blocked at once are spaced out
+            let wait = (needed - bucket.tokens) / bucket.rate;
+            bucket.tokens -= size;
+            // rates small enough for the wait to overflow never refill the bucket in practice
+            Duration::try_from_secs_f64(wait).unwrap_or(Duration::MAX)
+        };
+        std::thread::sleep(wait);
+        true
+    }
+}
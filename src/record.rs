@@ -0,0 +1,224 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! Traffic captures, and the record format they share with offline caches and outboxes.
+//!
+//! A capture file starts with [`MAGIC`], followed by one record per sample:
+//! - the sample's reception time, as little-endian `u64` nanoseconds since the recording started,
+//! - its kind, as a `u8` (0 for PUT, 1 for DELETE),
+//! - its key expression, encoding, timestamp (empty if none) and payload,
+//!   each as a little-endian `u32` length followed by that many bytes.
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use pyo3::prelude::*;
+use uhlc::Timestamp;
+use zenoh::{
+    prelude::{
+        sync::SyncResolve, Encoding, KeyExpr, Sample, SampleKind, SessionDeclarations, Value,
+    },
+    subscriber::Subscriber,
+};
+use zenoh_buffers::{buffer::SplitBuffer, ZBuf};
+
+use crate::{keyexpr::_KeyExpr, session::_Session, ToPyErr};
+
+const MAGIC: &[u8; 8] = b"ZPYREC01";
+
+struct Capture {
+    writer: BufWriter<File>,
+    start: Instant,
+    count: u64,
+    error: Option<std::io::Error>,
+}
+impl Capture {
+    fn write(&mut self, sample: &Sample) {
+        if self.error.is_some() {
+            return;
+        }
+        match write_record(&mut self.writer, self.start.elapsed(), sample) {
+            Ok(()) => self.count += 1,
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+/// A running capture, writing the samples received on a key expression to a file.
+#[pyclass(subclass)]
+pub struct _Recorder {
+    subscriber: Option<Subscriber<'static, ()>>,
+    capture: Arc<Mutex<Capture>>,
+}
+#[pymethods]
+impl _Recorder {
+    /// The number of samples written so far.
+    #[getter]
+    fn count(&self) -> u64 {
+        self.capture.lock().unwrap().count
+    }
+    /// Stops the capture, flushing the file.
+    fn stop(&mut self, py: Python) -> PyResult<()> {
+        self.subscriber = None;
+        py.allow_threads(|| {
+            let mut capture = self.capture.lock().unwrap();
+            if let Some(e) = capture.error.take() {
+                return Err(e);
+            }
+            capture.writer.flush()
+        })
+        .map_err(|e| e.to_pyerr())
+    }
+}
+
+#[pyfunction]
+pub fn record(session: &_Session, key_expr: &_KeyExpr, path: &str) -> PyResult<_Recorder> {
+    let mut writer = BufWriter::new(File::create(path).map_err(|e| e.to_pyerr())?);
+    writer.write_all(MAGIC).map_err(|e| e.to_pyerr())?;
+    let capture = Arc::new(Mutex::new(Capture {
+        writer,
+        start: Instant::now(),
+        count: 0,
+        error: None,
+    }));
+    let written = capture.clone();
+    let subscriber = session
+        .0
+        .declare_subscriber(&key_expr.0)
+        .callback(move |sample: Sample| written.lock().unwrap().write(&sample))
+        .res_sync()
+        .map_err(|e| e.to_pyerr())?;
+    Ok(_Recorder {
+        subscriber: Some(subscriber),
+        capture,
+    })
+}
+
+/// Writes a record of `sample`, received at `time`: what `time` is measured from is up to the file's format.
+pub(crate) fn write_record(
+    writer: &mut impl Write,
+    time: Duration,
+    sample: &Sample,
+) -> std::io::Result<()> {
+    let kind = match sample.kind {
+        SampleKind::Put => 0u8,
+        SampleKind::Delete => 1u8,
+    };
+    let encoding = sample.value.encoding.to_string();
+    let timestamp = sample.timestamp.map(|t| t.to_string()).unwrap_or_default();
+    let payload = sample.value.payload.contiguous();
+    writer.write_all(&(time.as_nanos() as u64).to_le_bytes())?;
+    writer.write_all(&[kind])?;
+    for field in [
+        sample.key_expr.as_str().as_bytes(),
+        encoding.as_bytes(),
+        timestamp.as_bytes(),
+        payload.as_ref(),
+    ] {
+        writer.write_all(&(field.len() as u32).to_le_bytes())?;
+        writer.write_all(field)?;
+    }
+    Ok(())
+}
+
+fn read_field(reader: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let mut field = vec![0; u32::from_le_bytes(len) as usize];
+    reader.read_exact(&mut field)?;
+    Ok(field)
+}
+
+pub(crate) fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(ErrorKind::InvalidData, message.to_owned())
+}
+
+/// Reads the next record, as the sample and the time it was received at, returning `None` at the end of the file.
+pub(crate) fn read_record(reader: &mut impl Read) -> std::io::Result<Option<(Duration, Sample)>> {
+    let mut time = [0; 8];
+    match reader.read_exact(&mut time) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let mut kind = [0; 1];
+    reader.read_exact(&mut kind)?;
+    let kind = match kind[0] {
+        0 => SampleKind::Put,
+        1 => SampleKind::Delete,
+        _ => return Err(invalid("unknown sample kind")),
+    };
+    let key_expr = String::from_utf8(read_field(reader)?)
+        .map_err(|_| invalid("key expression isn't UTF-8"))?;
+    let key_expr = KeyExpr::try_from(key_expr).map_err(|e| invalid(&e.to_string()))?;
+    let encoding =
+        String::from_utf8(read_field(reader)?).map_err(|_| invalid("encoding isn't UTF-8"))?;
+    let timestamp =
+        String::from_utf8(read_field(reader)?).map_err(|_| invalid("timestamp isn't UTF-8"))?;
+    let payload = read_field(reader)?;
+    let mut sample = Sample::new(
+        key_expr,
+        Value::new(ZBuf::from(payload)).encoding(Encoding::from(encoding)),
+    );
+    sample.kind = kind;
+    sample.timestamp = timestamp.parse::<Timestamp>().ok();
+    Ok(Some((
+        Duration::from_nanos(u64::from_le_bytes(time)),
+        sample,
+    )))
+}
+
+#[pyfunction]
+#[pyo3(signature = (session, path, speed = 1.0))]
+pub fn replay(py: Python, session: &_Session, path: &str, speed: f64) -> PyResult<u64> {
+    if !(speed > 0.) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "`speed` must be positive",
+        ));
+    }
+    let session = session.0.clone();
+    py.allow_threads(move || {
+        let mut reader = BufReader::new(File::open(path).map_err(|e| e.to_pyerr())?);
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic).map_err(|e| e.to_pyerr())?;
+        if &magic != MAGIC {
+            return Err(zenoh_core::zerror!("{} isn't a zenoh-python capture", path).to_pyerr());
+        }
+        let start = Instant::now();
+        let mut count = 0;
+        while let Some((elapsed, sample)) = read_record(&mut reader).map_err(|e| e.to_pyerr())? {
+            let due = Duration::try_from_secs_f64(elapsed.as_secs_f64() / speed)
+                .ok()
+                .and_then(|delay| start.checked_add(delay))
+                .ok_or_else(|| {
+                    pyo3::exceptions::PyValueError::new_err(
+                        "`speed` is too low for the capture to be replayed",
+                    )
+                })?;
+            let now = Instant::now();
+            if due > now {
+                std::thread::sleep(due - now);
+            }
+            let result = match sample.kind {
+                SampleKind::Put => session.put(&sample.key_expr, sample.value).res_sync(),
+                SampleKind::Delete => session.delete(&sample.key_expr).res_sync(),
+            };
+            result.map_err(|e| e.to_pyerr())?;
+            count += 1;
+        }
+        Ok(count)
+    })
+}
@@ -0,0 +1,85 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! Ordering and merging replies by timestamp, to assemble consistent snapshots from several storages.
+use std::collections::HashMap;
+
+use pyo3::{prelude::*, types::PyTuple};
+
+use crate::value::{_Reply, _Sample, _Timestamp};
+
+/// An ok reply or a sample, along with what it is ordered by.
+struct Entry {
+    timestamp: Option<_Timestamp>,
+    key_expr: String,
+    item: PyObject,
+}
+
+/// Reads the replies or samples from `source`: an iterable, such as a list or a `Queue`,
+/// or a function returning one, such as the receiver of a `ListCollector`. Error replies are skipped.
+fn entries(source: &PyAny, into: &mut Vec<Entry>) -> PyResult<()> {
+    let source = if source.is_callable() {
+        source.call0()?
+    } else {
+        source
+    };
+    for item in source.iter()? {
+        let item = item?;
+        let entry = |sample: &_Sample| Entry {
+            timestamp: sample.timestamp(),
+            key_expr: sample.key_expr().0.to_string(),
+            item: item.into(),
+        };
+        if let Ok(reply) = item.downcast::<PyCell<_Reply>>() {
+            if let Ok(sample) = &reply.borrow().reply {
+                into.push(entry(sample));
+            }
+        } else {
+            into.push(entry(&*item.downcast::<PyCell<_Sample>>()?.borrow()));
+        }
+    }
+    Ok(())
+}
+
+/// The replies of all `receivers`, by increasing timestamp; the untimestamped ones come last, in their original order.
+#[pyfunction]
+#[pyo3(signature = (*receivers))]
+pub fn merge_sorted(receivers: &PyTuple) -> PyResult<Vec<PyObject>> {
+    let mut all = Vec::new();
+    for receiver in receivers {
+        entries(receiver, &mut all)?;
+    }
+    // stable, so that equal timestamps keep the order of the receivers
+    all.sort_by_key(|entry| (entry.timestamp.is_none(), entry.timestamp));
+    Ok(all.into_iter().map(|entry| entry.item).collect())
+}
+
+/// The reply with the greatest timestamp for each key expression in `replies`, ordered by key expression.
+#[pyfunction]
+pub fn dedup_latest(replies: &PyAny) -> PyResult<Vec<PyObject>> {
+    let mut all = Vec::new();
+    entries(replies, &mut all)?;
+    let mut latest: HashMap<String, Entry> = HashMap::new();
+    for entry in all {
+        match latest.get(&entry.key_expr) {
+            // untimestamped replies are older than any other, and the first seen wins ties
+            Some(kept) if kept.timestamp >= entry.timestamp => {}
+            _ => {
+                latest.insert(entry.key_expr.clone(), entry);
+            }
+        }
+    }
+    let mut latest: Vec<_> = latest.into_values().collect();
+    latest.sort_by(|a, b| a.key_expr.cmp(&b.key_expr));
+    Ok(latest.into_iter().map(|entry| entry.item).collect())
+}
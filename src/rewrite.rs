@@ -0,0 +1,157 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! Key expression rewriting, mapping the key space a session's user sees onto the one used on the network.
+//!
+//! Each rule maps a local prefix onto a remote one, the empty prefix standing for the whole key space.
+//! Outgoing key expressions starting with a local prefix have it replaced by the remote one,
+//! and incoming key expressions starting with a remote prefix get the local one back. When several rules apply,
+//! the one with the longest prefix wins.
+use std::sync::RwLock;
+
+use pyo3::prelude::*;
+use zenoh::prelude::{KeyExpr, Selector};
+
+use crate::ToPyErr;
+
+/// The rules, as `(local, remote)` prefix pairs, sorted by decreasing local and remote prefix lengths respectively,
+/// so that the first rule applying to a key expression is the one with the longest prefix.
+#[derive(Default)]
+struct Rules {
+    by_local: Vec<(String, String)>,
+    by_remote: Vec<(String, String)>,
+}
+impl Rules {
+    fn retain(&mut self, f: impl Fn(&(String, String)) -> bool) {
+        self.by_local.retain(&f);
+        self.by_remote.retain(&f);
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Rewrites(RwLock<Rules>);
+
+/// Turns `prefix/**` (or `**`) into `prefix` (or the empty prefix).
+fn parse_prefix(pattern: &str) -> PyResult<String> {
+    if pattern == "**" {
+        return Ok(String::new());
+    }
+    match pattern.strip_suffix("/**") {
+        Some(prefix) if !prefix.contains(['*', '$']) => {
+            KeyExpr::try_from(prefix).map_err(|e| e.to_pyerr())?;
+            Ok(prefix.to_owned())
+        }
+        _ => Err(zenoh_core::zerror!(
+            "`{}` isn't a prefix mapping: expected `prefix/**` or `**`, without wildcards in `prefix`",
+            pattern
+        )
+        .to_pyerr()),
+    }
+}
+
+fn to_pattern(prefix: &str) -> String {
+    if prefix.is_empty() {
+        "**".to_owned()
+    } else {
+        format!("{}/**", prefix)
+    }
+}
+
+/// Replaces `from` by `to` at the start of `key`, if `key` starts with the `from` chunks.
+fn replace_prefix(key: &str, from: &str, to: &str) -> Option<String> {
+    let rest = if from.is_empty() {
+        key
+    } else if key == from {
+        ""
+    } else {
+        key.strip_prefix(from)?.strip_prefix('/')?
+    };
+    Some(match (to.is_empty(), rest.is_empty()) {
+        (true, _) => rest.to_owned(),
+        (false, true) => to.to_owned(),
+        (false, false) => format!("{}/{}", to, rest),
+    })
+}
+
+impl Rewrites {
+    pub(crate) fn add(&self, local: &str, remote: &str) -> PyResult<()> {
+        let local = parse_prefix(local)?;
+        let remote = parse_prefix(remote)?;
+        let mut rules = self.0.write().unwrap();
+        rules.retain(|(l, _)| *l != local);
+        let at = rules
+            .by_local
+            .partition_point(|(l, _)| l.len() >= local.len());
+        rules.by_local.insert(at, (local.clone(), remote.clone()));
+        let at = rules
+            .by_remote
+            .partition_point(|(_, r)| r.len() >= remote.len());
+        rules.by_remote.insert(at, (local, remote));
+        Ok(())
+    }
+    pub(crate) fn remove(&self, local: &str) -> PyResult<bool> {
+        let local = parse_prefix(local)?;
+        let mut rules = self.0.write().unwrap();
+        let count = rules.by_local.len();
+        rules.retain(|(l, _)| *l != local);
+        Ok(rules.by_local.len() != count)
+    }
+    pub(crate) fn list(&self) -> Vec<(String, String)> {
+        let rules = self.0.read().unwrap();
+        rules
+            .by_local
+            .iter()
+            .map(|(local, remote)| (to_pattern(local), to_pattern(remote)))
+            .collect()
+    }
+    fn rewrite(&self, key_expr: &KeyExpr<'_>, outgoing: bool) -> Option<KeyExpr<'static>> {
+        let rules = self.0.read().unwrap();
+        let key = key_expr.as_str();
+        let rewritten = if outgoing {
+            rules
+                .by_local
+                .iter()
+                .find_map(|(local, remote)| replace_prefix(key, local, remote))
+        } else {
+            rules
+                .by_remote
+                .iter()
+                .find_map(|(local, remote)| replace_prefix(key, remote, local))
+        }?;
+        KeyExpr::try_from(rewritten).ok()
+    }
+    /// Maps a key expression from the session's key space onto the network's.
+    pub(crate) fn outgoing(&self, key_expr: &KeyExpr<'_>) -> KeyExpr<'static> {
+        self.rewrite(key_expr, true)
+            .unwrap_or_else(|| key_expr.clone().into_owned())
+    }
+    /// Maps a key expression from the network's key space back onto the session's.
+    pub(crate) fn incoming(&self, key_expr: &KeyExpr<'_>) -> KeyExpr<'static> {
+        self.rewrite(key_expr, false)
+            .unwrap_or_else(|| key_expr.clone().into_owned())
+    }
+    pub(crate) fn outgoing_selector(&self, selector: &Selector<'_>) -> Selector<'static> {
+        with_parameters(self.outgoing(&selector.key_expr), selector.parameters())
+    }
+    pub(crate) fn incoming_selector(&self, selector: &Selector<'_>) -> Selector<'static> {
+        with_parameters(self.incoming(&selector.key_expr), selector.parameters())
+    }
+}
+
+fn with_parameters(key_expr: KeyExpr<'static>, parameters: &str) -> Selector<'static> {
+    let mut selector: Selector<'static> = key_expr.into();
+    if !parameters.is_empty() {
+        selector.set_parameters(parameters.to_owned());
+    }
+    selector
+}
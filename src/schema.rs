@@ -0,0 +1,149 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! A registry of the schemas of the data published on key expressions, served below [`SCHEMA_PREFIX`].
+//!
+//! The schema of `sensors/*/temperature` is served on `@/schema/sensors/*/temperature` by a queryable of the session
+//! that registered it, replying with the schema as it was registered, encoding included. The schemas fetched are
+//! cached by session, so that subscribers may look them up for each new key without querying every time.
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use pyo3::prelude::*;
+use zenoh::{
+    prelude::{sync::SyncResolve, KeyExpr, Sample, SessionDeclarations, Value},
+    queryable::Query,
+    Session,
+};
+
+use crate::{
+    keyexpr::_KeyExpr, queryable::_Queryable, run_interruptibly, session::_Session, stats::Entity,
+    value::_Value, DropWithoutGil, ToPyErr,
+};
+
+const SCHEMA_PREFIX: &str = "@/schema";
+
+/// The schemas fetched so far, by session id and key expression, along with when they were.
+static SCHEMAS: Mutex<Option<HashMap<(String, String), (Value, Instant)>>> = Mutex::new(None);
+
+fn schema_key(session: &_Session, key_expr: &_KeyExpr) -> PyResult<KeyExpr<'static>> {
+    let key_expr = session.2.outgoing(&key_expr.0);
+    KeyExpr::try_from(format!("{SCHEMA_PREFIX}/{key_expr}")).map_err(|e| e.to_pyerr())
+}
+
+/// Declares a queryable serving `schema` as the schema of the data published on `key_expr`.
+#[pyfunction]
+pub fn register_schema(
+    py: Python,
+    session: &_Session,
+    key_expr: &_KeyExpr,
+    schema: _Value,
+) -> PyResult<_Queryable> {
+    let served = schema_key(session, key_expr)?;
+    let zid = session.0.zid().to_string();
+    // a schema registered by this session replaces the one it may have cached
+    if let Some(schemas) = SCHEMAS.lock().unwrap().as_mut() {
+        schemas.retain(|(id, key), _| {
+            id != &zid
+                || !KeyExpr::try_from(key.as_str()).map_or(false, |key| key.intersects(&served))
+        });
+    }
+    let schema = Value::from(schema);
+    let queryable = py
+        .allow_threads(|| {
+            session
+                .0
+                .declare_queryable(served)
+                .callback(move |query: Query| {
+                    let sample = Sample::new(query.key_expr().clone(), schema.clone());
+                    let _ = query.reply(Ok(sample)).res_sync();
+                })
+                .res_sync()
+        })
+        .map_err(|e| e.to_pyerr())?;
+    Ok(_Queryable(
+        DropWithoutGil::new(queryable),
+        session.1.declare(Entity::Queryable),
+        None,
+    ))
+}
+
+fn fetch(
+    session: &Session,
+    key_expr: &KeyExpr<'static>,
+    timeout: Duration,
+) -> PyResult<Option<Value>> {
+    let replies = session
+        .get(key_expr)
+        .timeout(timeout)
+        .res_sync()
+        .map_err(|e| e.to_pyerr())?;
+    while let Ok(reply) = replies.recv() {
+        if let Ok(sample) = reply.sample {
+            return Ok(Some(sample.value));
+        }
+    }
+    Ok(None)
+}
+
+/// The schema registered for `key_expr`, if any replied within `timeout` seconds.
+///
+/// The schemas fetched less than `max_age` seconds ago are returned from the cache, unless `refresh` is set.
+#[pyfunction]
+#[pyo3(signature = (session, key_expr, timeout = 2.0, max_age = 60.0, refresh = false))]
+pub fn schema_of(
+    py: Python,
+    session: &_Session,
+    key_expr: &_KeyExpr,
+    timeout: f64,
+    max_age: f64,
+    refresh: bool,
+) -> PyResult<Option<_Value>> {
+    let timeout = Duration::try_from_secs_f64(timeout).map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err(
+            "`timeout` must be a non-negative number of seconds",
+        )
+    })?;
+    let max_age = Duration::try_from_secs_f64(max_age).map_err(|_| {
+        pyo3::exceptions::PyValueError::new_err(
+            "`max_age` must be a non-negative number of seconds",
+        )
+    })?;
+    let key = schema_key(session, key_expr)?;
+    let cached = (session.0.zid().to_string(), key.as_str().to_owned());
+    if !refresh {
+        let schemas = SCHEMAS.lock().unwrap();
+        if let Some((schema, fetched)) = schemas.as_ref().and_then(|schemas| schemas.get(&cached)) {
+            if fetched.elapsed() < max_age {
+                return Ok(Some(schema.clone().into()));
+            }
+        }
+    }
+    let shared: Arc<Session> = session.0.clone();
+    let schema = run_interruptibly(py, move || fetch(&shared, &key, timeout))??;
+    let mut schemas = SCHEMAS.lock().unwrap();
+    let schemas = schemas.get_or_insert_with(HashMap::new);
+    match schema {
+        Some(schema) => {
+            schemas.insert(cached, (schema.clone(), Instant::now()));
+            Ok(Some(schema.into()))
+        }
+        None => {
+            schemas.remove(&cached);
+            Ok(None)
+        }
+    }
+}
@@ -14,46 +14,170 @@
 
 #![allow(clippy::borrow_deref_ref)] // false positives with pyo3 macros
 
+use std::collections::HashMap;
 use std::convert::TryInto;
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    Arc, Mutex, RwLock, Weak,
+};
+use std::time::{Duration, Instant};
 
 use pyo3::{prelude::*, types::PyDict};
 use zenoh::{
     config::{WhatAmI, WhatAmIMatcher},
-    prelude::{sync::SyncResolve, SessionDeclarations},
-    publication::Publisher,
+    handlers::Callback,
+    prelude::{
+        sync::SyncResolve, Encoding, IntoCallbackReceiverPair, KeyExpr, KnownEncoding, Priority,
+        Sample, SampleKind, SessionDeclarations, Value, ZenohId,
+    },
+    publication::{CongestionControl, Publisher},
+    query::Reply,
+    queryable::Query,
+    sample::{Attachment, SourceInfo},
     scouting::Scout,
     subscriber::{PullSubscriber, Subscriber},
     Session,
 };
 
-use crate::closures::PyClosure;
+use crate::audit::AuditLog;
+use crate::closures::{
+    overflowing, CallbackPool, CallbackUnwrap, Overflow, PyBatchingClosure, PyClosure,
+    PyProfilingClosure, SampleReusingClosure, SampleSink, _CallbackProfile,
+};
 use crate::config::{PyConfig, _Config};
 use crate::enums::{
-    _CongestionControl, _Priority, _QueryConsolidation, _QueryTarget, _Reliability, _SampleKind,
+    _CongestionControl, _Locality, _Priority, _QueryConsolidation, _QueryTarget, _Reliability,
+    _SampleKind,
 };
+use crate::filter::{Filtered, _SampleFilter};
+use crate::interceptor::{Action, Fault, Interceptors};
 use crate::keyexpr::{_KeyExpr, _Selector};
-use crate::queryable::{_Query, _Queryable};
-use crate::value::{_Hello, _Reply, _Sample, _Value, _ZenohId};
-use crate::{PyAnyToValue, PyExtract, ToPyErr};
+use crate::outbox::Outbox;
+use crate::queryable::{QueryCache, _Query, _Queryable};
+use crate::ratelimit::RateLimiter;
+use crate::rewrite::Rewrites;
+use crate::stats::{Counter, Declared, Entity, Flow, SessionStats, _MetricsExporter, TRAFFIC};
+use crate::value::{_Change, _Hello, _Reply, _Sample, _Value, _ZenohId};
+use crate::{
+    recv_interruptibly, run_interruptibly, DropWithoutGil, PyAnyToValue, PyExtract, ToPyErr,
+};
+
+/// The sessions opened with `share_runtime`, by configuration.
+static SHARED_SESSIONS: Mutex<Option<HashMap<String, Weak<Session>>>> = Mutex::new(None);
 
 #[pyclass(subclass)]
 #[derive(Clone)]
-pub struct _Session(pub(crate) Arc<Session>);
+pub struct _Session(
+    pub(crate) Arc<Session>,
+    pub(crate) Arc<SessionStats>,
+    pub(crate) Arc<Rewrites>,
+    pub(crate) Option<Arc<CallbackPool>>,
+    pub(crate) Arc<Interceptors>,
+    pub(crate) Option<Arc<AuditLog>>,
+    pub(crate) Arc<RateLimiter>,
+);
+impl _Session {
+    /// Moves `callback` onto the session's callback threads, if it has any.
+    fn dispatched<T: Send + 'static>(
+        &self,
+        callback: Callback<'static, T>,
+    ) -> Callback<'static, T> {
+        match &self.3 {
+            Some(pool) => pool.bind(callback),
+            None => callback,
+        }
+    }
+    /// Records an operation in the session's audit log, if it has one.
+    fn audit(
+        &self,
+        operation: &str,
+        key_expr: &str,
+        value: Option<&Value>,
+        kwargs: Option<&PyDict>,
+    ) -> PyResult<()> {
+        match &self.5 {
+            Some(log) => log.record(operation, key_expr, value, kwargs),
+            None => Ok(()),
+        }
+    }
+}
 
 #[pymethods]
 impl _Session {
     #[new]
-    pub fn new(mut config: Option<&mut crate::config::_Config>) -> PyResult<Self> {
-        let c = match &mut config {
+    #[pyo3(signature = (config = None, share_runtime = false, callback_threads = None, audit = None))]
+    pub fn new(
+        py: Python,
+        mut config: Option<&mut crate::config::_Config>,
+        share_runtime: bool,
+        callback_threads: Option<usize>,
+        audit: Option<&PyAny>,
+    ) -> PyResult<Self> {
+        let audit = match audit {
+            Some(target) => Some(Arc::new(AuditLog::new(target)?)),
+            None => None,
+        };
+        let pool = match callback_threads {
+            Some(threads) => Some(Arc::new(CallbackPool::new(threads)?)),
+            None => None,
+        };
+        let c: zenoh::config::Config = match &mut config {
             Some(c) => c.0.take().unwrap_or_default(),
             None => Default::default(),
         };
-        let session = zenoh::open(c).res_sync().map_err(|e| e.to_pyerr())?;
+        crate::start_runtime();
+        let session = if share_runtime {
+            let key = serde_json::to_string(&c).map_err(|e| e.to_pyerr())?;
+            run_interruptibly(py, move || -> PyResult<_> {
+                let mut shared = SHARED_SESSIONS.lock().unwrap();
+                let shared = shared.get_or_insert_with(HashMap::new);
+                shared.retain(|_, session| session.strong_count() != 0);
+                if let Some(session) = shared.get(&key).and_then(Weak::upgrade) {
+                    return Ok(session);
+                }
+                let session = Arc::new(zenoh::open(c).res_sync().map_err(|e| e.to_pyerr())?);
+                shared.insert(key, Arc::downgrade(&session));
+                Ok(session)
+            })??
+        } else {
+            Arc::new(
+                run_interruptibly(py, move || zenoh::open(c).res_sync())?
+                    .map_err(|e| e.to_pyerr())?,
+            )
+        };
         if let Some(config) = config {
             *config = _Config(PyConfig::Notifier(session.config().clone()))
         }
-        Ok(_Session(Arc::new(session)))
+        Ok(_Session(
+            session,
+            Arc::new(SessionStats::new()),
+            Default::default(),
+            pool,
+            Default::default(),
+            audit,
+            Default::default(),
+        ))
+    }
+    /// Replaces the session's runtime by a new one opened with `config`, keeping the session's statistics, rewrites,
+    /// callback threads, hooks, audit log and rate limit.
+    ///
+    /// The entities declared on the previous runtime keep using it until they are dropped: they must be declared anew.
+    pub fn reopen(
+        slf: &PyCell<Self>,
+        py: Python,
+        config: &mut crate::config::_Config,
+    ) -> PyResult<()> {
+        let c = config.0.take().unwrap_or_default();
+        crate::start_runtime();
+        let session = Arc::new(
+            run_interruptibly(py, move || zenoh::open(c).res_sync())?.map_err(|e| e.to_pyerr())?,
+        );
+        *config = _Config(PyConfig::Notifier(session.config().clone()));
+        let previous = std::mem::replace(&mut slf.borrow_mut().0, session);
+        // a runtime that stopped responding may never finish closing
+        std::thread::spawn(move || drop(previous));
+        Ok(())
     }
     pub fn config(&self) -> _Config {
         _Config(PyConfig::Notifier(self.0.config().clone()))
@@ -62,14 +186,31 @@ impl _Session {
     #[pyo3(signature = (key_expr, value, **kwargs))]
     pub fn put(
         &self,
+        py: Python,
         key_expr: &crate::keyexpr::_KeyExpr,
         value: &PyAny,
         kwargs: Option<&PyDict>,
     ) -> PyResult<()> {
         let s = &self.0;
-        let k = &key_expr.0;
         let v = value.to_value()?;
-        let mut builder = s.put(k, v);
+        self.audit("put", key_expr.0.as_str(), Some(&v), kwargs)?;
+        let attachment = match kwargs.map(|kwargs| kwargs.extract_item::<&PyDict>("attachment")) {
+            Some(Ok(attachment)) => crate::interceptor::attachment_from_dict(attachment)?,
+            Some(Err(crate::ExtractError::Other(e))) => return Err(e),
+            _ => None,
+        };
+        let (v, attachment) =
+            match py.allow_threads(|| self.4.intercept_put(&key_expr.0, v, attachment)) {
+                Some(sample) => (sample.value, sample.attachment),
+                None => return Ok(()),
+            };
+        let k = &self.2.outgoing(&key_expr.0);
+        let size = v.payload.len();
+        let mut congestion = CongestionControl::default();
+        let mut builder = s.put(k, v).with_source_info(source_info(s.zid()));
+        if let Some(attachment) = attachment {
+            builder = builder.with_attachment(attachment);
+        }
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<_SampleKind>("kind") {
                 Ok(kind) => builder = builder.kind(kind.0),
@@ -78,6 +219,7 @@ impl _Session {
             }
             match kwargs.extract_item::<_CongestionControl>("congestion_control") {
                 Ok(congestion_control) => {
+                    congestion = congestion_control.0;
                     builder = builder.congestion_control(congestion_control.0)
                 }
                 Err(crate::ExtractError::Other(e)) => return Err(e),
@@ -88,19 +230,40 @@ impl _Session {
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
+            match kwargs.extract_item::<bool>("express") {
+                Ok(express) => builder = builder.express(express),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+        }
+        if !py.allow_threads(|| self.6.admit(size, congestion == CongestionControl::Block)) {
+            return Ok(());
         }
-        builder.res_sync().map_err(|e| e.to_pyerr())
+        py.allow_threads(|| builder.res_sync())
+            .map_err(|e| e.to_pyerr())?;
+        self.1.count(Counter::Puts);
+        self.1.traffic(Flow::Sent, k.as_str(), size);
+        Ok(())
     }
 
     #[pyo3(signature = (key_expr, **kwargs))]
     pub fn delete(
         &self,
+        py: Python,
         key_expr: &crate::keyexpr::_KeyExpr,
         kwargs: Option<&PyDict>,
     ) -> PyResult<()> {
+        self.audit("delete", key_expr.0.as_str(), None, kwargs)?;
+        let attachment = match py.allow_threads(|| self.4.intercept_delete(&key_expr.0)) {
+            Some(sample) => sample.attachment,
+            None => return Ok(()),
+        };
         let s = &self.0;
-        let k = &key_expr.0;
-        let mut builder = s.delete(k);
+        let k = &self.2.outgoing(&key_expr.0);
+        let mut builder = s.delete(k).with_source_info(source_info(s.zid()));
+        if let Some(attachment) = attachment {
+            builder = builder.with_attachment(attachment);
+        }
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<_SampleKind>("kind") {
                 Ok(kind) => builder = builder.kind(kind.0),
@@ -119,19 +282,49 @@ impl _Session {
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
+            match kwargs.extract_item::<bool>("express") {
+                Ok(express) => builder = builder.express(express),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
         }
-        builder.res_sync().map_err(|e| e.to_pyerr())
+        py.allow_threads(|| builder.res_sync())
+            .map_err(|e| e.to_pyerr())?;
+        self.1.count(Counter::Deletes);
+        self.1.traffic(Flow::Sent, k.as_str(), 0);
+        Ok(())
     }
 
     #[pyo3(signature = (selector, callback, **kwargs))]
     pub fn get(
         &self,
+        py: Python,
         selector: &_Selector,
         callback: &PyAny,
         kwargs: Option<&PyDict>,
     ) -> PyResult<()> {
+        let value = kwargs
+            .and_then(|kwargs| kwargs.extract_item::<_Value>("value").ok())
+            .map(Value::from);
+        self.audit("get", &selector.0.to_string(), value.as_ref(), kwargs)?;
         let callback: PyClosure<(_Reply,)> = <_ as TryInto<_>>::try_into(callback)?;
-        let mut builder = self.0.get(&selector.0).with(callback);
+        let callback: Callback<'static, Reply> = callback.into_cb_receiver_pair().0;
+        let rewrites = self.2.clone();
+        let stats = self.1.clone();
+        let mut builder =
+            self.0
+                .get(self.2.outgoing_selector(&selector.0))
+                .callback(move |mut reply: Reply| {
+                    if let Ok(sample) = &mut reply.sample {
+                        stats.traffic(
+                            Flow::Received,
+                            sample.key_expr.as_str(),
+                            sample.value.payload.len(),
+                        );
+                        sample.key_expr = rewrites.incoming(&sample.key_expr);
+                    }
+                    callback(reply)
+                });
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<_QueryConsolidation>("consolidation") {
                 Ok(_QueryConsolidation(Some(value))) => builder = builder.consolidation(value),
@@ -148,12 +341,27 @@ impl _Session {
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
+            match kwargs.extract_item::<f64>("timeout") {
+                Ok(value) => {
+                    let timeout = Duration::try_from_secs_f64(value).map_err(|_| {
+                        pyo3::exceptions::PyValueError::new_err(
+                            "`timeout` must be a non-negative number of seconds",
+                        )
+                    })?;
+                    builder = builder.timeout(timeout)
+                }
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
         }
-        builder.res_sync().map_err(|e| e.to_pyerr())
+        py.allow_threads(|| builder.res_sync())
+            .map_err(|e| e.to_pyerr())?;
+        self.1.count(Counter::QueriesSent);
+        Ok(())
     }
 
-    pub fn declare_keyexpr(&self, key_expr: &_KeyExpr) -> PyResult<_KeyExpr> {
-        match self.0.declare_keyexpr(&key_expr.0).res_sync() {
+    pub fn declare_keyexpr(&self, py: Python, key_expr: &_KeyExpr) -> PyResult<_KeyExpr> {
+        match py.allow_threads(|| self.0.declare_keyexpr(&key_expr.0).res_sync()) {
             Ok(k) => Ok(_KeyExpr(k.into_owned())),
             Err(e) => Err(e.to_pyerr()),
         }
@@ -162,12 +370,44 @@ impl _Session {
     #[pyo3(signature = (key_expr, callback, **kwargs))]
     pub fn declare_queryable(
         &self,
+        py: Python,
         key_expr: _KeyExpr,
         callback: &PyAny,
         kwargs: Option<&PyDict>,
     ) -> PyResult<_Queryable> {
+        self.audit("declare_queryable", key_expr.0.as_str(), None, kwargs)?;
         let callback: PyClosure<(_Query,)> = <_ as TryInto<_>>::try_into(callback)?;
-        let mut builder = self.0.declare_queryable(key_expr.0).with(callback);
+        let callback = self.dispatched::<_Query>(callback.into_cb_receiver_pair().0);
+        let cache = match kwargs.map(|kwargs| kwargs.extract_item::<(f64, usize)>("cache")) {
+            Some(Ok((ttl, max_entries))) => Some(Arc::new(QueryCache::new(ttl, max_entries)?)),
+            Some(Err(crate::ExtractError::Other(e))) => return Err(e),
+            _ => None,
+        };
+        let cached = cache.clone();
+        let stats = self.1.clone();
+        let rewrites = self.2.clone();
+        let mut builder = self
+            .0
+            .declare_queryable(self.2.outgoing(&key_expr.0))
+            .callback(move |query: Query| {
+                stats.count(Counter::QueriesReceived);
+                let cache = match (&cached, QueryCache::key(&query)) {
+                    (Some(cache), Some(key)) => match cache.lookup(&key) {
+                        Some(replies) => {
+                            for reply in replies {
+                                let _ = query.reply(reply).res_sync();
+                            }
+                            return;
+                        }
+                        None => Some((cache.clone(), key)),
+                    },
+                    _ => None,
+                };
+                callback(match cache {
+                    Some((cache, key)) => _Query::recorded(query, rewrites.clone(), cache, key),
+                    None => _Query::new(query, rewrites.clone()),
+                })
+            });
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<bool>("complete") {
                 Ok(value) => builder = builder.complete(value),
@@ -175,8 +415,75 @@ impl _Session {
                 _ => {}
             }
         }
-        match builder.res_sync() {
-            Ok(o) => Ok(_Queryable(o)),
+        match py.allow_threads(|| builder.res_sync()) {
+            Ok(o) => Ok(_Queryable(
+                DropWithoutGil::new(o),
+                self.1.declare(Entity::Queryable),
+                cache,
+            )),
+            Err(e) => Err(e.to_pyerr()),
+        }
+    }
+
+    /// Declares a queryable on `prefix/**` answering queries from the `keys()` and items of `mapping`,
+    /// each item being converted into a value by `encode`.
+    #[pyo3(signature = (prefix, mapping, encode, **kwargs))]
+    pub fn serve_mapping(
+        &self,
+        py: Python,
+        prefix: _KeyExpr,
+        mapping: PyObject,
+        encode: PyObject,
+        kwargs: Option<&PyDict>,
+    ) -> PyResult<_Queryable> {
+        let prefix = self.2.outgoing(&prefix.0);
+        let served = prefix.join("**").map_err(|e| e.to_pyerr())?;
+        let stats = self.1.clone();
+        let mut builder = self
+            .0
+            .declare_queryable(served)
+            .callback(move |query: Query| {
+                stats.count(Counter::QueriesReceived);
+                let replies = Python::with_gil(|py| -> PyResult<Vec<Sample>> {
+                    let mapping = mapping.as_ref(py);
+                    let mut replies = Vec::new();
+                    for key in mapping.call_method0("keys")?.iter()? {
+                        let key = key?;
+                        let key_expr = match prefix.join(key.str()?.to_str()?) {
+                            Ok(key_expr) => key_expr,
+                            Err(_) => continue,
+                        };
+                        if !query.key_expr().intersects(&key_expr) {
+                            continue;
+                        }
+                        let value: _Value =
+                            encode.call1(py, (mapping.get_item(key)?,))?.extract(py)?;
+                        replies.push(Sample::new(key_expr, Value::from(value)));
+                    }
+                    Ok(replies)
+                });
+                let replies = match replies {
+                    Ok(replies) => replies.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(Value::from(e.to_string()))],
+                };
+                for reply in replies {
+                    // Failing to reply means the querier is gone, there's no one left to tell.
+                    let _ = query.reply(reply).res_sync();
+                }
+            });
+        if let Some(kwargs) = kwargs {
+            match kwargs.extract_item::<bool>("complete") {
+                Ok(value) => builder = builder.complete(value),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+        }
+        match py.allow_threads(|| builder.res_sync()) {
+            Ok(o) => Ok(_Queryable(
+                DropWithoutGil::new(o),
+                self.1.declare(Entity::Queryable),
+                None,
+            )),
             Err(e) => Err(e.to_pyerr()),
         }
     }
@@ -184,61 +491,401 @@ impl _Session {
     #[pyo3(signature = (key_expr, **kwargs))]
     pub fn declare_publisher(
         &self,
+        py: Python,
         key_expr: _KeyExpr,
         kwargs: Option<&PyDict>,
     ) -> PyResult<_Publisher> {
-        let mut builder = self.0.declare_publisher(key_expr.0);
+        self.audit("declare_publisher", key_expr.0.as_str(), None, kwargs)?;
+        let mut congestion = CongestionControl::default();
+        let mut priority = Priority::default();
+        let mut outbox = None;
+        let mut builder = self.0.declare_publisher(self.2.outgoing(&key_expr.0));
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<_Priority>("priority") {
-                Ok(value) => builder = builder.priority(value.0),
+                Ok(value) => {
+                    priority = value.0;
+                    builder = builder.priority(value.0)
+                }
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
             match kwargs.extract_item::<_CongestionControl>("congestion_control") {
-                Ok(value) => builder = builder.congestion_control(value.0),
+                Ok(value) => {
+                    congestion = value.0;
+                    builder = builder.congestion_control(value.0)
+                }
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+            match kwargs.extract_item::<bool>("express") {
+                Ok(value) => builder = builder.express(value),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+            match kwargs.extract_item::<(Option<String>, usize, Option<f64>)>("outbox") {
+                Ok(value) => outbox = Some(value),
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
         }
-        match builder.res_sync() {
-            Ok(o) => Ok(_Publisher(o)),
+        let outbox = match outbox {
+            Some((path, capacity, max_age)) => {
+                if capacity == 0 {
+                    return Err(pyo3::exceptions::PyValueError::new_err(
+                        "the outbox's `max_messages` must be positive",
+                    ));
+                }
+                let max_age = max_age
+                    .map(|age| {
+                        Duration::try_from_secs_f64(age)
+                            .ok()
+                            .filter(|age| !age.is_zero())
+                            .ok_or_else(|| {
+                                pyo3::exceptions::PyValueError::new_err(
+                                    "the outbox's `max_age` must be a positive number of seconds",
+                                )
+                            })
+                    })
+                    .transpose()?;
+                let session = self.0.clone();
+                let outbox = py.allow_threads(|| {
+                    Outbox::open(
+                        session,
+                        priority,
+                        congestion,
+                        path.map(PathBuf::from),
+                        capacity,
+                        max_age,
+                    )
+                });
+                Some(outbox.map_err(|e| e.to_pyerr())?)
+            }
+            None => None,
+        };
+        match py.allow_threads(|| builder.res_sync()) {
+            Ok(o) => Ok(_Publisher(
+                DropWithoutGil::new(o),
+                self.1.declare(Entity::Publisher),
+                self.4.clone(),
+                key_expr.0,
+                self.5.clone(),
+                self.6.clone(),
+                congestion == CongestionControl::Block,
+                outbox,
+                Default::default(),
+                self.0.zid(),
+            )),
             Err(e) => Err(e.to_pyerr()),
         }
     }
 
+    #[pyo3(signature = (key_expr, value_fn, period, **kwargs))]
+    pub fn publish_periodically(
+        &self,
+        py: Python,
+        key_expr: _KeyExpr,
+        value_fn: Py<PyAny>,
+        period: f64,
+        kwargs: Option<&PyDict>,
+    ) -> PyResult<_PeriodicPublisher> {
+        let period = Duration::try_from_secs_f64(period)
+            .ok()
+            .filter(|period| !period.is_zero())
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(
+                    "`period` must be a positive number of seconds",
+                )
+            })?;
+        let publisher = self.declare_publisher(py, key_expr, kwargs)?;
+        let (stop, stopped) = flume::bounded::<()>(1);
+        let task = std::thread::spawn(move || {
+            let mut next = Instant::now();
+            loop {
+                // Don't try to catch up on the ticks a slow `value_fn` made us miss.
+                next = (next + period).max(Instant::now());
+                match stopped.recv_deadline(next) {
+                    Err(flume::RecvTimeoutError::Timeout) => {}
+                    _ => return None,
+                }
+                let value = Python::with_gil(|py| -> PyResult<Option<_Value>> {
+                    let value = value_fn.call0(py)?;
+                    if value.is_none(py) {
+                        Ok(None)
+                    } else {
+                        value.extract(py).map(Some)
+                    }
+                });
+                match value {
+                    Ok(Some(value)) => {
+                        if let Err(e) = publisher.send(value, None) {
+                            return Some(e);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => return Some(e),
+                }
+            }
+        });
+        Ok(_PeriodicPublisher {
+            stop: Some(stop),
+            task: Some(task),
+        })
+    }
+
     #[pyo3(signature = (key_expr, callback, **kwargs))]
     pub fn declare_subscriber(
         &self,
+        py: Python,
         key_expr: &_KeyExpr,
         callback: &PyAny,
         kwargs: Option<&PyDict>,
     ) -> PyResult<_Subscriber> {
-        let callback: PyClosure<(_Sample,)> = <_ as TryInto<_>>::try_into(callback)?;
-        let mut builder = self.0.declare_subscriber(&key_expr.0).with(callback);
+        self.audit("declare_subscriber", key_expr.0.as_str(), None, kwargs)?;
+        let mut builder = self.0.declare_subscriber(self.2.outgoing(&key_expr.0));
+        let mut reused = None;
+        let mut batch = None;
+        let mut changes = false;
+        let mut sink = None;
+        let mut filter = None;
+        let mut profile = None;
+        let mut overflow = None;
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<_Reliability>("reliability") {
                 Ok(reliabilty) => builder = builder.reliability(reliabilty.0),
                 Err(crate::ExtractError::Other(e)) => return Err(e),
                 _ => {}
             }
+            match kwargs.extract_item::<(usize, &str)>("overflow") {
+                Ok((capacity, policy)) => overflow = Some((capacity, Overflow::new(policy)?)),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+            match kwargs.extract_item::<_Locality>("allowed_origin") {
+                Ok(locality) => builder = builder.allowed_origin(locality.0),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+            match kwargs.extract_item::<&PyAny>("reuse_samples") {
+                Ok(slot) => reused = Some(slot),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+            match kwargs.extract_item::<(usize, f64)>("batch") {
+                Ok(value) => batch = Some(value),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+            match kwargs.extract_item::<bool>("changes") {
+                Ok(value) => changes = value,
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+            match kwargs.extract_item::<&str>("sink") {
+                Ok(kind) => sink = Some(Arc::new(SampleSink::new(kind)?)),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+            match kwargs.extract_item::<_SampleFilter>("filter") {
+                Ok(value) => filter = Some(value),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+            match kwargs.extract_item::<Py<_CallbackProfile>>("profile") {
+                Ok(value) => profile = Some(value),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+        }
+        if profile.is_some() && (sink.is_some() || changes || reused.is_some() || batch.is_some()) {
+            return Err(zenoh_core::zerror!(
+                "`profile` can't be combined with `sink`, `changes`, `reuse_samples` nor `batch`"
+            )
+            .to_pyerr());
+        }
+        if sink.is_some() && overflow.is_some() {
+            return Err(zenoh_core::zerror!(
+                "`overflow` can't be combined with `sink`, which has no callback"
+            )
+            .to_pyerr());
+        }
+        let handler: Callback<'static, Sample> = if let Some(sink) = &sink {
+            let sink = sink.clone();
+            Arc::new(move |sample| sink.receive(sample))
+        } else if changes {
+            if reused.is_some() || batch.is_some() {
+                return Err(zenoh_core::zerror!(
+                    "`changes` can't be combined with `reuse_samples` nor `batch`"
+                )
+                .to_pyerr());
+            }
+            let callback: PyClosure<(_Change,)> = <_ as TryInto<_>>::try_into(callback)?;
+            callback.into_cb_receiver_pair().0
+        } else {
+            match (reused, batch) {
+                (Some(_), Some(_)) => {
+                    return Err(zenoh_core::zerror!(
+                        "`reuse_samples` and `batch` can't be used on the same subscriber"
+                    )
+                    .to_pyerr())
+                }
+                (Some(slot), None) => {
+                    SampleReusingClosure::new(callback, slot)?
+                        .into_cb_receiver_pair()
+                        .0
+                }
+                (None, Some((max_count, max_delay))) => {
+                    PyBatchingClosure::<_Sample>::new(callback, max_count, max_delay)?
+                        .into_cb_receiver_pair()
+                        .0
+                }
+                (None, None) => match profile {
+                    Some(profile) => {
+                        PyProfilingClosure::<_Sample>::new(callback, profile)?
+                            .into_cb_receiver_pair()
+                            .0
+                    }
+                    None => {
+                        let callback: PyClosure<(_Sample,)> =
+                            <_ as TryInto<_>>::try_into(callback)?;
+                        callback.into_cb_receiver_pair().0
+                    }
+                },
+            }
+        };
+        let dropped = Arc::new(AtomicU64::new(0));
+        let handler = match overflow {
+            _ if sink.is_some() => handler,
+            // the overflow buffer's thread takes over from the callback pool's
+            Some((capacity, overflow)) => {
+                overflowing(handler, capacity, overflow, dropped.clone())?
+            }
+            None => self.dispatched(handler),
+        };
+        let activity = Arc::new(Activity::new());
+        let seen = activity.clone();
+        let stats = self.1.clone();
+        let rewrites = self.2.clone();
+        let interceptors = self.4.clone();
+        let builder = builder.with(Filtered::new(
+            move |mut sample: Sample| {
+                stats.count(Counter::SamplesReceived);
+                stats.traffic(
+                    Flow::Received,
+                    sample.key_expr.as_str(),
+                    sample.value.payload.len(),
+                );
+                sample.key_expr = rewrites.incoming(&sample.key_expr);
+                let seen = seen.clone();
+                let handler = handler.clone();
+                interceptors.intercept_ingress(sample, move |sample| {
+                    seen.touch(&sample);
+                    handler(sample)
+                })
+            },
+            filter,
+        ));
+        let subscriber = py
+            .allow_threads(|| builder.res())
+            .map_err(|e| e.to_pyerr())?;
+        Ok(_Subscriber {
+            _subscriber: DropWithoutGil::new(subscriber),
+            sink,
+            activity,
+            dropped,
+            _declared: self.1.declare(Entity::Subscriber),
+        })
+    }
+
+    #[pyo3(signature = (key_expr, **kwargs))]
+    pub fn declare_dispatcher(
+        &self,
+        py: Python,
+        key_expr: &_KeyExpr,
+        kwargs: Option<&PyDict>,
+    ) -> PyResult<_Dispatcher> {
+        self.audit("declare_dispatcher", key_expr.0.as_str(), None, kwargs)?;
+        let routes: Routes = Default::default();
+        let dispatched = routes.clone();
+        let dispatch: Callback<'static, Sample> = Arc::new(move |sample: Sample| {
+            let matching: Vec<_> = dispatched
+                .read()
+                .unwrap()
+                .iter()
+                .filter(|(route, _)| route.intersects(&sample.key_expr))
+                .map(|(_, callback)| callback.clone())
+                .collect();
+            if matching.is_empty() {
+                return;
+            }
+            let sample = _Sample::from(sample);
+            for callback in matching {
+                callback.call((sample.clone(),)).cb_unwrap();
+            }
+        });
+        let dispatch = self.dispatched(dispatch);
+        let stats = self.1.clone();
+        let rewrites = self.2.clone();
+        let interceptors = self.4.clone();
+        let mut builder = self
+            .0
+            .declare_subscriber(self.2.outgoing(&key_expr.0))
+            .callback(move |mut sample: Sample| {
+                stats.count(Counter::SamplesReceived);
+                stats.traffic(
+                    Flow::Received,
+                    sample.key_expr.as_str(),
+                    sample.value.payload.len(),
+                );
+                sample.key_expr = rewrites.incoming(&sample.key_expr);
+                let dispatch = dispatch.clone();
+                interceptors.intercept_ingress(sample, move |sample| dispatch(sample))
+            });
+        if let Some(kwargs) = kwargs {
+            match kwargs.extract_item::<_Reliability>("reliability") {
+                Ok(reliabilty) => builder = builder.reliability(reliabilty.0),
+                Err(crate::ExtractError::Other(e)) => return Err(e),
+                _ => {}
+            }
+        }
+        match py.allow_threads(|| builder.res()) {
+            Ok(subscriber) => Ok(_Dispatcher {
+                _subscriber: DropWithoutGil::new(subscriber),
+                routes,
+                _declared: self.1.declare(Entity::Subscriber),
+            }),
+            Err(e) => Err(e.to_pyerr()),
         }
-        let subscriber = builder.res().map_err(|e| e.to_pyerr())?;
-        Ok(_Subscriber(subscriber))
     }
 
     #[pyo3(signature = (key_expr, callback, **kwargs))]
     pub fn declare_pull_subscriber(
         &self,
+        py: Python,
         key_expr: &_KeyExpr,
         callback: &PyAny,
         kwargs: Option<&PyDict>,
     ) -> PyResult<_PullSubscriber> {
+        self.audit("declare_pull_subscriber", key_expr.0.as_str(), None, kwargs)?;
         let callback: PyClosure<(_Sample,)> = <_ as TryInto<_>>::try_into(callback)?;
+        let callback = self.dispatched::<Sample>(callback.into_cb_receiver_pair().0);
+        let stats = self.1.clone();
+        let rewrites = self.2.clone();
+        let interceptors = self.4.clone();
         let mut builder = self
             .0
-            .declare_subscriber(&key_expr.0)
+            .declare_subscriber(self.2.outgoing(&key_expr.0))
             .pull_mode()
-            .with(callback);
+            .callback(move |mut sample: Sample| {
+                stats.count(Counter::SamplesReceived);
+                stats.traffic(
+                    Flow::Received,
+                    sample.key_expr.as_str(),
+                    sample.value.payload.len(),
+                );
+                sample.key_expr = rewrites.incoming(&sample.key_expr);
+                let callback = callback.clone();
+                interceptors.intercept_ingress(sample, move |sample| callback(sample))
+            });
         if let Some(kwargs) = kwargs {
             match kwargs.extract_item::<_Reliability>("reliability") {
                 Ok(reliabilty) => builder = builder.reliability(reliabilty.0),
@@ -246,8 +893,13 @@ impl _Session {
                 _ => {}
             }
         }
-        let subscriber = builder.res().map_err(|e| e.to_pyerr())?;
-        Ok(_PullSubscriber(subscriber))
+        let subscriber = py
+            .allow_threads(|| builder.res())
+            .map_err(|e| e.to_pyerr())?;
+        Ok(_PullSubscriber(
+            DropWithoutGil::new(subscriber),
+            self.1.declare(Entity::Subscriber),
+        ))
     }
 
     pub fn zid(&self) -> _ZenohId {
@@ -264,11 +916,217 @@ impl _Session {
     pub fn peers_zid(&self) -> Vec<_ZenohId> {
         self.0.info().peers_zid().res_sync().map(_ZenohId).collect()
     }
+
+    /// Maps the `local/**` key space onto `remote/**` for the operations made through this session from now on.
+    pub fn add_keyexpr_mapping(&self, local: &str, remote: &str) -> PyResult<()> {
+        self.2.add(local, remote)
+    }
+    pub fn remove_keyexpr_mapping(&self, local: &str) -> PyResult<bool> {
+        self.2.remove(local)
+    }
+    pub fn keyexpr_mappings(&self) -> Vec<(String, String)> {
+        self.2.list()
+    }
+    /// Registers a hook on the samples this session publishes and/or receives, returning its id.
+    #[pyo3(signature = (callback = None, key_expr = None, filter = None, ingress = true, egress = true))]
+    pub fn add_interceptor(
+        &self,
+        callback: Option<PyObject>,
+        key_expr: Option<_KeyExpr>,
+        filter: Option<_SampleFilter>,
+        ingress: bool,
+        egress: bool,
+    ) -> PyResult<u64> {
+        if callback.is_none() && key_expr.is_none() && filter.is_none() {
+            return Err(zenoh_core::zerror!(
+                "an interceptor without `callback` requires a `key_expr` or a `filter`, as it would drop all samples"
+            )
+            .to_pyerr());
+        }
+        let action = callback.map_or(Action::Drop, Action::Callback);
+        Ok(self
+            .4
+            .add(ingress, egress, key_expr.map(|k| k.0), filter, action))
+    }
+    /// Registers a hook simulating a lossy and/or slow network on the matching samples, returning its id.
+    #[pyo3(signature = (key_expr = None, drop = 0., delay = 0., jitter = 0., ingress = true, egress = true))]
+    pub fn inject_fault(
+        &self,
+        key_expr: Option<_KeyExpr>,
+        drop: f64,
+        delay: f64,
+        jitter: f64,
+        ingress: bool,
+        egress: bool,
+    ) -> PyResult<u64> {
+        if !(0. ..=1.).contains(&drop) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "`drop` must be a probability, between 0 and 1",
+            ));
+        }
+        let duration = |name: &str, seconds: f64| {
+            Duration::try_from_secs_f64(seconds).map_err(|_| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "`{name}` must be a non-negative number of seconds"
+                ))
+            })
+        };
+        let fault = Fault {
+            drop,
+            delay: duration("delay", delay)?,
+            jitter: duration("jitter", jitter)?,
+        };
+        Ok(self.4.add(
+            ingress,
+            egress,
+            key_expr.map(|k| k.0),
+            None,
+            Action::Fault(fault),
+        ))
+    }
+    pub fn remove_interceptor(&self, id: u64) -> bool {
+        self.4.remove(id)
+    }
+    /// Limits the session's outgoing bandwidth, or lifts the limit if `bytes_per_s` is `None`.
+    #[pyo3(signature = (bytes_per_s, burst = None))]
+    pub fn set_rate_limit(&self, bytes_per_s: Option<f64>, burst: Option<f64>) -> PyResult<()> {
+        let limit = match bytes_per_s {
+            Some(rate) => {
+                let burst = burst.unwrap_or(rate);
+                if !(rate > 0. && rate.is_finite() && burst > 0. && burst.is_finite()) {
+                    return Err(pyo3::exceptions::PyValueError::new_err(
+                        "`bytes_per_s` and `burst` must be positive",
+                    ));
+                }
+                Some((rate, burst))
+            }
+            None => None,
+        };
+        self.6.set(limit);
+        Ok(())
+    }
+    #[getter]
+    pub fn rate_limit(&self) -> Option<(f64, f64)> {
+        self.6.get()
+    }
+    /// Waits for up to `timeout` seconds for the callbacks queued on the session's callback threads to have run,
+    /// returning whether they all did. Sessions without callback threads run their callbacks on zenoh's threads instead.
+    pub fn drain_callbacks(&self, py: Python, timeout: f64) -> PyResult<bool> {
+        let timeout = Duration::try_from_secs_f64(timeout).map_err(|_| {
+            pyo3::exceptions::PyValueError::new_err(
+                "`timeout` must be a non-negative number of seconds",
+            )
+        })?;
+        Ok(match &self.3 {
+            Some(pool) => py.allow_threads(|| pool.drain(timeout)),
+            None => true,
+        })
+    }
+    /// The session's counters and entity counts, or with `by_key`, the traffic attributed to each key expression prefix.
+    #[pyo3(signature = (by_key = false))]
+    pub fn stats(&self, py: Python, by_key: bool) -> PyResult<PyObject> {
+        let stats = PyDict::new(py);
+        if by_key {
+            for (prefix, counts) in self.1.traffic_by_key() {
+                let traffic = PyDict::new(py);
+                for (name, count) in TRAFFIC.iter().zip(counts) {
+                    traffic.set_item(name, count)?;
+                }
+                stats.set_item(prefix, traffic)?;
+            }
+        } else {
+            for (name, value) in self.1.snapshot() {
+                stats.set_item(name, value)?;
+            }
+            stats.set_item("uptime", self.1.uptime().as_secs_f64())?;
+        }
+        Ok(stats.into())
+    }
+    /// Sets how many chunks of key expressions traffic is attributed to, 0 disabling the attribution.
+    pub fn set_stats_key_depth(&self, depth: usize) {
+        self.1.set_key_depth(depth)
+    }
+    #[getter]
+    pub fn stats_key_depth(&self) -> usize {
+        self.1.key_depth()
+    }
+    /// The session's statistics, in the Prometheus text exposition format.
+    pub fn metrics(&self) -> String {
+        self.1.to_prometheus(&self.0.zid())
+    }
+    /// Declares a queryable replying with the session's metrics, without calling into Python.
+    pub fn serve_metrics(&self, key_expr: _KeyExpr) -> PyResult<_Queryable> {
+        let stats = self.1.clone();
+        let zid = self.0.zid();
+        let queryable = self
+            .0
+            .declare_queryable(self.2.outgoing(&key_expr.0))
+            .callback(move |query: Query| {
+                let value = Value::from(stats.to_prometheus(&zid))
+                    .encoding(Encoding::Exact(KnownEncoding::TextPlain));
+                let _ = query
+                    .reply(Ok(Sample::new(query.key_expr().clone(), value)))
+                    .res_sync();
+            })
+            .res_sync()
+            .map_err(|e| e.to_pyerr())?;
+        Ok(_Queryable(
+            DropWithoutGil::new(queryable),
+            self.1.declare(Entity::Queryable),
+            None,
+        ))
+    }
+    /// Declares a queryable replying with a JSON health report, without calling into Python.
+    pub fn enable_healthcheck(&self, key_expr: _KeyExpr) -> PyResult<_Queryable> {
+        let stats = self.1.clone();
+        let zid = self.0.zid();
+        let queryable = self
+            .0
+            .declare_queryable(self.2.outgoing(&key_expr.0))
+            .callback(move |query: Query| {
+                let value = Value::from(stats.to_health_json(&zid))
+                    .encoding(Encoding::Exact(KnownEncoding::AppJson));
+                let _ = query
+                    .reply(Ok(Sample::new(query.key_expr().clone(), value)))
+                    .res_sync();
+            })
+            .res_sync()
+            .map_err(|e| e.to_pyerr())?;
+        Ok(_Queryable(
+            DropWithoutGil::new(queryable),
+            self.1.declare(Entity::Queryable),
+            None,
+        ))
+    }
+    /// Serves the session's metrics over HTTP on `address:port`, from a thread of its own.
+    #[pyo3(signature = (address = "127.0.0.1", port = 0))]
+    pub fn export_metrics(&self, address: &str, port: u16) -> PyResult<_MetricsExporter> {
+        _MetricsExporter::spawn(self.0.zid(), self.1.clone(), address, port)
+    }
+}
+
+/// Names `zid` as the source of a publication, which subscribers read as `Sample.source_id`.
+pub(crate) fn source_info(zid: ZenohId) -> SourceInfo {
+    SourceInfo {
+        source_id: Some(zid),
+        source_sn: None,
+    }
 }
 
 #[pyclass(subclass)]
 #[derive(Clone)]
-pub struct _Publisher(Publisher<'static>);
+pub struct _Publisher(
+    DropWithoutGil<Publisher<'static>>,
+    Declared,
+    Arc<Interceptors>,
+    KeyExpr<'static>,
+    Option<Arc<AuditLog>>,
+    Arc<RateLimiter>,
+    bool,
+    Option<Arc<Outbox>>,
+    Arc<Pressure>,
+    ZenohId,
+);
 #[pymethods]
 impl _Publisher {
     #[new]
@@ -279,28 +1137,367 @@ impl _Publisher {
     pub fn key_expr(&self) -> _KeyExpr {
         _KeyExpr(self.0.key_expr().clone())
     }
-    pub fn put(&self, value: _Value) -> PyResult<()> {
-        self.0.put(value).res_sync().map_err(|e| e.to_pyerr())
+    #[pyo3(signature = (value, attachment = None))]
+    pub fn put(&self, py: Python, value: _Value, attachment: Option<&PyDict>) -> PyResult<()> {
+        let attachment = match attachment {
+            Some(attachment) => crate::interceptor::attachment_from_dict(attachment)?,
+            None => None,
+        };
+        py.allow_threads(|| self.send(value, attachment))
     }
-    pub fn delete(&self) -> PyResult<()> {
-        self.0.delete().res_sync().map_err(|e| e.to_pyerr())
+    pub fn delete(&self, py: Python) -> PyResult<()> {
+        if let Some(log) = &self.4 {
+            log.record("publisher.delete", self.3.as_str(), None, None)?;
+        }
+        let mut attachment = match py.allow_threads(|| self.2.intercept_delete(&self.3)) {
+            Some(sample) => sample.attachment,
+            None => return Ok(()),
+        };
+        if let Some(outbox) = &self.7 {
+            let mut sample = Sample::new(self.0.key_expr().clone(), Value::empty());
+            sample.kind = SampleKind::Delete;
+            sample.attachment = attachment;
+            match py.allow_threads(|| outbox.hold(sample)) {
+                Some(sample) => attachment = sample.attachment,
+                None => {
+                    self.count_delete();
+                    return Ok(());
+                }
+            }
+        }
+        let mut builder = self.0.delete().with_source_info(source_info(self.9));
+        if let Some(attachment) = attachment {
+            builder = builder.with_attachment(attachment);
+        }
+        py.allow_threads(|| builder.res_sync())
+            .map_err(|e| e.to_pyerr())?;
+        self.count_delete();
+        Ok(())
+    }
+    /// The recent average time puts took, in seconds, and share of publications dropped or held back, see [`Pressure`].
+    pub fn pressure(&self) -> (f64, f64) {
+        self.8.get()
+    }
+    /// The number of publications held back by the publisher's outbox.
+    #[getter]
+    pub fn outbox_len(&self) -> usize {
+        self.7.as_ref().map_or(0, |outbox| outbox.len())
+    }
+    /// The number of publications the outbox evicted, for lack of room or because they expired.
+    #[getter]
+    pub fn outbox_dropped(&self) -> u64 {
+        self.7.as_ref().map_or(0, |outbox| outbox.dropped())
+    }
+    /// Forwards the publications held back right away if the session reaches the network, returning how many were.
+    ///
+    /// Raises the error that stopped the outbox from writing its file, if any.
+    pub fn flush_outbox(&self, py: Python) -> PyResult<usize> {
+        match &self.7 {
+            Some(outbox) => py.allow_threads(|| match outbox.take_error() {
+                Some(e) => Err(e.to_pyerr()),
+                None => Ok(outbox.forward()),
+            }),
+            None => Ok(0),
+        }
+    }
+}
+impl _Publisher {
+    /// Publishes `value` with `attachment`, for callers that don't hold the GIL.
+    fn send(&self, value: _Value, attachment: Option<Attachment>) -> PyResult<()> {
+        let value = value.into();
+        if let Some(log) = &self.4 {
+            log.record("publisher.put", self.3.as_str(), Some(&value), None)?;
+        }
+        let (value, attachment) = match self.2.intercept_put(&self.3, value, attachment) {
+            Some(sample) => (sample.value, sample.attachment),
+            None => return Ok(()),
+        };
+        let start = Instant::now();
+        if !self.5.admit(value.payload.len(), self.6) {
+            self.8.record(Duration::ZERO, true);
+            return Ok(());
+        }
+        let size = value.payload.len();
+        let (value, attachment) = match &self.7 {
+            Some(outbox) => {
+                let mut sample = Sample::new(self.0.key_expr().clone(), value);
+                sample.attachment = attachment;
+                match outbox.hold(sample) {
+                    Some(sample) => (sample.value, sample.attachment),
+                    None => {
+                        self.8.record(Duration::ZERO, true);
+                        self.count_put(size);
+                        return Ok(());
+                    }
+                }
+            }
+            None => (value, attachment),
+        };
+        let mut publication = self.0.put(value).with_source_info(source_info(self.9));
+        if let Some(attachment) = attachment {
+            publication = publication.with_attachment(attachment);
+        }
+        publication.res_sync().map_err(|e| e.to_pyerr())?;
+        self.8.record(start.elapsed(), false);
+        self.count_put(size);
+        Ok(())
+    }
+    fn count_put(&self, size: usize) {
+        let stats = self.1.stats();
+        stats.count(Counter::Puts);
+        stats.traffic(Flow::Sent, self.0.key_expr().as_str(), size);
+    }
+    fn count_delete(&self) {
+        let stats = self.1.stats();
+        stats.count(Counter::Deletes);
+        stats.traffic(Flow::Sent, self.0.key_expr().as_str(), 0);
+    }
+}
+
+/// How hard a publisher's publications are being pushed back, as exponential moving averages
+/// whose past weighs half as much every [`PRESSURE_HALF_LIFE`].
+///
+/// Zenoh doesn't expose the occupancy of its queues, but puts with `CongestionControl::Block` wait for room
+/// in them, so the time puts take reflects it. Publications dropped by the session's rate limit,
+/// or held back by an outbox, count as fully pushed back.
+#[derive(Default)]
+pub(crate) struct Pressure(Mutex<PressureState>);
+#[derive(Default)]
+struct PressureState {
+    updated: Option<Instant>,
+    latency: f64,
+    held_back: f64,
+}
+const PRESSURE_HALF_LIFE: f64 = 1.0;
+impl PressureState {
+    /// The weight left to the averages after `now - updated`, as if nothing was published meanwhile.
+    fn decay(&self, now: Instant) -> f64 {
+        self.updated.map_or(0., |updated| {
+            0.5f64.powf(now.duration_since(updated).as_secs_f64() / PRESSURE_HALF_LIFE)
+        })
+    }
+}
+impl Pressure {
+    fn record(&self, latency: Duration, held_back: bool) {
+        let mut state = self.0.lock().unwrap();
+        let now = Instant::now();
+        // a publication weighs at least 1%, however fast they follow each other
+        let decay = state.decay(now).min(0.99);
+        state.latency = state.latency * decay + latency.as_secs_f64() * (1. - decay);
+        state.held_back = state.held_back * decay + if held_back { 1. - decay } else { 0. };
+        state.updated = Some(now);
+    }
+    fn get(&self) -> (f64, f64) {
+        let state = self.0.lock().unwrap();
+        let decay = state.decay(Instant::now());
+        (state.latency * decay, state.held_back * decay)
     }
 }
 
+/// A task publishing the values returned by a Python function at a fixed rate.
 #[pyclass(subclass)]
-pub struct _Subscriber(Subscriber<'static, ()>);
+pub struct _PeriodicPublisher {
+    stop: Option<flume::Sender<()>>,
+    task: Option<std::thread::JoinHandle<Option<PyErr>>>,
+}
+#[pymethods]
+impl _PeriodicPublisher {
+    /// Stops the task, raising the error that stopped it earlier, if any.
+    fn stop(&mut self, py: Python) -> PyResult<()> {
+        self.stop = None;
+        match self.task.take() {
+            Some(task) => match py.allow_threads(|| task.join()) {
+                Ok(None) => Ok(()),
+                Ok(Some(e)) => Err(e),
+                Err(_) => Err(zenoh_core::zerror!("periodic publication panicked").to_pyerr()),
+            },
+            None => Ok(()),
+        }
+    }
+    #[getter]
+    fn running(&self) -> bool {
+        self.task.as_ref().map_or(false, |task| !task.is_finished())
+    }
+}
+
+/// Tracks when a subscription last delivered a sample, and who awaits the next one.
+pub(crate) struct Activity {
+    start: Instant,
+    last: AtomicU64,
+    waiting: AtomicUsize,
+    waiters: Mutex<Vec<flume::Sender<Sample>>>,
+}
+impl Activity {
+    fn new() -> Self {
+        Activity {
+            start: Instant::now(),
+            last: AtomicU64::new(0),
+            waiting: AtomicUsize::new(0),
+            waiters: Mutex::new(Vec::new()),
+        }
+    }
+    fn touch(&self, sample: &Sample) {
+        self.last
+            .store(self.start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        if self.waiting.load(Ordering::Relaxed) != 0 {
+            let mut waiters = self.waiters.lock().unwrap();
+            self.waiting.store(0, Ordering::Relaxed);
+            for waiter in waiters.drain(..) {
+                let _ = waiter.send(sample.clone());
+            }
+        }
+    }
+    fn wait(&self) -> flume::Receiver<Sample> {
+        let (sender, receiver) = flume::bounded(1);
+        let mut waiters = self.waiters.lock().unwrap();
+        waiters.push(sender);
+        self.waiting.store(waiters.len(), Ordering::Relaxed);
+        receiver
+    }
+    fn last(&self) -> Instant {
+        self.start + Duration::from_nanos(self.last.load(Ordering::Relaxed))
+    }
+}
 
 #[pyclass(subclass)]
-pub struct _PullSubscriber(PullSubscriber<'static, ()>);
+pub struct _Subscriber {
+    _subscriber: DropWithoutGil<Subscriber<'static, ()>>,
+    sink: Option<Arc<SampleSink>>,
+    activity: Arc<Activity>,
+    dropped: Arc<AtomicU64>,
+    _declared: Declared,
+}
+#[pymethods]
+impl _Subscriber {
+    /// Calls `callback` whenever no sample was delivered for `timeout` seconds,
+    /// once per silence, for as long as the subscription lives.
+    fn on_silence(&self, timeout: f64, callback: Py<PyAny>) -> PyResult<()> {
+        let timeout = Duration::try_from_secs_f64(timeout)
+            .ok()
+            .filter(|timeout| !timeout.is_zero())
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(
+                    "`timeout` must be a positive number of seconds",
+                )
+            })?;
+        let activity = Arc::downgrade(&self.activity);
+        std::thread::spawn(move || {
+            let mut reported = None;
+            loop {
+                let last = match activity.upgrade() {
+                    Some(activity) => activity.last(),
+                    None => return,
+                };
+                // a silence too long to be represented never ends
+                let Some(deadline) = last.checked_add(timeout) else {
+                    return;
+                };
+                let now = Instant::now();
+                if now < deadline {
+                    std::thread::sleep(deadline - now);
+                } else if reported != Some(last) {
+                    reported = Some(last);
+                    Python::with_gil(|py| callback.call0(py)).cb_unwrap();
+                } else {
+                    std::thread::sleep(timeout);
+                }
+            }
+        });
+        Ok(())
+    }
+    /// Waits for the next sample the subscription delivers, returning `None` if `timeout` expires first.
+    fn next_sample(&self, py: Python, timeout: Option<f64>) -> PyResult<Option<_Sample>> {
+        let timeout = timeout
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|_| {
+                pyo3::exceptions::PyValueError::new_err(
+                    "`timeout` must be a non-negative number of seconds",
+                )
+            })?;
+        let receiver = self.activity.wait();
+        // timeouts too large to be represented as instants never expire
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        Ok(recv_interruptibly(py, &receiver, deadline)?
+            .ok()
+            .map(_Sample::from))
+    }
+    fn count(&self) -> PyResult<u64> {
+        match &self.sink {
+            Some(sink) => Ok(sink.count()),
+            None => {
+                Err(zenoh_core::zerror!("this subscriber wasn't declared with a sink").to_pyerr())
+            }
+        }
+    }
+    fn latest(&self) -> PyResult<Option<_Sample>> {
+        match &self.sink {
+            Some(sink) => sink.latest(),
+            None => {
+                Err(zenoh_core::zerror!("this subscriber wasn't declared with a sink").to_pyerr())
+            }
+        }
+    }
+    /// The number of samples dropped so far by the subscription's overflow policy.
+    #[getter]
+    fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+type Routes = Arc<RwLock<Vec<(KeyExpr<'static>, Arc<PyClosure<(_Sample,)>>)>>>;
+
+/// A single subscription dispatching its samples to the callbacks of the routes that intersect with them.
+#[pyclass(subclass)]
+pub struct _Dispatcher {
+    _subscriber: DropWithoutGil<Subscriber<'static, ()>>,
+    routes: Routes,
+    _declared: Declared,
+}
+#[pymethods]
+impl _Dispatcher {
+    fn add(&self, py: Python, key_expr: _KeyExpr, callback: &PyAny) -> PyResult<()> {
+        let callback = Arc::new(<_ as TryInto<_>>::try_into(callback)?);
+        // The subscription's callback needs the GIL once it has released the lock, so don't hold the former while waiting for the latter.
+        py.allow_threads(|| self.routes.write().unwrap().push((key_expr.0, callback)));
+        Ok(())
+    }
+    fn remove(&self, py: Python, key_expr: &_KeyExpr) -> bool {
+        let removed: Vec<_> = py.allow_threads(|| {
+            let mut routes = self.routes.write().unwrap();
+            let (removed, kept) = routes
+                .drain(..)
+                .partition(|(route, _)| route == &key_expr.0);
+            *routes = kept;
+            removed
+        });
+        !removed.is_empty()
+    }
+}
+
+#[pyclass(subclass)]
+pub struct _PullSubscriber(DropWithoutGil<PullSubscriber<'static, ()>>, Declared);
 #[pymethods]
 impl _PullSubscriber {
-    fn pull(&self) -> PyResult<()> {
-        self.0.pull().res_sync().map_err(|e| e.to_pyerr())
+    fn pull(&self, py: Python) -> PyResult<()> {
+        py.allow_threads(|| self.0.pull().res_sync())
+            .map_err(|e| e.to_pyerr())
     }
 }
 
 #[pyclass(subclass)]
-pub struct _Scout(Scout<()>);
+pub struct _Scout(Option<DropWithoutGil<Scout<()>>>);
+#[pymethods]
+impl _Scout {
+    /// Stops sending scout messages and calling back. Stopping a stopped scout does nothing.
+    pub fn stop(&mut self) {
+        self.0 = None;
+    }
+    #[getter]
+    pub fn stopped(&self) -> bool {
+        self.0.is_none()
+    }
+}
 
 #[pyfunction]
 pub fn scout(callback: &PyAny, config: Option<&_Config>, what: Option<&str>) -> PyResult<_Scout> {
@@ -313,9 +1510,10 @@ pub fn scout(callback: &PyAny, config: Option<&_Config>, what: Option<&str>) ->
         },
     };
     let config = config.and_then(|c| c.0.clone().take()).unwrap_or_default();
+    crate::start_runtime();
     let scout = zenoh::scout(what, config).with(callback).res_sync();
     match scout {
-        Ok(scout) => Ok(_Scout(scout)),
+        Ok(scout) => Ok(_Scout(Some(DropWithoutGil::new(scout)))),
         Err(e) => Err(e.to_pyerr()),
     }
 }
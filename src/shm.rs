@@ -0,0 +1,106 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! Shared memory payloads, for processes on the same host to exchange large samples without copying them through
+//! their transports.
+//!
+//! Payloads are copied once into a segment the publishing process owns: zenoh then only sends their location to the
+//! subscribers on the same host that enabled shared memory too, which map the segment to read them.
+use std::sync::Mutex;
+
+use pyo3::prelude::*;
+use zenoh::{prelude::Encoding, shm::SharedMemoryManager};
+use zenoh_buffers::ZBuf;
+
+use crate::{
+    dlpack,
+    enums::_Encoding,
+    value::{Payload, _Value},
+    ToPyErr,
+};
+
+#[pyclass(subclass)]
+pub struct _ShmManager(Mutex<SharedMemoryManager>);
+#[pymethods]
+impl _ShmManager {
+    #[new]
+    pub fn new(id: String, size: usize) -> PyResult<Self> {
+        if size == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "shared memory segments must hold at least one byte",
+            ));
+        }
+        SharedMemoryManager::make(id, size)
+            .map(|manager| _ShmManager(Mutex::new(manager)))
+            .map_err(|e| e.to_pyerr())
+    }
+    /// Copies `payload` into the segment, returning a value referring to it.
+    ///
+    /// The buffers the subscribers released are reclaimed when the segment is full.
+    pub fn value(
+        &self,
+        py: Python,
+        payload: &[u8],
+        encoding: Option<_Encoding>,
+    ) -> PyResult<_Value> {
+        Ok(_Value {
+            payload: Payload::Zenoh(self.copy(py, payload)?),
+            encoding: encoding.map(|e| e.0).unwrap_or(Encoding::EMPTY),
+        })
+    }
+    /// Copies the bytes of a host tensor into the segment, recording its data type and shape in the encoding.
+    pub fn from_dlpack(&self, py: Python, tensor: &PyAny) -> PyResult<_Value> {
+        dlpack::with_tensor(tensor, |bytes, encoding| {
+            Ok(_Value {
+                payload: Payload::Zenoh(self.copy(py, bytes)?),
+                encoding,
+            })
+        })
+    }
+    /// Reclaims the buffers the subscribers released, returning how many bytes were.
+    pub fn collect(&self, py: Python) -> usize {
+        py.allow_threads(|| {
+            let mut manager = self.0.lock().unwrap();
+            let freed = manager.garbage_collect();
+            manager.defragment();
+            freed
+        })
+    }
+}
+impl _ShmManager {
+    fn copy(&self, py: Python, payload: &[u8]) -> PyResult<ZBuf> {
+        let buf = py.allow_threads(|| {
+            let mut manager = self.0.lock().unwrap();
+            let mut buf = match manager.alloc(payload.len()) {
+                Ok(buf) => buf,
+                Err(_) => {
+                    manager.garbage_collect();
+                    manager.defragment();
+                    manager.alloc(payload.len()).map_err(|e| e.to_string())?
+                }
+            };
+            // the buffer was just allocated: no one else refers to it yet
+            unsafe { buf.as_mut_slice() }.copy_from_slice(payload);
+            Ok::<_, String>(buf)
+        });
+        let buf = buf.map_err(|e| {
+            zenoh_core::zerror!(
+                "no room for {} bytes in the shared memory segment: {}",
+                payload.len(),
+                e
+            )
+            .to_pyerr()
+        })?;
+        Ok(ZBuf::from(buf))
+    }
+}
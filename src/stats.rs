@@ -0,0 +1,341 @@
+//
+// Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+//
+//! Session statistics, maintained without involving Python so they can be exported while the interpreter is busy.
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{Read, Write},
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use pyo3::prelude::*;
+use zenoh::prelude::ZenohId;
+
+use crate::ToPyErr;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Counter {
+    Puts,
+    Deletes,
+    QueriesSent,
+    SamplesReceived,
+    QueriesReceived,
+}
+const COUNTERS: [(&str, &str); 5] = [
+    ("puts_total", "Number of values published."),
+    ("deletes_total", "Number of deletions published."),
+    ("queries_sent_total", "Number of queries sent."),
+    (
+        "samples_received_total",
+        "Number of samples received by subscribers.",
+    ),
+    (
+        "queries_received_total",
+        "Number of queries received by queryables.",
+    ),
+];
+
+#[derive(Clone, Copy)]
+pub(crate) enum Entity {
+    Publisher,
+    Subscriber,
+    Queryable,
+}
+const ENTITIES: [(&str, &str); 3] = [
+    ("publishers", "Number of currently declared publishers."),
+    ("subscribers", "Number of currently declared subscribers."),
+    ("queryables", "Number of currently declared queryables."),
+];
+
+#[derive(Clone, Copy)]
+pub(crate) enum Flow {
+    Sent,
+    Received,
+}
+pub(crate) const TRAFFIC: [&str; 4] = [
+    "messages_sent",
+    "bytes_sent",
+    "messages_received",
+    "bytes_received",
+];
+/// Beyond this many prefixes, traffic is attributed to [`OTHER_PREFIXES`], so that stats can't exhaust memory.
+const MAX_PREFIXES: usize = 1024;
+const OTHER_PREFIXES: &str = "<other>";
+
+/// The traffic of the key expressions sharing a prefix, counted as in [`TRAFFIC`].
+#[derive(Default)]
+struct Traffic([AtomicU64; TRAFFIC.len()]);
+
+/// Returns the first `depth` chunks of `key_expr`.
+fn prefix(key_expr: &str, depth: usize) -> &str {
+    match key_expr.match_indices('/').nth(depth - 1) {
+        Some((end, _)) => &key_expr[..end],
+        None => key_expr,
+    }
+}
+
+pub(crate) struct SessionStats {
+    start: Instant,
+    counters: [AtomicU64; COUNTERS.len()],
+    entities: [AtomicUsize; ENTITIES.len()],
+    /// How many chunks of key expressions traffic is attributed to, 0 disabling the attribution.
+    key_depth: AtomicUsize,
+    traffic: RwLock<HashMap<String, Arc<Traffic>>>,
+}
+impl SessionStats {
+    pub(crate) fn new() -> Self {
+        SessionStats {
+            start: Instant::now(),
+            counters: Default::default(),
+            entities: Default::default(),
+            key_depth: AtomicUsize::new(1),
+            traffic: Default::default(),
+        }
+    }
+    pub(crate) fn count(&self, counter: Counter) {
+        self.counters[counter as usize].fetch_add(1, Ordering::Relaxed);
+    }
+    /// Attributes a message of `size` bytes to the prefix of `key_expr`.
+    pub(crate) fn traffic(&self, flow: Flow, key_expr: &str, size: usize) {
+        let depth = self.key_depth.load(Ordering::Relaxed);
+        if depth == 0 {
+            return;
+        }
+        let prefix = prefix(key_expr, depth);
+        let known = self.traffic.read().unwrap().get(prefix).cloned();
+        let traffic = match known {
+            Some(traffic) => traffic,
+            None => {
+                let mut prefixes = self.traffic.write().unwrap();
+                let prefix = if prefixes.len() < MAX_PREFIXES || prefixes.contains_key(prefix) {
+                    prefix
+                } else {
+                    OTHER_PREFIXES
+                };
+                prefixes.entry(prefix.to_owned()).or_default().clone()
+            }
+        };
+        let (messages, bytes) = match flow {
+            Flow::Sent => (0, 1),
+            Flow::Received => (2, 3),
+        };
+        traffic.0[messages].fetch_add(1, Ordering::Relaxed);
+        traffic.0[bytes].fetch_add(size as u64, Ordering::Relaxed);
+    }
+    /// Sets how many chunks of key expressions traffic is attributed to, resetting the traffic counted so far.
+    pub(crate) fn set_key_depth(&self, depth: usize) {
+        let mut prefixes = self.traffic.write().unwrap();
+        self.key_depth.store(depth, Ordering::Relaxed);
+        prefixes.clear();
+    }
+    pub(crate) fn key_depth(&self) -> usize {
+        self.key_depth.load(Ordering::Relaxed)
+    }
+    /// The counters and entity counts, by name.
+    pub(crate) fn snapshot(&self) -> Vec<(&'static str, u64)> {
+        let counters = COUNTERS
+            .iter()
+            .zip(&self.counters)
+            .map(|((name, _), value)| (*name, value.load(Ordering::Relaxed)));
+        let entities = ENTITIES
+            .iter()
+            .zip(&self.entities)
+            .map(|((name, _), value)| (*name, value.load(Ordering::Relaxed) as u64));
+        counters.chain(entities).collect()
+    }
+    /// The traffic counted for each prefix, in the order of [`TRAFFIC`].
+    pub(crate) fn traffic_by_key(&self) -> Vec<(String, [u64; TRAFFIC.len()])> {
+        self.traffic
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(prefix, traffic)| {
+                let mut counts = [0; TRAFFIC.len()];
+                for (count, value) in counts.iter_mut().zip(&traffic.0) {
+                    *count = value.load(Ordering::Relaxed);
+                }
+                (prefix.clone(), counts)
+            })
+            .collect()
+    }
+    /// Counts an entity as declared for as long as the returned guard lives.
+    pub(crate) fn declare(self: &Arc<Self>, entity: Entity) -> Declared {
+        self.entities[entity as usize].fetch_add(1, Ordering::Relaxed);
+        Declared {
+            stats: self.clone(),
+            entity,
+        }
+    }
+    pub(crate) fn uptime(&self) -> Duration {
+        self.start.elapsed()
+    }
+    /// Renders the statistics as a health report, as served by `_Session::enable_healthcheck`.
+    pub(crate) fn to_health_json(&self, zid: &ZenohId) -> String {
+        let counters = COUNTERS
+            .iter()
+            .zip(&self.counters)
+            .map(|((name, _), value)| {
+                (
+                    name.to_string(),
+                    serde_json::Value::from(value.load(Ordering::Relaxed)),
+                )
+            });
+        let entities = ENTITIES
+            .iter()
+            .zip(&self.entities)
+            .map(|((name, _), value)| {
+                (
+                    name.to_string(),
+                    serde_json::Value::from(value.load(Ordering::Relaxed)),
+                )
+            });
+        serde_json::json!({
+            "status": "alive",
+            "zid": zid.to_string(),
+            "uptime": self.uptime().as_secs_f64(),
+            "stats": counters.chain(entities).collect::<serde_json::Map<_, _>>(),
+        })
+        .to_string()
+    }
+    /// Renders the statistics in the Prometheus text exposition format.
+    pub(crate) fn to_prometheus(&self, zid: &ZenohId) -> String {
+        let mut text = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: &dyn std::fmt::Display| {
+            let _ = write!(
+                text,
+                "# HELP zenoh_python_{name} {help}\n# TYPE zenoh_python_{name} {kind}\nzenoh_python_{name}{{zid=\"{zid}\"}} {value}\n"
+            );
+        };
+        metric(
+            "uptime_seconds",
+            "gauge",
+            "Time since the session was opened.",
+            &self.uptime().as_secs_f64(),
+        );
+        for ((name, help), value) in COUNTERS.iter().zip(&self.counters) {
+            metric(name, "counter", help, &value.load(Ordering::Relaxed));
+        }
+        for ((name, help), value) in ENTITIES.iter().zip(&self.entities) {
+            metric(name, "gauge", help, &value.load(Ordering::Relaxed));
+        }
+        text
+    }
+}
+
+/// Keeps an entity counted in its session's statistics until dropped.
+pub(crate) struct Declared {
+    stats: Arc<SessionStats>,
+    entity: Entity,
+}
+impl Declared {
+    pub(crate) fn stats(&self) -> &SessionStats {
+        &self.stats
+    }
+}
+impl Clone for Declared {
+    fn clone(&self) -> Self {
+        self.stats.declare(self.entity)
+    }
+}
+impl Drop for Declared {
+    fn drop(&mut self) {
+        self.stats.entities[self.entity as usize].fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A minimal HTTP server answering every request with the session's statistics.
+#[pyclass(subclass)]
+pub struct _MetricsExporter {
+    addr: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    task: Option<std::thread::JoinHandle<()>>,
+}
+#[pymethods]
+impl _MetricsExporter {
+    /// The port the exporter listens on.
+    #[getter]
+    fn port(&self) -> u16 {
+        self.addr.port()
+    }
+    /// Stops the exporter and waits for its thread to exit.
+    fn stop(&mut self, py: Python) {
+        if let Some(task) = self.task.take() {
+            self.stopped.store(true, Ordering::Relaxed);
+            self.wake();
+            let _ = py.allow_threads(|| task.join());
+        }
+    }
+}
+impl _MetricsExporter {
+    pub(crate) fn spawn(
+        zid: ZenohId,
+        stats: Arc<SessionStats>,
+        address: &str,
+        port: u16,
+    ) -> PyResult<Self> {
+        let listener = TcpListener::bind((address, port)).map_err(|e| e.to_pyerr())?;
+        let addr = listener.local_addr().map_err(|e| e.to_pyerr())?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stop = stopped.clone();
+        let task = std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                if let Ok(mut stream) = stream {
+                    // The request itself doesn't matter: every path serves the metrics.
+                    let _ = stream.set_read_timeout(Some(Duration::from_secs(1)));
+                    let _ = stream.read(&mut [0; 1024]);
+                    let body = stats.to_prometheus(&zid);
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                }
+            }
+        });
+        Ok(_MetricsExporter {
+            addr,
+            stopped,
+            task: Some(task),
+        })
+    }
+    /// Connects to the listener so that it notices it was stopped.
+    fn wake(&self) {
+        let mut addr = self.addr;
+        // listening on all interfaces includes the loopback one
+        if addr.ip().is_unspecified() {
+            addr.set_ip(match addr {
+                SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+            });
+        }
+        let _ = TcpStream::connect(addr);
+    }
+}
+impl Drop for _MetricsExporter {
+    fn drop(&mut self) {
+        if self.task.is_some() {
+            self.stopped.store(true, Ordering::Relaxed);
+            self.wake();
+        }
+    }
+}
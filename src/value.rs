@@ -10,11 +10,16 @@
 // Contributors:
 //   ZettaScale Zenoh team, <zenoh@zettascale.tech>
 
-use pyo3::{prelude::*, types::PyBytes};
+use base64::Engine;
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyDict, PyFloat, PyList, PyLong, PyString, PyType},
+};
 use uhlc::Timestamp;
 use zenoh::{
-    prelude::{Encoding, KeyExpr, Sample, Value, ZenohId},
+    prelude::{Encoding, KeyExpr, Sample, SampleKind, Value, ZenohId},
     query::Reply,
+    sample::Attachment,
     scouting::Hello,
 };
 use zenoh_buffers::{
@@ -23,7 +28,8 @@ use zenoh_buffers::{
 };
 
 use crate::{
-    enums::{_Encoding, _SampleKind},
+    dlpack,
+    enums::{_ChangeKind, _Encoding, _SampleKind},
     keyexpr::_KeyExpr,
     ToPyErr,
 };
@@ -61,6 +67,52 @@ impl Payload {
             Payload::Python(buf) => buf,
         }
     }
+    /// The number of pieces the payload was received in: zenoh reassembles the fragments of large samples
+    /// without copying them, keeping one slice per fragment.
+    pub(crate) fn fragments(&self) -> usize {
+        match self {
+            Payload::Zenoh(buf) => buf.slices().count().max(1),
+            Payload::Python(_) => 1,
+        }
+    }
+    pub(crate) fn with_bytes<R>(&self, py: Python<'_>, f: impl FnOnce(&[u8]) -> R) -> R {
+        match self {
+            Payload::Zenoh(buf) => f(buf.contiguous().as_ref()),
+            Payload::Python(buf) => f(buf.as_bytes(py)),
+        }
+    }
+    pub(crate) fn deserialize(&self, t: &PyType, py: Python<'_>) -> PyResult<PyObject> {
+        let supported = [
+            py.get_type::<PyString>(),
+            py.get_type::<PyLong>(),
+            py.get_type::<PyFloat>(),
+            py.get_type::<PyDict>(),
+            py.get_type::<PyList>(),
+        ];
+        // unsupported types are reported as such, whatever the payload holds
+        if !supported.iter().any(|supported| t.is(*supported)) {
+            return Err(
+                zenoh_core::zerror!("Can't deserialize a payload into {}", t.name()?).to_pyerr(),
+            );
+        }
+        let string = self.with_bytes(py, |bytes| match std::str::from_utf8(bytes) {
+            Ok(s) => Ok(PyString::new(py, s)),
+            Err(e) => Err(e.to_pyerr()),
+        })?;
+        if t.is(py.get_type::<PyString>()) {
+            Ok(string.into_py(py))
+        } else if t.is(py.get_type::<PyLong>()) || t.is(py.get_type::<PyFloat>()) {
+            Ok(t.call1((string,))?.into_py(py))
+        } else {
+            // a dict or a list
+            let value = py.import("json")?.call_method1("loads", (string,))?;
+            if value.is_instance(t)? {
+                Ok(value.into_py(py))
+            } else {
+                Err(zenoh_core::zerror!("Payload isn't a JSON {}", t.name()?).to_pyerr())
+            }
+        }
+    }
 }
 impl From<ZBuf> for Payload {
     fn from(buf: ZBuf) -> Self {
@@ -130,6 +182,29 @@ impl _Value {
     pub fn with_encoding(&mut self, encoding: _Encoding) {
         self.encoding = encoding.0;
     }
+    pub fn deserialize(&self, t: &PyType, py: Python<'_>) -> PyResult<PyObject> {
+        self.payload.deserialize(t, py)
+    }
+    /// Copies the bytes of a host tensor, recording its data type and shape in the encoding.
+    #[staticmethod]
+    pub fn from_dlpack(tensor: &PyAny) -> PyResult<Self> {
+        let py = tensor.py();
+        dlpack::with_tensor(tensor, |bytes, encoding| {
+            let payload = py.allow_threads(|| ZBuf::from(bytes.to_vec()));
+            Ok(_Value {
+                payload: Payload::Zenoh(payload),
+                encoding,
+            })
+        })
+    }
+    #[pyo3(signature = (stream = None))]
+    pub fn __dlpack__(&self, py: Python, stream: Option<PyObject>) -> PyResult<PyObject> {
+        let _ = stream;
+        dlpack::export(py, &self.payload, &self.encoding)
+    }
+    pub fn __dlpack_device__(&self) -> (i32, i32) {
+        dlpack::HOST
+    }
     pub fn __str__(&self) -> String {
         format!("{self:?}")
     }
@@ -166,6 +241,8 @@ pub struct _Sample {
     value: _Value,
     kind: _SampleKind,
     timestamp: Option<_Timestamp>,
+    source_id: Option<ZenohId>,
+    attachment: Option<Attachment>,
 }
 impl From<Sample> for _Sample {
     fn from(sample: Sample) -> Self {
@@ -174,6 +251,8 @@ impl From<Sample> for _Sample {
             value,
             kind,
             timestamp,
+            source_info,
+            attachment,
             ..
         } = sample;
         _Sample {
@@ -181,6 +260,8 @@ impl From<Sample> for _Sample {
             value: value.into(),
             kind: _SampleKind(kind),
             timestamp: timestamp.map(_Timestamp),
+            source_id: source_info.source_id,
+            attachment,
         }
     }
 }
@@ -231,6 +312,15 @@ impl _Timestamp {
     pub fn seconds_since_unix_epoch(&self) -> f64 {
         self.0.get_time().as_secs_f64()
     }
+    fn __hash__(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.0.hash(&mut hasher);
+        hasher.finish()
+    }
+    pub fn __str__(&self) -> String {
+        self.0.to_string()
+    }
 }
 impl core::fmt::Debug for _Timestamp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -266,6 +356,9 @@ impl _Sample {
     pub fn encoding(&self) -> _Encoding {
         _Encoding(self.value.encoding.clone())
     }
+    pub fn deserialize(&self, t: &PyType, py: Python<'_>) -> PyResult<PyObject> {
+        self.value.payload.deserialize(t, py)
+    }
     #[getter]
     pub fn kind(&self) -> _SampleKind {
         self.kind.clone()
@@ -274,6 +367,26 @@ impl _Sample {
     pub fn timestamp(&self) -> Option<_Timestamp> {
         self.timestamp
     }
+    #[getter]
+    pub fn source_id(&self) -> Option<_ZenohId> {
+        self.source_id.map(_ZenohId)
+    }
+    /// The sample's attachment, as a `dict` of `bytes`, empty if it has none.
+    #[getter]
+    pub fn attachment(&self, py: Python) -> PyResult<Py<PyDict>> {
+        crate::interceptor::attachment_to_dict(py, self.attachment.as_ref())
+    }
+    #[getter]
+    pub fn fragments(&self) -> usize {
+        self.value.payload.fragments()
+    }
+    #[pyo3(signature = (stream = None))]
+    pub fn __dlpack__(&self, py: Python, stream: Option<PyObject>) -> PyResult<PyObject> {
+        self.value.__dlpack__(py, stream)
+    }
+    pub fn __dlpack_device__(&self) -> (i32, i32) {
+        dlpack::HOST
+    }
     #[staticmethod]
     pub fn new(
         key_expr: _KeyExpr,
@@ -286,13 +399,134 @@ impl _Sample {
             value,
             kind,
             timestamp,
+            source_id: None,
+            attachment: None,
         }
     }
+    /// Serializes the sample into a JSON object, its payload being base64-encoded.
+    pub fn to_json(&self, py: Python<'_>) -> String {
+        let payload = self.value.payload.with_bytes(py, |bytes| {
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        });
+        serde_json::json!({
+            "key_expr": self.key_expr.as_str(),
+            "payload": payload,
+            "encoding": self.value.encoding.to_string(),
+            "kind": self.kind.__str__(),
+            "timestamp": self.timestamp.map(|t| t.0.to_string()),
+        })
+        .to_string()
+    }
+    #[staticmethod]
+    pub fn from_json(json: &str) -> PyResult<Self> {
+        let json: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_pyerr())?;
+        let field = |name: &str| match json.get(name) {
+            Some(serde_json::Value::String(s)) => Ok(Some(s.as_str())),
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(_) => {
+                Err(zenoh_core::zerror!("Sample field `{}` must be a string", name).to_pyerr())
+            }
+        };
+        let key_expr = match field("key_expr")? {
+            Some(key_expr) => KeyExpr::try_from(key_expr.to_owned()).map_err(|e| e.to_pyerr())?,
+            None => return Err(zenoh_core::zerror!("Sample is missing its `key_expr`").to_pyerr()),
+        };
+        let payload = match field("payload")? {
+            Some(payload) => base64::engine::general_purpose::STANDARD
+                .decode(payload)
+                .map_err(|e| e.to_pyerr())?,
+            None => Vec::new(),
+        };
+        let encoding = match field("encoding")? {
+            Some(encoding) => Encoding::from(encoding.to_owned()),
+            None => Encoding::EMPTY,
+        };
+        let kind = match field("kind")? {
+            None | Some("PUT") => SampleKind::Put,
+            Some("DELETE") => SampleKind::Delete,
+            Some(kind) => {
+                return Err(zenoh_core::zerror!("Unknown sample kind `{}`", kind).to_pyerr())
+            }
+        };
+        let timestamp = match field("timestamp")? {
+            Some(timestamp) => Some(_Timestamp(timestamp.parse().map_err(|e| {
+                zenoh_core::zerror!("Invalid timestamp `{}`: {:?}", timestamp, e).to_pyerr()
+            })?)),
+            None => None,
+        };
+        Ok(_Sample {
+            key_expr,
+            value: _Value {
+                payload: Payload::Zenoh(ZBuf::from(payload)),
+                encoding,
+            },
+            kind: _SampleKind(kind),
+            timestamp,
+            source_id: None,
+            attachment: None,
+        })
+    }
     fn __str__(&self) -> String {
         format!("{self:?}")
     }
 }
 
+/// A sample, seen as a change to the value of its key expression.
+#[pyclass(subclass)]
+#[derive(Clone, Debug)]
+pub struct _Change {
+    key_expr: KeyExpr<'static>,
+    value: Option<_Value>,
+    kind: _ChangeKind,
+    timestamp: Option<_Timestamp>,
+}
+#[pymethods]
+impl _Change {
+    #[new]
+    pub fn pynew(this: Self) -> Self {
+        this
+    }
+    #[getter]
+    pub fn path(&self) -> _KeyExpr {
+        _KeyExpr(self.key_expr.clone())
+    }
+    #[getter]
+    pub fn value(&self) -> Option<_Value> {
+        self.value.clone()
+    }
+    #[getter]
+    pub fn kind(&self) -> _ChangeKind {
+        self.kind.clone()
+    }
+    #[getter]
+    pub fn timestamp(&self) -> Option<_Timestamp> {
+        self.timestamp
+    }
+    fn __str__(&self) -> String {
+        format!("{self:?}")
+    }
+}
+impl From<Sample> for _Change {
+    fn from(sample: Sample) -> Self {
+        let Sample {
+            key_expr,
+            value,
+            kind,
+            timestamp,
+            ..
+        } = sample;
+        _Change {
+            key_expr,
+            value: match kind {
+                SampleKind::Delete => None,
+                _ => Some(value.into()),
+            },
+            kind: _ChangeKind(kind.into()),
+            timestamp: timestamp.map(_Timestamp),
+        }
+    }
+}
+
 impl From<_Sample> for Sample {
     fn from(sample: _Sample) -> Self {
         let _Sample {
@@ -300,10 +534,13 @@ impl From<_Sample> for Sample {
             value,
             kind,
             timestamp,
+            attachment,
+            ..
         } = sample;
         let mut sample = Sample::new(key_expr, value);
         sample.kind = kind.0;
         sample.timestamp = timestamp.map(|t| t.0);
+        sample.attachment = attachment;
         sample
     }
 }
@@ -380,6 +617,20 @@ impl _Hello {
     pub fn locators(&self) -> Vec<String> {
         self.0.locators.iter().map(|l| l.to_string()).collect()
     }
+    /// The locators split into their protocol and address, without their metadata.
+    #[getter]
+    pub fn parsed_locators(&self) -> Vec<(String, String)> {
+        self.0
+            .locators
+            .iter()
+            .map(|l| {
+                (
+                    l.protocol().as_str().to_owned(),
+                    l.address().as_str().to_owned(),
+                )
+            })
+            .collect()
+    }
     pub fn __str__(&self) -> String {
         self.0.to_string()
     }
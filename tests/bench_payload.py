@@ -0,0 +1,75 @@
+# Copyright (c) 2017, 2022 ZettaScale Technology Inc.
+
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+
+# Contributors:
+#   ZettaScale Zenoh team, <zenoh@zettascale.tech>
+#
+# Measures the cost of converting received payloads into Python `bytes`.
+# Run it against two builds of zenoh-python to compare them:
+#   $ python3 tests/bench_payload.py
+import json
+import time
+from threading import Event
+import zenoh
+
+MSG_COUNT = 10_000
+MSG_SIZE = [8, 1_024, 65_536, 1_048_576]
+ENDPOINT = "tcp/127.0.0.1:17448"
+
+
+def open_sessions():
+    conf = zenoh.Config()
+    conf.insert_json5("listen/endpoints", json.dumps([ENDPOINT]))
+    conf.insert_json5("scouting/multicast/enabled", "false")
+    sub_session = zenoh.open(conf)
+    conf = zenoh.Config()
+    conf.insert_json5("connect/endpoints", json.dumps([ENDPOINT]))
+    conf.insert_json5("scouting/multicast/enabled", "false")
+    pub_session = zenoh.open(conf)
+    return sub_session, pub_session
+
+
+def bench(sub_session, pub_session, size: int):
+    count = 0
+    elapsed = 0.0
+    done = Event()
+
+    def listener(sample):
+        nonlocal count, elapsed
+        start = time.perf_counter()
+        sample.payload
+        elapsed += time.perf_counter() - start
+        count += 1
+        if count == MSG_COUNT:
+            done.set()
+
+    sub = sub_session.declare_subscriber("bench/payload", zenoh.Closure(listener), reliability=zenoh.Reliability.RELIABLE())
+    time.sleep(1)
+    pub = pub_session.declare_publisher("bench/payload", congestion_control=zenoh.CongestionControl.BLOCK())
+    payload = bytes(size)
+    start = time.perf_counter()
+    for _ in range(MSG_COUNT):
+        pub.put(payload)
+    done.wait(timeout=60)
+    total = time.perf_counter() - start
+    print(f"{size:>9} B: {count / total:>10.1f} msgs/s, {1e6 * elapsed / max(count, 1):>8.2f} us/payload conversion")
+    pub.undeclare()
+    sub.undeclare()
+
+
+def main():
+    sub_session, pub_session = open_sessions()
+    for size in MSG_SIZE:
+        bench(sub_session, pub_session, size)
+    sub_session.close()
+    pub_session.close()
+
+
+if __name__ == "__main__":
+    main()
@@ -1,8 +1,22 @@
 import zenoh
+import zenoh.crypto
+import zenoh.testing
+import zenoh.replies
+import zenoh.offline
+import zenoh.filetransfer
 import json
 from zenoh import Session, Query, Sample
 from typing import List, Tuple
 import time
+import os
+import ssl
+import sys
+import tempfile
+import pytest
+import zenoh.testing
+import zenoh.replies
+import zenoh.filetransfer
+import threading
 
 SLEEP = 1
 MSG_COUNT = 1_000;
@@ -86,3 +100,786 @@ def test_session():
     (peer01, peer02) = open_session(["tcp/127.0.0.1:17447"])
     run_session_qryrep(peer01, peer02)
     close_session(peer01, peer02)
+
+
+def test_config_dict():
+    conf = zenoh.Config()
+    conf.insert_json5("mode", json.dumps("client"))
+    conf.insert_json5("connect/endpoints", json.dumps(["tcp/127.0.0.1:17447"]))
+
+    print("[CD][01c] Round-tripping a configuration through a dict");
+    d = conf.to_dict()
+    assert d["mode"] == "client"
+    assert d["connect"]["endpoints"] == ["tcp/127.0.0.1:17447"]
+    assert zenoh.Config.from_dict(d).to_dict() == d
+    with pytest.raises(TypeError):
+        zenoh.Config.from_dict(json.dumps(d))
+
+
+def test_tls_from_ssl_context():
+    print("[TL][01c] Mapping the verify mode of client and server contexts");
+    client = ssl.create_default_context()
+    tls = zenoh.TlsConfig.from_ssl_context(client, cafile="ca.pem")
+    assert (tls.cafile, tls.check_hostname, tls.client_auth) == ("ca.pem", True, False)
+    server = ssl.SSLContext(ssl.PROTOCOL_TLS_SERVER)
+    server.verify_mode = ssl.CERT_REQUIRED
+    assert zenoh.TlsConfig.from_ssl_context(server, certfile="cert.pem", keyfile="key.pem").client_auth
+    server.verify_mode = ssl.CERT_NONE
+    assert not zenoh.TlsConfig.from_ssl_context(server, certfile="cert.pem", keyfile="key.pem").client_auth
+    conf = zenoh.Config()
+    conf.set_tls(tls)
+    assert json.loads(conf.get_json("transport/link/tls/root_ca_certificate")) == "ca.pem"
+
+    print("[TL][02c] Refusing the settings zenoh can't honour");
+    server.verify_mode = ssl.CERT_OPTIONAL
+    client.check_hostname = False
+    client.verify_mode = ssl.CERT_NONE
+    for context in [server, client, ssl.SSLContext(ssl.PROTOCOL_TLS)]:
+        with pytest.raises(ValueError):
+            zenoh.TlsConfig.from_ssl_context(context)
+
+
+def test_active_endpoint():
+    zenoh.init_logger()
+    conf = zenoh.Config()
+    conf.insert_json5("listen/endpoints", json.dumps(["tcp/127.0.0.1:17451"]))
+    conf.insert_json5("scouting/multicast/enabled", "false")
+    peer01 = zenoh.open(conf)
+    conf = zenoh.Config()
+    conf.set_connect_endpoints(["tcp/127.0.0.1:17452", ("tcp/127.0.0.1:17451", zenoh.RetryPolicy(period_max_ms=1000))])
+    conf.insert_json5("scouting/multicast/enabled", "false")
+    peer02 = zenoh.open(conf)
+    time.sleep(SLEEP)
+
+    print("[AE][01c] Reporting the endpoint the session is connected through");
+    assert peer02.info().active_endpoint() == "tcp/127.0.0.1:17451"
+    assert peer01.info().active_endpoint() is None
+
+    close_session(peer01, peer02)
+
+
+def test_endpoint_re_resolution():
+    zenoh.init_logger()
+    locators = ["tcp/127.0.0.1:17453"]
+    zenoh.register_endpoint_resolver("test-resolve", lambda service: locators)
+
+    def listen(locator: str) -> Session:
+        conf = zenoh.Config()
+        conf.insert_json5("listen/endpoints", json.dumps([locator]))
+        conf.insert_json5("scouting/multicast/enabled", "false")
+        return zenoh.open(conf)
+    peer01 = listen("tcp/127.0.0.1:17453")
+    conf = zenoh.Config()
+    conf.set_connect_endpoints(["test-resolve/zenoh"])
+    conf.insert_json5("scouting/multicast/enabled", "false")
+    peer02 = zenoh.open(conf)
+    time.sleep(SLEEP)
+    assert [str(zid) for zid in peer02.info().peers_zid()] == [str(peer01.zid())]
+
+    print("[RR][01c] Resolving the endpoints anew once the session lost its transports");
+    locators = ["tcp/127.0.0.1:17454"]
+    peer03 = listen("tcp/127.0.0.1:17454")
+    peer01.close()
+    time.sleep(2 * SLEEP)
+    assert [str(zid) for zid in peer02.info().peers_zid()] == [str(peer03.zid())]
+
+    zenoh.register_endpoint_resolver("test-resolve", None)
+    close_session(peer02, peer03)
+
+
+@pytest.mark.skipif(sys.platform == "win32", reason="unix sockets are not available on Windows")
+def test_session_unixsock():
+    zenoh.init_logger()
+    path = os.path.join(tempfile.mkdtemp(), "zenoh-test.sock")
+    (peer01, peer02) = open_session([f"unixsock-stream/{path}"])
+    run_session_qryrep(peer01, peer02)
+    close_session(peer01, peer02)
+
+
+def test_usrpwd_dictionary():
+    conf = zenoh.Config()
+    conf.insert_json5("scouting/multicast/enabled", "false")
+
+    print("[UP][01c] Writing the dictionary to a private file");
+    conf.set_usrpwd_auth(dictionary={"alice": "secret"})
+    path = json.loads(conf.get_json("transport/auth/usrpwd/dictionary_file"))
+    with open(path) as f:
+        assert f.read() == "alice:secret\n"
+    if sys.platform != "win32":
+        assert os.stat(path).st_mode & 0o777 == 0o600
+
+    print("[UP][02c] Removing it once the session is closed");
+    session = zenoh.open(conf)
+    assert os.path.exists(path)
+    session.close()
+    assert not os.path.exists(path)
+
+
+def open_local_session() -> Session:
+    conf = zenoh.Config()
+    conf.insert_json5("scouting/multicast/enabled", "false")
+    return zenoh.open(conf)
+
+
+def test_queryable_generator():
+    zenoh.init_logger()
+    session = open_local_session()
+    def replies(query):
+        for i in range(3):
+            yield Sample(f"test/generator/{i}", i)
+
+    print("[QG][01c] Replying with a generator");
+    queryable = session.declare_queryable("test/generator/**", replies)
+    time.sleep(SLEEP)
+    replied = session.get("test/generator/**", zenoh.ListCollector(), zenoh.QueryConsolidation.NONE())()
+    assert sorted((str(reply.ok.key_expr), reply.ok.payload) for reply in replied) == [
+        ("test/generator/0", b"0"),
+        ("test/generator/1", b"1"),
+        ("test/generator/2", b"2"),
+    ]
+
+    print("[QG][02c] Replying with a single value");
+    queryable.undeclare()
+    queryable = session.declare_queryable("test/generator/single", lambda query: "single")
+    time.sleep(SLEEP)
+    replied = session.get("test/generator/single", zenoh.ListCollector())()
+    assert [reply.ok.payload for reply in replied] == [b"single"]
+
+    print("[QG][03c] Replying with a list, returned as a single value");
+    queryable.undeclare()
+    queryable = session.declare_queryable("test/generator/list", lambda query: [1, 2])
+    time.sleep(SLEEP)
+    replied = session.get("test/generator/list", zenoh.ListCollector())()
+    assert [json.loads(reply.ok.payload) for reply in replied] == [[1, 2]]
+
+    queryable.undeclare()
+    session.close()
+
+
+def test_register_eval():
+    zenoh.init_logger()
+    session = open_local_session()
+    def greet(params):
+        if "name" not in params:
+            raise KeyError("name")
+        return f"Hello {params['name']}!"
+
+    print("[EV][01c] Replying with the function's result");
+    queryable = session.register_eval("test/eval", greet)
+    time.sleep(SLEEP)
+    replied = session.get("test/eval?name=zenoh", zenoh.ListCollector())()
+    assert [reply.ok.payload for reply in replied] == [b"Hello zenoh!"]
+
+    print("[EV][02c] Replying with the exception it raised");
+    replied = session.get("test/eval", zenoh.ListCollector())()
+    assert [reply.err.payload for reply in replied] == [b"'name'"]
+
+    queryable.undeclare()
+    session.close()
+
+
+def test_session_local_subscriber():
+    zenoh.init_logger()
+    (peer01, peer02) = open_session(["tcp/127.0.0.1:17448"])
+    keyexpr = "test/session/local"
+    received = []
+
+    print("[LS][01c] Session-local subscriber on peer01 session");
+    sub = peer01.declare_subscriber(keyexpr, lambda sample: received.append(sample.payload), allowed_origin=zenoh.Locality.SESSION_LOCAL())
+    time.sleep(SLEEP)
+
+    print("[LS][02c] Putting from both sessions");
+    peer02.put(keyexpr, b"remote")
+    peer01.put(keyexpr, b"local")
+    time.sleep(SLEEP)
+
+    assert received == [b"local"]
+    sub.undeclare()
+    close_session(peer01, peer02)
+
+
+def test_sample_source_id():
+    zenoh.init_logger()
+    (peer01, peer02) = open_session(["tcp/127.0.0.1:17450"])
+    keyexpr = "test/session/source"
+    received = []
+    sub = peer02.declare_subscriber(keyexpr, lambda sample: received.append(sample.source_id))
+    time.sleep(SLEEP)
+
+    print("[SI][01c] Reading the publishing session's id from the samples");
+    peer01.put(keyexpr, b"put")
+    pub = peer01.declare_publisher(keyexpr)
+    pub.put(b"publisher")
+    time.sleep(SLEEP)
+    assert [str(zid) for zid in received] == [str(peer01.zid())] * 2
+
+    pub.undeclare()
+    sub.undeclare()
+    close_session(peer01, peer02)
+
+
+def test_integrity():
+    session = open_local_session()
+    checked = zenoh.integrity.ChecksumSession(session, algorithm="sha256")
+    keyexpr = "test/session/integrity"
+    plain = []
+    verified = []
+    errors = []
+    sub = session.declare_subscriber(keyexpr, lambda sample: plain.append(sample))
+    checked_sub = checked.declare_subscriber(keyexpr, lambda sample: verified.append(sample.payload))
+    checked.on_mismatch = errors.append
+    time.sleep(SLEEP)
+
+    print("[IN][01c] Carrying checksums in attachments, leaving payloads untouched");
+    checked.put(keyexpr, b"firmware")
+    time.sleep(SLEEP)
+    assert [sample.payload for sample in plain] == [b"firmware"]
+    assert plain[0].attachment[zenoh.integrity.CHECKSUM_ALGORITHM] == b"sha256"
+    assert verified == [b"firmware"]
+
+    print("[IN][02c] Dropping samples that don't match their checksum");
+    session.put(keyexpr, b"corrupted", attachment=zenoh.integrity.checksum(b"firmware"))
+    session.put(keyexpr, b"unchecked")
+    time.sleep(SLEEP)
+    assert verified == [b"firmware"]
+    assert len(errors) == 2
+
+    checked_sub.undeclare()
+    sub.undeclare()
+    session.close()
+
+
+def test_subscriber_options():
+    session = open_local_session()
+
+    print("[SO][01c] Combining handler options that exclude each other");
+    for options in [dict(reuse_samples=True, changes=True), dict(changes=True, batch=(4, 0.1)), dict(reuse_samples=True, batch=(4, 0.1))]:
+        with pytest.raises(ValueError):
+            session.declare_subscriber("test/options", lambda sample: None, **options)
+    with pytest.raises(ValueError):
+        session.declare_subscriber("test/options", sink="count", changes=True)
+    session.close()
+
+
+def test_sample_json():
+    sample = Sample("test/json", b"\x00\x01binary\xff", zenoh.SampleKind.PUT())
+
+    print("[SJ][01c] Round-tripping a sample through JSON");
+    json_sample = sample.to_json()
+    assert json.loads(json_sample)["timestamp"] is None
+    parsed = Sample.from_json(json_sample)
+    assert parsed.key_expr == sample.key_expr
+    assert parsed.payload == sample.payload
+    assert str(parsed.encoding) == str(sample.encoding)
+    assert parsed.kind == sample.kind
+
+    print("[SJ][02c] Parsing a minimal sample");
+    parsed = Sample.from_json(json.dumps({"key_expr": "test/json", "kind": "DELETE"}))
+    assert parsed.payload == b""
+    assert parsed.kind == zenoh.SampleKind.DELETE()
+    with pytest.raises(zenoh.ZError):
+        Sample.from_json(json.dumps({"payload": ""}))
+
+
+def test_record_replay():
+    zenoh.init_logger()
+    session = open_local_session()
+    path = os.path.join(tempfile.mkdtemp(), "capture.zrec")
+    time.sleep(SLEEP)
+
+    print("[RR][01c] Recording samples");
+    recorder = zenoh.record(session, "test/capture/**", path)
+    time.sleep(SLEEP)
+    session.put("test/capture/a", b"1")
+    session.put("test/capture/b", b"2")
+    session.delete("test/capture/a")
+    time.sleep(SLEEP)
+    recorder.stop()
+    assert recorder.count == 3
+
+    print("[RR][02c] Replaying them");
+    replayed = []
+    sub = session.declare_subscriber("test/capture/**", lambda sample: replayed.append((str(sample.key_expr), sample.kind, sample.payload)))
+    time.sleep(SLEEP)
+    assert zenoh.replay(session, path, speed=float("inf")) == 3
+    time.sleep(SLEEP)
+    assert replayed == [
+        ("test/capture/a", zenoh.SampleKind.PUT(), b"1"),
+        ("test/capture/b", zenoh.SampleKind.PUT(), b"2"),
+        ("test/capture/a", zenoh.SampleKind.DELETE(), b""),
+    ]
+    with pytest.raises(ValueError):
+        zenoh.replay(session, path, speed=0)
+
+    sub.undeclare()
+    session.close()
+
+
+def test_keyexpr_mqtt_topics():
+    print("[KC][01c] Mapping MQTT topics");
+    assert str(zenoh.mqtt_topic_to_keyexpr("/sensors/+/temp")) == "%/sensors/*/temp"
+    assert str(zenoh.mqtt_topic_to_keyexpr("sensors/#")) == "sensors/**"
+    for topic in ["/sensors/+/temp", "sensors/#", "a/50%/b", "a//b"]:
+        assert zenoh.keyexpr_to_mqtt_topic(zenoh.mqtt_topic_to_keyexpr(topic)) == topic
+    for topic in ["a/#/b", "a/b+"]:
+        with pytest.raises(ValueError):
+            zenoh.mqtt_topic_to_keyexpr(topic)
+    with pytest.raises(ValueError):
+        zenoh.keyexpr_to_mqtt_topic("a/**/b")
+
+
+def test_keyexpr_patterns():
+    print("[KP][01c] Converting globs and regexes");
+    assert str(zenoh.glob_to_keyexpr("logs/**/*.txt")) == "logs/**/$*.txt"
+    assert str(zenoh.regex_to_keyexpr(r"^sensors/[^/]*/temp\.c$")) == "sensors/*/temp.c"
+    assert str(zenoh.regex_to_keyexpr("logs/.*")) == "logs/**"
+    for glob in ["logs/?.txt", "logs/[ab].txt"]:
+        with pytest.raises(ValueError):
+            zenoh.glob_to_keyexpr(glob)
+    for regex in ["logs/(a|b)", "logs/a+"]:
+        with pytest.raises(ValueError):
+            zenoh.regex_to_keyexpr(regex)
+
+
+def test_session_registry():
+    print("[SG][01c] Registering a session by name");
+    session = zenoh.init(zenoh.Config.test(), name="test-registry")
+    assert zenoh.get_session("test-registry") is session
+    with pytest.raises(ValueError):
+        zenoh.init(zenoh.Config.test(), name="test-registry")
+
+    print("[SG][02c] Unregistering it once closed");
+    session.close()
+    with pytest.raises(KeyError):
+        zenoh.get_session("test-registry")
+
+
+def test_keyexpr_mappings():
+    zenoh.init_logger()
+    (peer01, peer02) = open_session(["tcp/127.0.0.1:17449"])
+    # the longest prefix wins: local ones for outgoing key expressions, remote ones for incoming ones
+    peer01.add_keyexpr_mapping("a/**", "net/long/prefix/**")
+    peer01.add_keyexpr_mapping("a/b/**", "net/**")
+    assert peer01.keyexpr_mappings() == [("a/b/**", "net/**"), ("a/**", "net/long/prefix/**")]
+    received01 = []
+    received02 = []
+
+    print("[KM][01c] Subscribing on peer01 session");
+    sub01 = peer01.declare_subscriber("**", lambda sample: received01.append(str(sample.key_expr)))
+    time.sleep(SLEEP)
+
+    print("[KM][02c] Putting from peer02 session");
+    peer02.put("net/long/prefix/c", b"1")
+    peer02.put("net/d", b"2")
+    time.sleep(SLEEP)
+    assert received01 == ["a/c", "a/b/d"]
+
+    print("[KM][03c] Subscribing on peer02 session");
+    sub02 = peer02.declare_subscriber("net/**", lambda sample: received02.append(str(sample.key_expr)))
+    time.sleep(SLEEP)
+
+    print("[KM][04c] Putting from peer01 session");
+    peer01.put("a/y", b"3")
+    peer01.put("a/b/x", b"4")
+    time.sleep(SLEEP)
+    assert received02 == ["net/long/prefix/y", "net/x"]
+
+    sub01.undeclare()
+    sub02.undeclare()
+    close_session(peer01, peer02)
+
+
+def test_sealing():
+    zenoh.init_logger()
+    session = open_local_session()
+    key = os.urandom(32)
+    errors = []
+    sealer = zenoh.crypto.SealingSession(session, lambda key_expr: key, lambda sample, e: errors.append(e))
+    sealed = []
+    unsealed = []
+    raw = session.declare_subscriber("test/sealed/**", lambda sample: sealed.append(sample.payload))
+    sub = sealer.declare_subscriber("test/sealed/**", lambda sample: unsealed.append(sample.payload))
+    time.sleep(SLEEP)
+
+    print("[SE][01c] Putting through the sealing session");
+    sealer.put("test/sealed/plans", b"attack at dawn")
+    time.sleep(SLEEP)
+    assert unsealed == [b"attack at dawn"]
+    assert len(sealed) == 1 and b"attack at dawn" not in sealed[0]
+
+    print("[SE][02c] Unsealing tampered ciphertexts");
+    value = sealer.seal("test/sealed/plans", b"attack at dawn")
+    assert sealer.unseal(Sample("test/sealed/plans", value)).payload == b"attack at dawn"
+    tampered = bytearray(value.payload)
+    tampered[-1] ^= 1
+    assert sealer.unseal(Sample("test/sealed/plans", bytes(tampered))) is None
+    # the key expression is authenticated too
+    assert sealer.unseal(Sample("test/sealed/other", value)) is None
+    assert len(errors) == 2
+    session.put("test/sealed/plans", b"not sealed")
+    time.sleep(SLEEP)
+    assert unsealed == [b"attack at dawn"]
+    assert len(errors) == 3
+
+    raw.undeclare()
+    sub.undeclare()
+    session.close()
+
+
+def test_mock_session():
+    session = zenoh.testing.MockSession()
+    assert isinstance(session, zenoh.ISession)
+    with pytest.raises(TypeError):
+        zenoh.ISession()
+    sub = session.declare_subscriber("demo/**", zenoh.Queue())
+
+    print("[MS][01c] Delivering puts and injected samples");
+    session.put("demo/a", b"1")
+    session.inject("demo/b", b"2")
+    assert [sub.receiver.get().payload for _ in range(2)] == [b"1", b"2"]
+    assert [str(sample.key_expr) for sample in session.published] == ["demo/a"]
+
+    print("[MS][02c] Answering queries");
+    def replies(query):
+        yield Sample("demo/c", b"3")
+    queryable = session.declare_queryable("demo/c", replies)
+    session.add_reply("demo/d", b"4")
+    assert sorted(reply.ok.payload for reply in session.get("demo/**")()) == [b"3", b"4"]
+    assert [reply.ok.payload for reply in session.query("demo/c")] == [b"3"]
+
+    queryable.undeclare()
+    sub.undeclare()
+    session.close()
+
+
+def test_rate_limit():
+    zenoh.init_logger()
+    session = open_local_session()
+    received = []
+    sub = session.declare_subscriber("test/ratelimit", lambda sample: received.append(len(sample.payload)))
+    time.sleep(SLEEP)
+
+    print("[RL][01c] Dropping what exceeds the burst");
+    session.set_rate_limit(1_000, burst=1_000)
+    assert session.rate_limit == (1_000, 1_000)
+    for _ in range(5):
+        session.put("test/ratelimit", bytes(500), congestion_control=zenoh.CongestionControl.DROP())
+    time.sleep(SLEEP)
+    assert received == [500, 500]
+
+    print("[RL][02c] Blocking until the bandwidth allows it through");
+    session.set_rate_limit(1_000, burst=500)
+    start = time.monotonic()
+    for _ in range(3):
+        session.put("test/ratelimit", bytes(500), congestion_control=zenoh.CongestionControl.BLOCK())
+    assert time.monotonic() - start >= 0.9
+
+    print("[RL][03c] Lifting the limit");
+    session.set_rate_limit(None)
+    assert session.rate_limit is None
+    with pytest.raises(ValueError):
+        session.set_rate_limit(0)
+
+    sub.undeclare()
+    session.close()
+
+
+def test_replies_ordering():
+    zenoh.init_logger()
+    conf = zenoh.Config()
+    conf.insert_json5("scouting/multicast/enabled", "false")
+    conf.insert_json5("timestamping/enabled", "true")
+    session = zenoh.open(conf)
+    samples = []
+    sub = session.declare_subscriber("test/replies/*", lambda sample: samples.append(sample))
+    time.sleep(SLEEP)
+
+    print("[RO][01c] Collecting timestamped samples");
+    session.put("test/replies/a", b"a1")
+    session.put("test/replies/b", b"b1")
+    session.put("test/replies/a", b"a2")
+    time.sleep(SLEEP)
+    assert len(samples) == 3 and all(sample.timestamp is not None for sample in samples)
+    (a1, b1, a2) = samples
+    untimestamped = Sample("test/replies/a", b"a0")
+
+    print("[RO][02c] Merging them by timestamp");
+    merged = zenoh.replies.merge_sorted([a2, untimestamped, a1], [b1])
+    assert [sample.payload for sample in merged] == [b"a1", b"b1", b"a2", b"a0"]
+
+    print("[RO][03c] Keeping the latest sample of each key");
+    latest = zenoh.replies.dedup_latest([a2, b1, untimestamped, a1])
+    assert [sample.payload for sample in latest] == [b"a2", b"b1"]
+    assert zenoh.replies.dedup_latest([untimestamped]) == [untimestamped]
+
+    sub.undeclare()
+    session.close()
+
+
+def test_queryable_cache():
+    zenoh.init_logger()
+    session = open_local_session()
+    queried = []
+    def on_query(query):
+        queried.append(str(query.key_expr))
+        return f"reply {len(queried)}"
+
+    print("[QC][01c] Evicting the least recently used selectors");
+    queryable = session.declare_queryable("test/cache/*", on_query, cache=zenoh.Cache(ttl=60.0, max_entries=2))
+    time.sleep(SLEEP)
+    for key in ["a", "b", "a", "c", "a", "b"]:
+        replies = session.get(f"test/cache/{key}", zenoh.ListCollector())()
+        assert len(replies) == 1
+    assert queried == ["test/cache/a", "test/cache/b", "test/cache/c", "test/cache/b"]
+    queryable.undeclare()
+
+    print("[QC][02c] Expiring the cached replies");
+    queried.clear()
+    queryable = session.declare_queryable("test/cache/*", on_query, cache=zenoh.Cache(ttl=0.5))
+    time.sleep(SLEEP)
+    first = session.get("test/cache/a", zenoh.ListCollector())()
+    again = session.get("test/cache/a", zenoh.ListCollector())()
+    assert first[0].ok.payload == again[0].ok.payload == b"reply 1"
+    time.sleep(0.6)
+    expired = session.get("test/cache/a", zenoh.ListCollector())()
+    assert expired[0].ok.payload == b"reply 2"
+    assert len(queried) == 2
+
+    queryable.undeclare()
+    session.close()
+
+
+def test_offline_cache():
+    zenoh.init_logger()
+    session = open_local_session()
+    path = os.path.join(tempfile.mkdtemp(), "cache.zcache")
+    cache = zenoh.offline.OfflineCache(session, "test/offline/**", path)
+    time.sleep(SLEEP)
+
+    print("[OC][01c] Caching the last value of each key");
+    session.put("test/offline/a", b"1")
+    session.put("test/offline/b", b"2")
+    session.put("test/offline/a", b"3")
+    session.put("test/offline/c", b"4")
+    session.delete("test/offline/c")
+    time.sleep(SLEEP)
+    assert not cache.online
+    assert len(cache) == 2
+    replies = cache.get("test/offline/*", zenoh.ListCollector())()
+    assert [(str(reply.ok.key_expr), reply.ok.payload) for reply in replies] == [("test/offline/a", b"3"), ("test/offline/b", b"2")]
+    assert all(reply.stale and reply.age >= 0 for reply in replies)
+    cache.close()
+
+    print("[OC][02c] Restoring the values from the file");
+    cache = zenoh.offline.OfflineCache(session, "test/offline/**", path)
+    assert [(str(reply.ok.key_expr), reply.ok.payload) for reply in cache.cached("test/offline/a")] == [("test/offline/a", b"3")]
+    cache.close()
+    session.close()
+
+
+def test_publisher_outbox():
+    zenoh.init_logger()
+    session = open_local_session()
+    path = os.path.join(tempfile.mkdtemp(), "outbox")
+    received = []
+    sub = session.declare_subscriber("test/outbox", lambda sample: received.append(sample.payload))
+    time.sleep(SLEEP)
+
+    print("[OB][01c] Holding publications back while reaching no one");
+    pub = session.declare_publisher("test/outbox", outbox=zenoh.Outbox(path, max_messages=3))
+    for i in range(5):
+        pub.put(str(i))
+    assert pub.outbox_pending == 3
+    assert pub.outbox_dropped == 2
+    assert pub.flush_outbox() == 0
+    time.sleep(SLEEP)
+    assert received == []
+    pub.undeclare()
+
+    print("[OB][02c] Restoring them from the outbox's file");
+    pub = session.declare_publisher("test/outbox", outbox=zenoh.Outbox(path, max_messages=3))
+    assert pub.outbox_pending == 3
+
+    pub.undeclare()
+    sub.undeclare()
+    session.close()
+
+
+def test_filetransfer():
+    zenoh.init_logger()
+    session = open_local_session()
+    served = tempfile.mkdtemp()
+    os.mkdir(os.path.join(served, "v2"))
+    data = os.urandom(5_000)
+    with open(os.path.join(served, "v2", "image.bin"), "wb") as f:
+        f.write(data)
+    server = zenoh.filetransfer.serve_directory(session, "test/files", served, max_chunk_size=1_500)
+    time.sleep(SLEEP)
+    dest = os.path.join(tempfile.mkdtemp(), "image.bin")
+
+    print("[FT][01c] Fetching a file in chunks");
+    assert zenoh.filetransfer.fetch(session, "test/files/v2/image.bin", dest, chunk_size=2_000) == len(data)
+    with open(dest, "rb") as f:
+        assert f.read() == data
+    assert not os.path.exists(dest + ".part")
+
+    print("[FT][02c] Resuming an interrupted transfer");
+    os.remove(dest)
+    with open(dest + ".part", "wb") as f:
+        f.write(data[:1_200])
+    with open(dest + ".part.stat", "wb") as f:
+        f.write(session.get("test/files/v2/image.bin?stat", zenoh.ListCollector())()[0].ok.payload)
+    assert zenoh.filetransfer.fetch(session, "test/files/v2/image.bin", dest) == len(data)
+    with open(dest, "rb") as f:
+        assert f.read() == data
+    assert not os.path.exists(dest + ".part.stat")
+
+    print("[FT][03c] Starting over once the served file changed");
+    os.remove(dest)
+    with open(dest + ".part", "wb") as f:
+        f.write(b"\0" * 1_200)
+    with open(dest + ".part.stat", "w") as f:
+        f.write(json.dumps({"size": len(data), "modified": 0.0}))
+    assert zenoh.filetransfer.fetch(session, "test/files/v2/image.bin", dest) == len(data)
+    with open(dest, "rb") as f:
+        assert f.read() == data
+
+    print("[FT][04c] Refusing missing files");
+    with pytest.raises(zenoh.ZError):
+        zenoh.filetransfer.fetch(session, "test/files/v2/missing.bin", dest, timeout=2.0)
+
+    server.undeclare()
+    session.close()
+
+
+def test_ring_channel():
+    print("[CH][01c] Keeping the most recent values");
+    ring = zenoh.RingChannel(2)
+    for i in range(5):
+        ring.put(i)
+    assert ring.dropped == 3
+    assert [ring.get(), ring.get()] == [3, 4]
+    assert ring.try_recv() is None
+    with pytest.raises(ValueError):
+        zenoh.RingChannel(0)
+
+
+def test_fifo_channel():
+    print("[CH][02c] Dropping the newest values");
+    fifo = zenoh.FifoChannel(2, when_full="drop")
+    for i in range(5):
+        fifo.put(i)
+    assert fifo.dropped == 3
+    assert [fifo.get(), fifo.get()] == [0, 1]
+
+    print("[CH][03c] Blocking until there is room");
+    fifo = zenoh.FifoChannel(1)
+    fifo.put(0)
+    putter = threading.Thread(target=fifo.put, args=(1,))
+    putter.start()
+    time.sleep(0.2)
+    assert putter.is_alive()
+    assert fifo.get() == 0
+    putter.join(SLEEP)
+    assert not putter.is_alive()
+    assert fifo.get() == 1 and fifo.dropped == 0
+    with pytest.raises(ValueError):
+        zenoh.FifoChannel(1, when_full="wait")
+
+
+def test_dlpack():
+    np = pytest.importorskip("numpy")
+    zenoh.init_logger()
+    session = open_local_session()
+    tensor = np.arange(12, dtype=np.float32).reshape(3, 4)
+
+    print("[DL][01c] Round-tripping a tensor through a value");
+    value = zenoh.Value.from_dlpack(tensor)
+    assert value.payload == tensor.tobytes()
+    assert np.array_equal(np.from_dlpack(value), tensor)
+    assert np.array_equal(np.from_dlpack(zenoh.Value(b"abc")), np.frombuffer(b"abc", dtype=np.uint8))
+    exported = np.from_dlpack(value)
+    exported[0, 0] = 42
+    assert value.payload == tensor.tobytes()
+    with pytest.raises(ValueError):
+        zenoh.Value.from_dlpack(tensor.T)
+
+    print("[DL][02c] Publishing a tensor");
+    received = []
+    sub = session.declare_subscriber("test/dlpack", lambda sample: received.append(np.from_dlpack(sample)))
+    time.sleep(SLEEP)
+    session.put("test/dlpack", tensor)
+    time.sleep(SLEEP)
+    assert len(received) == 1
+    assert received[0].dtype == tensor.dtype and np.array_equal(received[0], tensor)
+
+    sub.undeclare()
+    session.close()
+
+
+def test_subscriber_overflow():
+    zenoh.init_logger()
+    session = open_local_session()
+    for (policy, expected) in [("drop_newest", [b"0", b"1", b"2"]), ("drop_oldest", [b"0", b"3", b"4"])]:
+        received = []
+        release = threading.Event()
+        def slow(sample):
+            release.wait()
+            received.append(sample.payload)
+
+        print(f"[OV][01c] Buffering samples for a slow callback, with the {policy} policy");
+        sub = session.declare_subscriber("test/overflow", slow, overflow=(2, policy))
+        time.sleep(SLEEP)
+        session.put("test/overflow", b"0")
+        # the first sample is taken by the callback, which then blocks, leaving the buffer to fill up
+        time.sleep(0.2)
+        for i in range(1, 5):
+            session.put("test/overflow", str(i).encode())
+        release.set()
+        time.sleep(SLEEP)
+        assert received == expected
+        assert sub.dropped == 2
+        sub.undeclare()
+    with pytest.raises(ValueError):
+        session.declare_subscriber("test/overflow", lambda sample: None, overflow=(2, "drop_all"))
+    session.close()
+
+
+def test_schema_registry():
+    zenoh.init_logger()
+    session = open_local_session()
+    schema = {"type": "object", "properties": {"celsius": {"type": "number"}}}
+
+    print("[SR][01c] Registering a schema");
+    registration = session.register_schema("test/schema/*/temperature", schema)
+    time.sleep(SLEEP)
+    fetched = session.schema_of("test/schema/kitchen/temperature")
+    assert fetched is not None and json.loads(fetched.payload) == schema
+    assert str(fetched.encoding).startswith("application/json")
+    assert session.schema_of("test/schema/kitchen/humidity", timeout=0.5) is None
+
+    print("[SR][02c] Serving the cached schemas");
+    registration.undeclare()
+    time.sleep(SLEEP)
+    assert json.loads(session.schema_of("test/schema/kitchen/temperature").payload) == schema
+    assert session.schema_of("test/schema/kitchen/temperature", timeout=0.5, refresh=True) is None
+
+    session.close()
+
+
+def test_get_paged():
+    zenoh.init_logger()
+    session = open_local_session()
+    data = {f"test/paged/{i:02}": i for i in range(5)}
+    queryable = session.declare_paged_queryable("test/paged/**", lambda after, limit, query:
+        (Sample(k, v) for k, v in sorted(data.items()) if after is None or k > after), max_limit=3)
+    time.sleep(SLEEP)
+
+    print("[PG][01c] Paging through the queryable");
+    assert [str(sample.key_expr) for sample in session.get_paged("test/paged/**", page_size=2)] == sorted(data)
+
+    print("[PG][02c] Asking for pages larger than the queryable serves");
+    with pytest.raises(zenoh.ZError):
+        list(session.get_paged("test/paged/**", page_size=5))
+    queryable.undeclare()
+    session.close()
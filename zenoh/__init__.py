@@ -11,22 +11,49 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from .zenoh import init_logger, scout as _scout
-from .keyexpr import IntoKeyExpr, IntoSelector, KeyExpr, Selector
-from .config import Config
-from .session import Session, Publisher, Subscriber, PullSubscriber, Info
-from .enums import CongestionControl, Encoding, Priority, QueryConsolidation, QueryTarget, Reliability, SampleKind
-from .value import Hello, Value, IntoValue, IValue, Sample, IntoSample, ZenohId, Timestamp, Reply
-from .closures import Closure, IClosure, IntoClosure, Handler, IHandler, IntoHandler, ListCollector, Queue
-from .queryable import Queryable, Query
-from typing import Any
+from .zenoh import init_logger, configure_runtime, scout as _scout, record as _record, replay as _replay, ZError, RecvTimeoutError
+from .keyexpr import IntoKeyExpr, IntoSelector, KeyExpr, Selector, mqtt_topic_to_keyexpr, keyexpr_to_mqtt_topic, glob_to_keyexpr, regex_to_keyexpr
+from .config import Config, RetryPolicy, TlsConfig, register_endpoint_resolver
+from .session import _SESSIONS, Session, Outbox, Publisher, PeriodicPublisher, MetricsExporter, Subscriber, PullSubscriber, Dispatcher, Info, Declaration
+from .enums import CongestionControl, Encoding, Priority, QueryConsolidation, QueryTarget, Reliability, SampleKind, ChangeKind, Locality
+from .value import Hello, Value, IntoValue, IValue, Sample, Change, SampleFilter, IntoSample, ZenohId, Timestamp, Reply, register_codec
+from .closures import Closure, IClosure, IntoClosure, Handler, IHandler, IntoHandler, ListCollector, Queue, RingChannel, FifoChannel
+from .queryable import Cache, Queryable, Query
+from .admin import MatchingEntity
+from .workspace import Workspace, Data
+from .topic import Topic, Codec
+from .kv import KV
+from . import admin
+from . import aio
+from . import crypto
+from . import filetransfer
+from . import integrity
+from . import offline
+from . import replies
+from . import shm
+from . import supervisor
+from . import testing
+from . import timestamps
+from .interfaces import ISession, IPublisher, ISubscriber, IQueryable
+from typing import Any, Callable, Optional, List, Union
 
 def open(*args, **kwargs):
     """
     Open a Zenoh session.
 
+    Opening may take a while, for example when the configured endpoints are unreachable: like the other blocking calls
+    of these bindings, such as ``Queue.get``, it can be interrupted with Ctrl-C, which raises ``KeyboardInterrupt``.
+
     :param config: The configuration of the Zenoh session
     :type config: Config
+    :param share_runtime: if ``True``, reuse the runtime of any open session with the same configuration, see ``Session``
+    :param namespace: a prefix transparently added to all the key expressions the session sends, and stripped from those it receives
+    :param callback_threads: if set, the number of threads the session's callbacks are called from, see ``Session``
+    :param audit: a file path or ``logging.Logger`` to record all the session's operations to, see ``Session``
+    :param callback_errors: ``"log"`` (the default), ``"raise"`` or ``"ignore"``: what becomes of the exceptions raised by callbacks, see ``Session``
+    :param on_error: a function called with each exception raised by the session's callbacks
+    :param runtime_threads: if set, the number of worker threads of the async runtime backing the sessions, see ``configure_runtime``
+    :param resilient: if ``True``, rebuild the session's runtime and declare its entities anew when it stops responding, see ``Session``
     :rtype: Session
 
     :Example:
@@ -36,39 +63,242 @@ def open(*args, **kwargs):
     """
     return Session(*args, **kwargs)
 
+def init(config: Union[Config, Any] = None, name: str = None) -> Session:
+    """
+    Opens a session and registers it for the whole process, as the default one unless ``name`` is passed,
+    so that the libraries built on top of zenoh can share it through ``default_session`` or ``get_session``.
+
+    The session stays registered until it is released through ``release_session``, or closed.
+    Raises a ``ValueError`` if a session was already registered under that name.
+
+    :Example:
+
+    >>> import zenoh
+    >>> zenoh.init({'mode': 'client'})
+    >>> zenoh.default_session().put('demo/hello', 'Hello World!')
+    """
+    return _SESSIONS.register(name, lambda: Session(config), exclusive=True)
+
+def default_session() -> Session:
+    "Returns the session registered by ``zenoh.init``, opening one with the default configuration if there is none yet."
+    return _SESSIONS.register(None, Session, exclusive=False)
+
+def get_session(name: str) -> Session:
+    "Returns the session registered under ``name`` through ``zenoh.init``, raising a ``KeyError`` if there is none."
+    return _SESSIONS.get(name)
+
+def release_session(name: str = None) -> Optional[Session]:
+    """
+    Unregisters the session registered under ``name`` (the default one if ``None``), closing it.
+
+    Returns the released session, or ``None`` if there was none.
+    """
+    session = _SESSIONS.release(name)
+    if session is not None:
+        session.close()
+    return session
+
 class Scout:
+    """
+    A running scout, as returned by ``zenoh.scout``, which exposes its handler's receiver through ``self.receiver``.
+
+    Scouting lasts until ``stop`` is called, the scout's ``timeout`` expires, or the scout is garbage collected.
+    """
     def __init__(self, inner, receiver):
         self._inner_ = inner
         self.receiver = receiver
+        self._timer_ = None
     
     def stop(self):
-        self._inner_ = None
+        "Stops scouting, after which the handler is no longer called. Stopping a stopped scout does nothing."
+        if self._timer_ is not None:
+            self._timer_.cancel()
+        self._inner_.stop()
+
+    @property
+    def stopped(self) -> bool:
+        "Whether the scout was stopped."
+        return self._inner_.stopped
+
+    def __enter__(self):
+        return self
 
-def scout(handler: IntoHandler[Hello, Any, Any] = None, what: str = None, config: Config = None, timeout=None):
+    def __exit__(self, *args):
+        self.stop()
+
+def scout(handler: IntoHandler[Hello, Any, Any] = None, what: str = None, config: Config = None, timeout=None,
+          listener: Callable[[Hello], Any] = None) -> Scout:
     """
     Scout for routers and/or peers.
 
-    This spawns a task that periodically sends scout messages for a specified duration and returns
-    a list of received :class:`Hello` messages.
+    This spawns a task that periodically sends scout messages, for ``timeout`` seconds if set, or until the returned
+    :class:`Scout` is stopped otherwise, passing the received :class:`Hello` messages to ``handler``.
+    By default, they are collected into a list, which ``receiver()`` returns.
+
+    With ``listener`` instead, a callback, each :class:`Hello` is passed to it as soon as it is received, without being buffered,
+    which lets long-running applications track the routers and peers appearing over time. Note that zenoh processes answer
+    every scout message: ``listener`` is called again for each of them, once per scouting period.
 
     :param what: The kind of zenoh process to scout for
     :param config: The configuration to use for scouting
-    :param timeout: the duration of scout (in seconds)
+    :param timeout: the duration of scout (in seconds), unlimited if ``None``
     :param handler:
-    :rtype: list of :class:`Hello`
+    :param listener: a callback receiving each :class:`Hello`, as an alternative to ``handler``
+    :rtype: Scout
 
     :Example:
 
     >>> import zenoh
     >>> for hello in zenoh.scout(what = "peer|router", timeout=1.0).receiver():
     ...     print(hello)
+
+    Scouting continuously:
+
+    >>> seen = set()
+    >>> def on_hello(hello):
+    ...     if str(hello.zid) not in seen:
+    ...         seen.add(str(hello.zid))
+    ...         print("discovered", hello)
+    >>> scout = zenoh.scout(what="peer|router", listener=on_hello)
+    >>> # ... later on
+    >>> scout.stop()
     """
     from threading import Timer
-    if handler is None:
+    if listener is not None:
+        if handler is not None:
+            raise ValueError("`handler` and `listener` are mutually exclusive")
+        handler = listener
+    elif handler is None:
         handler = ListCollector()
     handler = Handler(handler, lambda x: Hello._upgrade_(x))
     scout = _scout(handler.closure, config, what)
     scout = Scout(scout, handler.receiver)
     if timeout:
-        Timer(timeout, lambda: scout.stop()).start()
+        scout._timer_ = Timer(timeout, scout.stop)
+        scout._timer_.daemon = True
+        scout._timer_.start()
     return scout
+
+def wait_for(session: Session, keyexpr: IntoKeyExpr, predicate: Callable[[Sample], bool] = None, timeout: float = None) -> Optional[Sample]:
+    """
+    Waits for the first sample published on ``keyexpr`` (or the first one ``predicate`` returns ``True`` for),
+    through a temporary subscriber, returning ``None`` if ``timeout`` (in seconds) expires first.
+
+    :Example:
+
+    >>> import zenoh
+    >>> s = zenoh.open({})
+    >>> sample = zenoh.wait_for(s, 'demo/robot/state', lambda sample: sample.payload == b'ready', timeout=10)
+    """
+    from time import monotonic
+    deadline = None if timeout is None else monotonic() + timeout
+    subscriber = session.declare_subscriber(keyexpr, Queue())
+    try:
+        while True:
+            remaining = None if deadline is None else max(deadline - monotonic(), 0)
+            try:
+                sample = subscriber.receiver.get(remaining)
+            except (TimeoutError, StopIteration):
+                return None
+            if predicate is None or predicate(sample):
+                return sample
+    finally:
+        subscriber.undeclare()
+
+_SHUTDOWN_HANDLER_INSTALLED = False
+
+def install_shutdown_handler(signals: Optional[List[int]] = None, drain_timeout: float = 1.0):
+    """
+    Makes sure all open sessions are closed when the interpreter exits, or when it receives one of ``signals``
+    (``SIGINT`` and ``SIGTERM`` by default).
+
+    Closing the sessions undeclares their entities, which stops the threads running their handlers, after spending up
+    to ``drain_timeout`` seconds per session on forwarding the publications held back by outboxes and handling the
+    samples and queries already received (see ``Session.close``). The event loop running coroutine handlers is then
+    stopped. Once the sessions are closed, the signals are handled as they were before this call.
+    Calling this function again has no effect.
+
+    The interpreter joins its threads before running the ``atexit`` hooks this registers: so that they don't keep it
+    from exiting, the threads running the handlers declared from then on are daemon threads, which these hooks join
+    once the handlers are done. This should thus be called before opening sessions.
+
+    Signal handlers can only be installed from the main thread.
+
+    :Example:
+
+    >>> import zenoh
+    >>> zenoh.install_shutdown_handler()
+    >>> s = zenoh.open({})
+    """
+    global _SHUTDOWN_HANDLER_INSTALLED
+    if _SHUTDOWN_HANDLER_INSTALLED:
+        return
+    import signal, atexit
+    from . import closures
+    if signals is None:
+        signals = [signal.SIGINT, signal.SIGTERM]
+    def shutdown():
+        _SESSIONS.close_all(drain_timeout)
+        closures._stop_internal_loop()
+    def handler(signum, frame, previous):
+        shutdown()
+        if callable(previous):
+            previous(signum, frame)
+        elif previous != signal.SIG_IGN:
+            raise SystemExit(128 + signum)
+    for signum in signals:
+        previous = signal.getsignal(signum)
+        signal.signal(signum, lambda signum, frame, previous=previous: handler(signum, frame, previous))
+    closures._DAEMON_HANDLER_THREADS = True
+    atexit.register(shutdown)
+    _SHUTDOWN_HANDLER_INSTALLED = True
+
+class Recorder:
+    """
+    A running traffic capture, as returned by ``zenoh.record``.
+
+    The capture lasts until ``stop`` is called, which must be done for the file to be complete.
+    """
+    def __init__(self, inner):
+        self._inner_ = inner
+
+    @property
+    def count(self) -> int:
+        "The number of samples captured so far."
+        return self._inner_.count
+
+    def stop(self):
+        "Stops the capture and flushes its file, raising a ``ZError`` if writing it failed."
+        self._inner_.stop()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *args):
+        self.stop()
+
+def record(session: Session, keyexpr: IntoKeyExpr, path: str) -> Recorder:
+    """
+    Captures the samples received on ``keyexpr`` into the file at ``path``, along with their reception times.
+
+    The capture is written by the bindings in a compact binary format, without going through Python.
+
+    :Example:
+
+    >>> import zenoh, time
+    >>> s = zenoh.open({})
+    >>> with zenoh.record(s, 'demo/**', 'demo.zrec'):
+    ...     time.sleep(10)
+    """
+    return Recorder(_record(session, KeyExpr(keyexpr), path))
+
+def replay(session: Session, path: str, speed: float = 1.0) -> int:
+    """
+    Re-publishes the samples captured by ``zenoh.record`` into the file at ``path``, reproducing their original timing,
+    accelerated by ``speed`` (``float('inf')`` re-publishing them as fast as possible).
+
+    Blocks until the whole capture was replayed, returning the number of samples that were.
+    Note that the samples' original timestamps aren't replayed: zenoh timestamps them anew.
+    """
+    return _replay(session, path, speed)
+
@@ -0,0 +1,148 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+from typing import List, Dict, Any, Optional
+import json
+
+from .keyexpr import KeyExpr, IntoKeyExpr
+from .closures import Queue
+from .enums import QueryConsolidation
+from .zenoh import ZError
+
+ADMIN_PREFIX = "@/router"
+
+class MatchingEntity:
+    """
+    A remote subscriber or queryable whose key expression intersects with a given one,
+    as reported by the admin space of a router.
+
+    ``kind`` is either ``"subscriber"`` or ``"queryable"``.
+    Note that routers aggregate the declarations they route: ``zid`` and ``whatami`` identify
+    the router that reported the entity, not necessarily the node that declared it.
+    """
+    def __init__(self, zid: str, whatami: str, kind: str, key_expr: KeyExpr):
+        self.zid = zid
+        self.whatami = whatami
+        self.kind = kind
+        self.key_expr = key_expr
+
+    def __repr__(self):
+        return f"MatchingEntity(zid={self.zid!r}, whatami={self.whatami!r}, kind={self.kind!r}, key_expr='{self.key_expr}')"
+
+
+class Plugin:
+    "A plugin loaded by a router."
+    def __init__(self, name: str, raw: Dict[str, Any]):
+        self.name = name
+        self.path: Optional[str] = raw.get("path")
+        self.raw = raw
+
+    def __repr__(self):
+        return f"Plugin(name={self.name!r}, path={self.path!r})"
+
+
+class Storage:
+    "A storage managed by a router's storage manager plugin."
+    def __init__(self, name: str, raw: Dict[str, Any]):
+        self.name = name
+        key_expr = raw.get("key_expr")
+        self.key_expr: Optional[KeyExpr] = None if key_expr is None else KeyExpr(key_expr)
+        self.volume = raw.get("volume")
+        self.raw = raw
+
+    def __repr__(self):
+        return f"Storage(name={self.name!r}, key_expr={str(self.key_expr)!r}, volume={self.volume!r})"
+
+
+class Transport:
+    "A transport session between a router and another zenoh node, along with the links it uses."
+    def __init__(self, raw: Dict[str, Any]):
+        self.zid: Optional[str] = raw.get("peer")
+        self.whatami: Optional[str] = raw.get("whatami")
+        self.links: List[str] = list(raw.get("links", []))
+        self.raw = raw
+
+    def __repr__(self):
+        return f"Transport(zid={self.zid!r}, whatami={self.whatami!r}, links={self.links!r})"
+
+
+class RouterClient:
+    """
+    A typed client for the admin space of a router.
+
+    Each method queries the router under ``@/router/<router_zid>`` and parses
+    the JSON replies into Python objects, keeping the raw JSON available through their ``raw`` field.
+
+    :Example:
+
+    >>> import zenoh
+    >>> s = zenoh.open({})
+    >>> for zid in s.info().routers_zid():
+    ...     print(zenoh.admin.RouterClient(s, zid).plugins())
+    """
+    def __init__(self, session, router_zid):
+        self.session = session
+        self.router_zid = str(router_zid)
+
+    @property
+    def prefix(self) -> str:
+        "The admin space key expression of the router."
+        return f"{ADMIN_PREFIX}/{self.router_zid}"
+
+    def info(self) -> Dict[str, Any]:
+        """
+        Returns the router's description, as a JSON dictionary.
+
+        Raises a ``ZError`` if the router didn't reply.
+        """
+        for sample in _admin_get(self.session, self.prefix):
+            return json.loads(sample.payload)
+        raise ZError(f"Router {self.router_zid} didn't reply on its admin space")
+
+    def plugins(self) -> List[Plugin]:
+        "Returns the plugins loaded by the router."
+        return [Plugin(name, raw) for name, raw in self.info().get("plugins", dict()).items()]
+
+    def transports(self) -> List[Transport]:
+        "Returns the transport sessions the router currently maintains."
+        return [Transport(raw) for raw in self.info().get("sessions", [])]
+
+    def storages(self) -> List[Storage]:
+        "Returns the storages managed by the router's storage manager plugin."
+        prefix = f"{self.prefix}/status/plugins/storage_manager/storages/"
+        storages = []
+        for sample in _admin_get(self.session, f"{prefix}*"):
+            name = str(sample.key_expr)[len(prefix):]
+            storages.append(Storage(name, json.loads(sample.payload)))
+        return storages
+
+
+def _admin_get(session, selector: str):
+    "Queries the admin space, returning the ok replies' samples."
+    replies = session.get(selector, Queue(), consolidation=QueryConsolidation.NONE())
+    return [reply.ok for reply in replies if reply.is_ok]
+
+
+def matching_entities(session, keyexpr: IntoKeyExpr) -> List[MatchingEntity]:
+    keyexpr = KeyExpr(keyexpr)
+    entities = []
+    for kind in ("subscriber", "queryable"):
+        for sample in _admin_get(session, f"{ADMIN_PREFIX}/*/{kind}/**"):
+            # admin keys are formatted as `@/router/<zid>/<kind>/<key_expr>`
+            parts = str(sample.key_expr).split("/", 4)
+            if len(parts) != 5:
+                continue
+            remote = KeyExpr.autocanonize(parts[4])
+            if remote.intersects(keyexpr):
+                entities.append(MatchingEntity(parts[2], "router", kind, remote))
+    return entities
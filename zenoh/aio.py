@@ -0,0 +1,175 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+An asyncio flavour of the API, for applications built around an event loop.
+
+Operations that may block are run on the event loop's default executor, and the samples, queries and replies zenoh
+delivers are handed over to the event loop, so that neither ever stalls it.
+"""
+import asyncio
+import functools
+from typing import Any, Callable, Generic, List, TypeVar, Union
+
+from .closures import IHandler, IClosure, Closure
+from .config import Config
+from .enums import Priority, CongestionControl, Reliability, QueryConsolidation, QueryTarget
+from .keyexpr import IntoKeyExpr, IntoSelector
+from .value import IntoValue, Sample, Reply
+from .queryable import Queryable, Query
+from .session import Session, Publisher, Subscriber
+
+In = TypeVar("In")
+_CLOSED = object()
+
+class AsyncQueue(IHandler[In, None, 'AsyncQueue'], Generic[In]):
+    """
+    A queue handing the values zenoh passes to its callback over to an asyncio event loop.
+
+    When used as a handler, it provides itself as the receiver, which may be iterated with ``async for``
+    until the callback is dropped (for example, once all the replies to a query have been received).
+
+    Must be constructed from within the event loop, unless ``loop`` is passed.
+    """
+    def __init__(self, loop: asyncio.AbstractEventLoop = None):
+        self._loop_ = asyncio.get_running_loop() if loop is None else loop
+        self._queue_ = asyncio.Queue()
+
+    def _put_(self, value):
+        try:
+            self._loop_.call_soon_threadsafe(self._queue_.put_nowait, value)
+        except RuntimeError:
+            # the event loop is closed, there's no one left to deliver to
+            pass
+
+    @property
+    def closure(self) -> IClosure[In, None]:
+        return Closure((self._put_, lambda: self._put_(_CLOSED)))
+
+    @property
+    def receiver(self) -> 'AsyncQueue':
+        return self
+
+    async def get(self) -> In:
+        "Waits for the next value, raising ``StopAsyncIteration`` once the queue is closed and empty."
+        value = await self._queue_.get()
+        if value is _CLOSED:
+            self._queue_.put_nowait(_CLOSED)
+            raise StopAsyncIteration
+        return value
+
+    async def get_remaining(self) -> List[In]:
+        "Waits for the queue to be closed, returning the values it still held."
+        return [value async for value in self]
+
+    def __aiter__(self):
+        return self
+
+    async def __anext__(self) -> In:
+        return await self.get()
+
+def _dispatcher(loop: asyncio.AbstractEventLoop, callback: Callable[[Any], Any]) -> Closure:
+    "Makes a closure calling ``callback`` (or scheduling it, if it is a coroutine function) on ``loop``."
+    if asyncio.iscoroutinefunction(callback):
+        dispatch = lambda x: loop.create_task(callback(x))
+    else:
+        dispatch = callback
+    def call(x):
+        try:
+            loop.call_soon_threadsafe(dispatch, x)
+        except RuntimeError:
+            pass
+    return Closure(call)
+
+class AsyncSession:
+    """
+    An asyncio counterpart to ``Session``, as returned by ``zenoh.aio.open``.
+
+    The wrapped ``Session`` is available as ``session``, for the operations that don't block.
+
+    :Example:
+
+    >>> import asyncio, zenoh.aio
+    >>> async def main():
+    ...     s = await zenoh.aio.open({})
+    ...     sub = await s.declare_subscriber('demo/**')
+    ...     await s.put('demo/hello', 'Hello World!')
+    ...     async for sample in sub.receiver:
+    ...         print(sample.payload)
+    >>> asyncio.run(main())
+    """
+    def __init__(self, session: Session):
+        self.session = session
+
+    async def _run_(self, f, *args, **kwargs):
+        return await asyncio.get_running_loop().run_in_executor(None, functools.partial(f, *args, **kwargs))
+
+    async def put(self, keyexpr: IntoKeyExpr, value: IntoValue, encoding=None,
+                  priority: Priority = None, congestion_control: CongestionControl = None, express: bool = None):
+        "The awaitable counterpart of ``Session.put``."
+        await self._run_(self.session.put, keyexpr, value, encoding, priority, congestion_control, express=express)
+
+    async def delete(self, keyexpr: IntoKeyExpr,
+                     priority: Priority = None, congestion_control: CongestionControl = None, express: bool = None):
+        "The awaitable counterpart of ``Session.delete``."
+        await self._run_(self.session.delete, keyexpr, priority, congestion_control, express=express)
+
+    async def get(self, selector: IntoSelector, consolidation: QueryConsolidation = None, target: QueryTarget = None,
+                  value: IntoValue = None, timeout: float = None) -> AsyncQueue[Reply]:
+        """
+        The awaitable counterpart of ``Session.get``, returning an ``AsyncQueue`` of the replies.
+
+        The queue is closed once all the replies have been received.
+        """
+        replies = AsyncQueue()
+        return await self._run_(self.session.get, selector, replies, consolidation, target, value, timeout=timeout)
+
+    async def declare_publisher(self, keyexpr: IntoKeyExpr, priority: Priority = None,
+                                congestion_control: CongestionControl = None, express: bool = None) -> Publisher:
+        "The awaitable counterpart of ``Session.declare_publisher``."
+        return await self._run_(self.session.declare_publisher, keyexpr, priority, congestion_control, express=express)
+
+    async def declare_subscriber(self, keyexpr: IntoKeyExpr, callback: Callable[[Sample], Any] = None,
+                                 reliability: Reliability = None) -> Subscriber:
+        """
+        The awaitable counterpart of ``Session.declare_subscriber``.
+
+        ``callback`` is called from the event loop, and may be a coroutine function, in which case a task is created for each sample.
+        Without ``callback``, the samples are delivered to the returned subscriber's ``receiver``, an ``AsyncQueue``.
+        """
+        handler = AsyncQueue() if callback is None else _dispatcher(asyncio.get_running_loop(), callback)
+        return await self._run_(self.session.declare_subscriber, keyexpr, handler, reliability)
+
+    async def declare_queryable(self, keyexpr: IntoKeyExpr, callback: Callable[[Query], Any] = None,
+                                complete: bool = None) -> Queryable:
+        """
+        The awaitable counterpart of ``Session.declare_queryable``.
+
+        ``callback`` is called from the event loop, and may be a coroutine function, in which case a task is created for each query.
+        Without ``callback``, the queries are delivered to the returned queryable's ``receiver``, an ``AsyncQueue``.
+        """
+        handler = AsyncQueue() if callback is None else _dispatcher(asyncio.get_running_loop(), callback)
+        return await self._run_(self.session.declare_queryable, keyexpr, handler, complete)
+
+    async def close(self):
+        "The awaitable counterpart of ``Session.close``."
+        await self._run_(self.session.close)
+
+async def open(config: Union[Config, Any] = None) -> AsyncSession:
+    """
+    Opens a Zenoh session without blocking the event loop.
+
+    :param config: The configuration of the Zenoh session
+    :rtype: AsyncSession
+    """
+    return AsyncSession(await asyncio.get_running_loop().run_in_executor(None, Session, config))
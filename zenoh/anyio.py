@@ -0,0 +1,169 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+An event loop agnostic flavour of the async API, built on `AnyIO <https://anyio.readthedocs.io>`_,
+and thus usable from trio as well as from asyncio. It requires ``anyio`` to be installed (``pip install eclipse-zenoh[anyio]``).
+
+The values zenoh delivers are handed over through ``Channel``, which zenoh's threads fill without ever waiting
+on the event loop, while blocking operations run on AnyIO's worker threads.
+"""
+import collections
+import functools
+import socket
+from typing import Any, Generic, List, TypeVar, Union
+
+import anyio
+
+from .closures import IHandler, IClosure, Closure
+from .config import Config
+from .enums import Priority, CongestionControl, Reliability, QueryConsolidation, QueryTarget
+from .keyexpr import IntoKeyExpr, IntoSelector
+from .value import IntoValue, Reply
+from .queryable import Queryable
+from .session import Session, Publisher, Subscriber
+
+In = TypeVar("In")
+
+class Channel(IHandler[In, None, 'Channel'], Generic[In]):
+    """
+    A channel handing the values zenoh passes to its callback over to whichever event loop awaits them.
+
+    Producers append to a queue and wake the consumer up through a socket pair, which any event loop can wait on.
+    When used as a handler, it provides itself as the receiver, which may be iterated with ``async for``
+    until the callback is dropped. It supports a single consumer at a time.
+    """
+    def __init__(self):
+        self._values_ = collections.deque()
+        self._closed_ = False
+        self._reader_, self._writer_ = socket.socketpair()
+        self._reader_.setblocking(False)
+        self._writer_.setblocking(False)
+
+    def _wake_(self):
+        try:
+            self._writer_.send(b"\0")
+        except OSError:
+            # the socket's buffer is full of wake-ups already, or the channel was garbage collected
+            pass
+
+    def _put_(self, value):
+        self._values_.append(value)
+        self._wake_()
+
+    def _close_(self):
+        self._closed_ = True
+        self._wake_()
+
+    @property
+    def closure(self) -> IClosure[In, None]:
+        return Closure((self._put_, self._close_))
+
+    @property
+    def receiver(self) -> 'Channel':
+        return self
+
+    async def get(self) -> In:
+        "Waits for the next value, raising ``StopAsyncIteration`` once the channel is closed and empty."
+        while True:
+            if self._values_:
+                return self._values_.popleft()
+            if self._closed_:
+                raise StopAsyncIteration
+            await anyio.wait_socket_readable(self._reader_)
+            try:
+                self._reader_.recv(4096)
+            except BlockingIOError:
+                pass
+
+    async def get_remaining(self) -> List[In]:
+        "Waits for the channel to be closed, returning the values it still held."
+        return [value async for value in self]
+
+    def __aiter__(self):
+        return self
+
+    async def __anext__(self) -> In:
+        return await self.get()
+
+    def __del__(self):
+        self._reader_.close()
+        self._writer_.close()
+
+class AnyioSession:
+    """
+    An AnyIO counterpart to ``Session``, as returned by ``zenoh.anyio.open``.
+
+    The wrapped ``Session`` is available as ``session``, for the operations that don't block.
+
+    :Example:
+
+    >>> import trio, zenoh.anyio
+    >>> async def main():
+    ...     s = await zenoh.anyio.open({})
+    ...     sub = await s.declare_subscriber('demo/**')
+    ...     await s.put('demo/hello', 'Hello World!')
+    ...     async for sample in sub.receiver:
+    ...         print(sample.payload)
+    >>> trio.run(main)
+    """
+    def __init__(self, session: Session):
+        self.session = session
+
+    async def _run_(self, f, *args, **kwargs):
+        return await anyio.to_thread.run_sync(functools.partial(f, *args, **kwargs))
+
+    async def put(self, keyexpr: IntoKeyExpr, value: IntoValue, encoding=None,
+                  priority: Priority = None, congestion_control: CongestionControl = None, express: bool = None):
+        "The awaitable counterpart of ``Session.put``."
+        await self._run_(self.session.put, keyexpr, value, encoding, priority, congestion_control, express=express)
+
+    async def delete(self, keyexpr: IntoKeyExpr,
+                     priority: Priority = None, congestion_control: CongestionControl = None, express: bool = None):
+        "The awaitable counterpart of ``Session.delete``."
+        await self._run_(self.session.delete, keyexpr, priority, congestion_control, express=express)
+
+    async def get(self, selector: IntoSelector, consolidation: QueryConsolidation = None, target: QueryTarget = None,
+                  value: IntoValue = None, timeout: float = None) -> Channel[Reply]:
+        """
+        The awaitable counterpart of ``Session.get``, returning a ``Channel`` of the replies.
+
+        The channel is closed once all the replies have been received.
+        """
+        return await self._run_(self.session.get, selector, Channel(), consolidation, target, value, timeout=timeout)
+
+    async def declare_publisher(self, keyexpr: IntoKeyExpr, priority: Priority = None,
+                                congestion_control: CongestionControl = None, express: bool = None) -> Publisher:
+        "The awaitable counterpart of ``Session.declare_publisher``."
+        return await self._run_(self.session.declare_publisher, keyexpr, priority, congestion_control, express=express)
+
+    async def declare_subscriber(self, keyexpr: IntoKeyExpr, reliability: Reliability = None) -> Subscriber:
+        "The awaitable counterpart of ``Session.declare_subscriber``, delivering the samples to the returned subscriber's ``receiver``, a ``Channel``."
+        return await self._run_(self.session.declare_subscriber, keyexpr, Channel(), reliability)
+
+    async def declare_queryable(self, keyexpr: IntoKeyExpr, complete: bool = None) -> Queryable:
+        "The awaitable counterpart of ``Session.declare_queryable``, delivering the queries to the returned queryable's ``receiver``, a ``Channel``."
+        return await self._run_(self.session.declare_queryable, keyexpr, Channel(), complete)
+
+    async def close(self):
+        "The awaitable counterpart of ``Session.close``."
+        await self._run_(self.session.close)
+
+async def open(config: Union[Config, Any] = None) -> AnyioSession:
+    """
+    Opens a Zenoh session without blocking the event loop.
+
+    :param config: The configuration of the Zenoh session
+    :rtype: AnyioSession
+    """
+    return AnyioSession(await anyio.to_thread.run_sync(Session, config))
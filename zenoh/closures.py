@@ -12,10 +12,14 @@
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
 import abc
-from typing import Generic, Callable, Union, Any, TypeVar, Tuple, List
-from threading import Condition, Thread
+from typing import Generic, Callable, Union, Any, TypeVar, Tuple, List, Dict, Optional
+from threading import Condition, Thread, Lock, current_thread
 from collections import deque
+import asyncio
+import concurrent.futures
+import logging
 import time
+import traceback
 
 from .zenoh import _Queue
 
@@ -24,6 +28,7 @@ Out = TypeVar("Out")
 Receiver = TypeVar("Receiver")
 CallbackCall = Callable[[In], Out]
 CallbackDrop = Callable[[], None]
+CallbackError = Callable[[Exception], Any]
 
 class IClosure(Generic[In, Out]):
     """
@@ -70,13 +75,81 @@ class IHandler(Generic[In, Out, Receiver]):
         "The part of the handler that should be used as the receiver when the handler is channel-like."
         ...
 
+class _CallbackErrors:
+    """
+    What becomes of the exceptions raised by a session's callbacks: they are passed to ``on_error`` if set, then either
+    logged to the ``zenoh`` logger (``"log"``), kept for the session's next call to raise (``"raise"``), or dropped (``"ignore"``).
+    """
+    def __init__(self, policy: str = "log", on_error: CallbackError = None):
+        if policy not in ("log", "raise", "ignore"):
+            raise ValueError(f"unknown callback error policy {policy!r}, expected 'log', 'raise' or 'ignore'")
+        self.policy = policy
+        self.on_error = on_error
+        self._pending_ = None
+        self._lock_ = Lock()
+
+    def __call__(self, e: Exception):
+        if self.on_error is not None:
+            try:
+                self.on_error(e)
+            except Exception:
+                logging.getLogger("zenoh").exception("`on_error` raised while handling an exception raised in a callback")
+        if self.policy == "log":
+            logging.getLogger("zenoh").error("Exception raised in a callback", exc_info=e)
+        elif self.policy == "raise":
+            with self._lock_:
+                # only the first exception is kept until raised: the later ones are likely its consequences
+                if self._pending_ is None:
+                    self._pending_ = e
+
+    def raise_pending(self):
+        with self._lock_:
+            e, self._pending_ = self._pending_, None
+        if e is not None:
+            raise e
+
 IntoClosure = Union[IHandler[In, Out, Any], IClosure[In, Out], Tuple[CallbackCall, CallbackDrop], CallbackCall]
+class QueueProfile:
+    """
+    Statistics on how long the values passed through a ``Closure``'s queue waited in it,
+    and how long the callback took to process them. Durations are in seconds.
+    """
+    def __init__(self):
+        self._lock_ = Lock()
+        self.calls = 0
+        self.queue_wait_total = 0.
+        self.queue_wait_max = 0.
+        self.callback_total = 0.
+        self.callback_max = 0.
+
+    def _record_(self, queue_wait: float, callback: float):
+        with self._lock_:
+            self.calls += 1
+            self.queue_wait_total += queue_wait
+            self.queue_wait_max = max(self.queue_wait_max, queue_wait)
+            self.callback_total += callback
+            self.callback_max = max(self.callback_max, callback)
+
+    def stats(self) -> Dict[str, float]:
+        with self._lock_:
+            calls = max(self.calls, 1)
+            return {
+                "queue_wait_avg": self.queue_wait_total / calls,
+                "queue_wait_max": self.queue_wait_max,
+                "callback_avg": self.callback_total / calls,
+                "callback_max": self.callback_max,
+            }
+
+# set by ``zenoh.install_shutdown_handler``, whose exit hook joins the handler threads itself
+_DAEMON_HANDLER_THREADS = False
+
 class Closure(IClosure, Generic[In, Out]):
     """
     A Closure is a pair of a ``call`` function that will be used as a callback,
     and a ``drop`` function that will be called when the closure is destroyed.
     """
-    def __init__(self, closure: IntoClosure[In, Out], type_adaptor: Callable[[Any], In] = None, prevent_direct_calls=False):
+    def __init__(self, closure: IntoClosure[In, Out], type_adaptor: Callable[[Any], In] = None, prevent_direct_calls=False, profile: QueueProfile = None,
+                 on_error: CallbackError = None):
         _call_ = None
         _drop_ = lambda: None
         if isinstance(closure, IHandler):
@@ -95,22 +168,47 @@ class Closure(IClosure, Generic[In, Out]):
             adapted = lambda *args: _call_(type_adaptor(*args))
         else:
             adapted = _call_
+        if on_error is not None:
+            unguarded = adapted
+            def adapted(*args):
+                try:
+                    unguarded(*args)
+                except Exception as e:
+                    on_error(e)
         if prevent_direct_calls:
+            # The callback may use the session, which may call it back from the same thread (such as when it puts
+            # on a key it subscribes to): those calls are kept aside rather than waiting for room in the queue,
+            # which only this thread would make. They are handled once the current call returns.
             queue = Queue(128)
+            reentrant = deque()
+            def run(enqueued, x):
+                start = time.perf_counter()
+                adapted(*x)
+                if profile is not None:
+                    profile._record_(start - enqueued, time.perf_counter() - start)
             def readqueue():
-                for x in queue:
-                    adapted(*x)
+                for (enqueued, x) in queue:
+                    run(enqueued, x)
                     x = None
-            t = Thread(target=readqueue)
+                    while reentrant:
+                        run(*reentrant.popleft())
+            t = Thread(target=readqueue, daemon=_DAEMON_HANDLER_THREADS)
             t.start()
-            self._call_ = lambda *args: queue.put(args)
+            def call(*args):
+                if current_thread() is t:
+                    reentrant.append((time.perf_counter(), args))
+                else:
+                    queue.put((time.perf_counter(), args))
+            self._call_ = call
             def drop():
                 queue.close()
-                t.join()
+                # the callback may also undeclare its own subscription, in which case the thread ends once it returns
+                if current_thread() is not t:
+                    t.join()
                 _drop_()
             self._drop_ = drop
         else:
-            self._call_ = _call_
+            self._call_ = adapted
             self._drop_ = _drop_
 
     @property
@@ -121,6 +219,54 @@ class Closure(IClosure, Generic[In, Out]):
     def drop(self) -> Callable[[], None]:
         return self._drop_
 
+_CLOSED = object()
+_LOOP_LOCK = Lock()
+_LOOP = None
+
+def _stop_internal_loop():
+    "Stops the event loop of ``_internal_loop`` if it was started, the next coroutine callback starting a new one."
+    global _LOOP
+    with _LOOP_LOCK:
+        loop, _LOOP = _LOOP, None
+    if loop is not None:
+        loop.call_soon_threadsafe(loop.stop)
+
+def _internal_loop() -> asyncio.AbstractEventLoop:
+    "The event loop running coroutine callbacks when no other loop was provided, started on first use."
+    global _LOOP
+    with _LOOP_LOCK:
+        if _LOOP is None:
+            _LOOP = asyncio.new_event_loop()
+            Thread(target=_LOOP.run_forever, name="zenoh-asyncio", daemon=True).start()
+        return _LOOP
+
+def _coroutine_closure(callback: Callable[[In], Any], loop: asyncio.AbstractEventLoop = None, on_error: CallbackError = None) -> Closure:
+    "Makes a closure that runs the coroutine function ``callback`` as a task of ``loop``, or of an internal event loop."
+    loop = _internal_loop() if loop is None else loop
+    def report(task: asyncio.Task):
+        if not task.cancelled() and task.exception() is not None:
+            on_error(task.exception())
+    def start(x):
+        task = loop.create_task(callback(x))
+        if on_error is not None:
+            task.add_done_callback(report)
+    def call(x):
+        loop.call_soon_threadsafe(start, x)
+    return Closure(call)
+
+def _executor_closure(callback: Callable[[In], Any], executor: concurrent.futures.Executor, on_error: CallbackError = None) -> Closure:
+    "Makes a closure that submits each call of ``callback`` to ``executor``, passing the exceptions they raise to ``on_error``, or printing them."
+    def report(future: concurrent.futures.Future):
+        if not future.cancelled() and future.exception() is not None:
+            e = future.exception()
+            if on_error is not None:
+                on_error(e)
+            else:
+                traceback.print_exception(type(e), e, e.__traceback__)
+    def call(x):
+        executor.submit(callback, x).add_done_callback(report)
+    return Closure(call)
+
 IntoHandler = Union[IHandler[In, Out, Receiver], IClosure[In, Out],  Tuple[IClosure, Receiver], Tuple[CallbackCall,CallbackDrop, Receiver], Tuple[CallbackCall,CallbackDrop], CallbackCall]
 class Handler(IHandler, Generic[In, Out, Receiver]):
     """
@@ -130,7 +276,8 @@ class Handler(IHandler, Generic[In, Out, Receiver]):
         a) ``input`` is already an instance of ``Closure`` or ``Handler`` where ``input.closure`` is an instance of ``Closure``
         b) ``prevent_direct_calls`` is set to ``False``
     """
-    def __init__(self, input: IntoHandler[In, Out, Receiver], type_adaptor: Callable[[Any], In] = None, prevent_direct_calls = True):
+    def __init__(self, input: IntoHandler[In, Out, Receiver], type_adaptor: Callable[[Any], In] = None, prevent_direct_calls = True, profile: QueueProfile = None,
+                 on_error: CallbackError = None):
         self._receiver_ = None
         if isinstance(input, IHandler):
             self._receiver_ = input.receiver
@@ -147,7 +294,7 @@ class Handler(IHandler, Generic[In, Out, Receiver]):
                 self._closure_ = input
         else:
             self._closure_ = input
-        self._closure_ = Closure(self._closure_, type_adaptor, prevent_direct_calls and not isinstance(self._closure_, Closure))
+        self._closure_ = Closure(self._closure_, type_adaptor, prevent_direct_calls and not isinstance(self._closure_, Closure), profile, on_error)
 
     @property
     def closure(self) -> IClosure[In, Out]:
@@ -198,6 +345,9 @@ class Queue(IHandler[In, None, 'Queue'], Generic[In]):
     callback that appends elements to the queue.
 
     Can be bounded by passing a maximum size as ``bound``.
+
+    It may be iterated over with ``for``, as well as with ``async for`` from a coroutine, in which case each element
+    is waited for on the event loop's default executor, so that the event loop is never blocked.
     """
     def __init__(self, bound: int = None):
         self._inner_ = _Queue(bound)
@@ -227,28 +377,112 @@ class Queue(IHandler[In, None, 'Queue'], Generic[In]):
 
         Raises a ``StopIteration`` exception if the queue was closed before the timeout ran out,
         this allows using the Queue as an iterator in for-loops.
-        Raises a ``TimeoutError`` if the timeout ran out.
+        Raises a ``RecvTimeoutError`` (a ``TimeoutError``) if the timeout ran out,
+        and a ``ValueError`` if it is negative.
+        """
+        return self._inner_.get(timeout)
+
+    def recv(self, timeout: float = None) -> In:
+        """
+        Waits for one element, for up to ``timeout`` seconds if set, without holding the GIL:
+        the blocking counterpart of ``try_recv``, for polling loops.
+
+        Raises a ``RecvTimeoutError``, a subclass of ``TimeoutError``, if ``timeout`` ran out,
+        and a ``StopIteration`` exception if the queue was closed and emptied.
         """
         return self._inner_.get(timeout)
+
+    def try_recv(self) -> Optional[In]:
+        """
+        Gets one element from the queue if one is available, returning ``None`` immediately otherwise,
+        for applications polling from a loop of their own.
+
+        Raises a ``StopIteration`` exception if the queue was closed and emptied.
+        """
+        return self._inner_.try_get()
     
     def close(self):
         return self._inner_.close()
+
+    @property
+    def capacity(self) -> Optional[int]:
+        "The number of elements the queue holds at most, or ``None`` if it is unbounded."
+        return self._inner_.capacity
+
+    @property
+    def dropped(self) -> int:
+        "The number of elements dropped so far for lack of room, which only ``RingChannel`` and dropping ``FifoChannel`` do."
+        return self._inner_.dropped
     
     def get_remaining(self, timeout: float = None) -> List[In]:
         """
         Awaits the closing of the queue, returning the remaining queued values in a list.
         The values inserted into the queue up until this happens will be available through ``get``.
 
-        Raises a ``TimeoutError`` if the timeout in seconds provided was exceeded before closing,
+        Raises a ``RecvTimeoutError`` (a ``TimeoutError``) if the timeout in seconds provided was exceeded before closing,
         whose ``args[0]`` will contain the elements that were collected before timing out.
         """
-        return self._inner_.get_remaining()
+        return self._inner_.get_remaining(timeout)
 
     def __iter__(self):
         return self
     def __next__(self):
         return self.get()
 
+    def _get_or_closed_(self):
+        # `StopIteration` can't go through a future
+        try:
+            return self.get()
+        except StopIteration:
+            return _CLOSED
+    def __aiter__(self):
+        return self
+    async def __anext__(self):
+        value = await asyncio.get_running_loop().run_in_executor(None, self._get_or_closed_)
+        if value is _CLOSED:
+            raise StopAsyncIteration
+        return value
+
+class RingChannel(Queue[In]):
+    """
+    A ``Queue`` keeping only the ``capacity`` most recent elements: putting on a full ring drops its oldest element
+    rather than waiting, so that a slow consumer always gets the latest values, such as those of sensor streams,
+    without ever pushing back on the network. ``dropped`` counts the elements dropped so far.
+
+    :Example:
+
+    >>> sub = s.declare_subscriber('sensors/temperature', zenoh.RingChannel(1))
+    >>> latest = sub.receiver.recv()
+    """
+    def __init__(self, capacity: int):
+        if capacity < 1:
+            raise ValueError("a `RingChannel`'s capacity must be at least 1")
+        self._inner_ = _Queue(capacity, "drop_oldest")
+
+class FifoChannel(Queue[In]):
+    """
+    A ``Queue`` holding up to ``capacity`` elements, handed out in the order they were put.
+
+    ``when_full`` decides what putting on a full channel does: with ``"block"``, the default, it waits for room,
+    which holds the reception of further samples or queries back until some are consumed, pushing back on the network;
+    with ``"drop"``, the element being put is dropped instead, and counted by ``dropped``, so that the network is never
+    held back, at the cost of losing the newest elements under load. Dropped queries are finalized right away,
+    their queriers receiving no reply from this queryable.
+
+    :Example:
+
+    >>> sub = s.declare_subscriber('demo/**', zenoh.FifoChannel(1024, when_full="drop"))
+    >>> for sample in sub:
+    ...     print(sample.key_expr)
+    """
+    def __init__(self, capacity: int, when_full: str = "block"):
+        if capacity < 1:
+            raise ValueError("a `FifoChannel`'s capacity must be at least 1")
+        if when_full not in ("block", "drop"):
+            raise ValueError(f"unknown `when_full` policy {when_full!r}, expected 'block' or 'drop'")
+        self.when_full = when_full
+        self._inner_ = _Queue(capacity, "block" if when_full == "block" else "drop_newest")
+
 if __name__ == "__main__":
     def get(collector):
         import time
@@ -11,9 +11,168 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from typing import Union
-from .zenoh import _Config
+from typing import Union, Dict, Any, List, Tuple, Optional, Callable
+from .zenoh import _Config, _TlsConfig
 import json
+import ssl
+import tempfile
+import hashlib
+import uuid
+import os
+import weakref
+
+WHATAMI = ("router", "peer", "client")
+
+def _validate_whatami_matcher(matcher: str) -> str:
+    "Checks that ``matcher`` is a ``|``-separated list of ``router``, ``peer`` or ``client``, possibly empty."
+    if not isinstance(matcher, str):
+        raise TypeError("whatami matchers must be `|`-separated strings, such as \"router|peer\"")
+    for whatami in matcher.split("|") if matcher else []:
+        if whatami not in WHATAMI:
+            raise ValueError(f"Couldn't parse `{matcher}` into a whatami matcher: must be a `|`-separated list of `peer`, `client` or `router`")
+    return matcher
+
+class GossipScoutingConfig:
+    """
+    A typed accessor to the gossip scouting section of a ``Config``, obtained through ``Config.gossip``.
+
+    Every assignment is validated before being written into the underlying configuration.
+    """
+    def __init__(self, config: 'Config'):
+        self._config_ = config
+
+    def _get_(self, key: str):
+        return json.loads(self._config_.get_json(f"{GOSSIP_KEY}/{key}"))
+
+    def _set_(self, key: str, value):
+        self._config_.insert_json5(f"{GOSSIP_KEY}/{key}", json.dumps(value))
+
+    @property
+    def enabled(self) -> bool:
+        "Whether gossip scouting is enabled."
+        return self._get_("enabled")
+    @enabled.setter
+    def enabled(self, enabled: bool):
+        if not isinstance(enabled, bool):
+            raise TypeError("`enabled` must be a `bool`")
+        self._set_("enabled", enabled)
+
+    @property
+    def multihop(self) -> bool:
+        "Whether gossip information is forwarded beyond direct neighbours."
+        return self._get_("multihop")
+    @multihop.setter
+    def multihop(self, multihop: bool):
+        if not isinstance(multihop, bool):
+            raise TypeError("`multihop` must be a `bool`")
+        self._set_("multihop", multihop)
+
+    def autoconnect(self, mode: str) -> str:
+        """
+        Returns the kinds of nodes a node running in ``mode`` will automatically connect to
+        when discovering them through gossip, as a ``|``-separated string such as ``"router|peer"``.
+        """
+        if mode not in WHATAMI:
+            raise ValueError(f"`mode` must be one of {WHATAMI}")
+        return self._get_(f"autoconnect/{mode}")
+
+    def set_autoconnect(self, mode: str, matcher: str):
+        """
+        Sets the kinds of nodes a node running in ``mode`` will automatically connect to
+        when discovering them through gossip. ``matcher`` is a ``|``-separated list of
+        ``router``, ``peer`` or ``client``, where ``""`` disables autoconnection.
+        """
+        if mode not in WHATAMI:
+            raise ValueError(f"`mode` must be one of {WHATAMI}")
+        self._set_(f"autoconnect/{mode}", _validate_whatami_matcher(matcher))
+
+class RetryPolicy:
+    """
+    How often connecting to an endpoint should be retried: the first retry happens after ``period_init_ms``,
+    the period is then multiplied by ``period_increase_factor`` after each failure, up to ``period_max_ms``.
+    """
+    def __init__(self, period_init_ms: int = 1000, period_max_ms: int = 4000, period_increase_factor: float = 2.0):
+        if period_init_ms <= 0 or period_max_ms < period_init_ms:
+            raise ValueError("retry periods must be positive, with `period_max_ms >= period_init_ms`")
+        if period_increase_factor < 1:
+            raise ValueError("`period_increase_factor` must be greater or equal to 1")
+        self.period_init_ms = period_init_ms
+        self.period_max_ms = period_max_ms
+        self.period_increase_factor = period_increase_factor
+
+    def _endpoint_config_(self) -> str:
+        return f"retry_period_init_ms={self.period_init_ms};retry_period_max_ms={self.period_max_ms};retry_period_increase_factor={self.period_increase_factor}"
+
+IntoConnectEndpoint = Union[str, Tuple[str, RetryPolicy]]
+
+class TlsConfig(_TlsConfig):
+    """
+    The TLS material and verification settings used by ``tls/`` and ``quic/`` endpoints, described the way
+    Python's ``ssl`` module does, to be applied to a configuration through ``Config.set_tls``.
+
+    :param cafile: The CA bundle used to verify the remote certificates
+    :param certfile: The certificate chain this node presents, both when listening and, if requested, when connecting
+    :param keyfile: The private key matching ``certfile``
+    :param check_hostname: Whether the remote certificate's name must match the endpoint's host (SNI)
+    :param client_auth: Whether the nodes connecting to this node's listeners must present a certificate
+    """
+    @classmethod
+    def from_ssl_context(cls, context: ssl.SSLContext, cafile: str = None, certfile: str = None, keyfile: str = None) -> 'TlsConfig':
+        """
+        Builds a ``TlsConfig`` reusing the verification settings of an ``ssl.SSLContext``.
+
+        Since ``ssl.SSLContext`` doesn't expose the files it was loaded from, they must still be passed explicitly.
+        The context's ``protocol`` tells how its ``verify_mode`` applies:
+
+        - on ``ssl.PROTOCOL_TLS_CLIENT`` contexts, the remote certificates are verified, and ``ssl.CERT_NONE`` is refused,
+          since zenoh always verifies them;
+        - on ``ssl.PROTOCOL_TLS_SERVER`` contexts, ``ssl.CERT_REQUIRED`` enables client authentication, ``ssl.CERT_NONE``
+          leaves it disabled, and ``ssl.CERT_OPTIONAL`` is refused, since zenoh can't make it optional.
+
+        Raises ``ValueError`` on the other settings zenoh can't honour, such as other protocols,
+        certificate revocation checks, or a ``maximum_version`` below TLS 1.2.
+        """
+        return super().from_ssl_context(context, cafile, certfile, keyfile)
+
+def _check_endpoint(endpoint: str) -> str:
+    "Rejects the endpoints whose protocol the bindings know zenoh can't handle, with a helpful message."
+    if not isinstance(endpoint, str):
+        raise TypeError("endpoints must be strings, such as \"tcp/127.0.0.1:7447\"")
+    if endpoint.startswith("wss/"):
+        raise ValueError("`wss/` endpoints are not supported: zenoh's WebSocket link doesn't handle TLS, use `ws/` behind a TLS-terminating proxy instead")
+    return endpoint
+
+EndpointResolver = Callable[[str], List[str]]
+_RESOLVERS: Dict[str, EndpointResolver] = dict()
+
+def register_endpoint_resolver(scheme: str, resolver: EndpointResolver):
+    """
+    Registers a resolver for the connect endpoints starting with ``<scheme>/``.
+
+    When a session is opened, and whenever it loses all its transports, every such endpoint is replaced by the locators
+    returned by ``resolver``, which receives the part of the endpoint that follows ``<scheme>/``.
+    Passing ``None`` as ``resolver`` unregisters the scheme.
+
+    :Example:
+
+    >>> import zenoh
+    >>> zenoh.register_endpoint_resolver("dns-sd", lambda service: ["tcp/192.168.1.10:7447"])
+    >>> conf = zenoh.Config()
+    >>> conf.set_connect_endpoints(["dns-sd/zenoh._tcp"])
+    >>> s = zenoh.open(conf)
+    """
+    if resolver is None:
+        _RESOLVERS.pop(scheme, None)
+    else:
+        _RESOLVERS[scheme] = resolver
+
+def _remove_files(paths: List[str]):
+    "Removes the temporary files a configuration wrote, at most once, as ``paths`` is emptied."
+    while paths:
+        try:
+            os.unlink(paths.pop())
+        except FileNotFoundError:
+            pass
 
 class Config(_Config):
     """
@@ -24,13 +183,41 @@ class Config(_Config):
     def __init__(self):
         super().__init__()
     @staticmethod
+    def _upgrade_(inner: _Config) -> 'Config':
+        if isinstance(inner, Config):
+            return inner
+        return _Config.__new__(Config, inner)
+    def __copy__(self) -> 'Config':
+        """
+        Returns a copy of the configuration.
+        Note that copying the configuration of a session (see ``Session.config``) keeps it bound to that session.
+        """
+        return _Config.__new__(Config, self)
+    def __deepcopy__(self, memo) -> 'Config':
+        """
+        Returns a copy of the configuration, detached from any session.
+        """
+        return Config._upgrade_(super().__deepcopy__(memo))
+    def _own_file_(self, path: str):
+        "Has the temporary file at ``path`` removed once the configuration, or the session opened with it, is done with it."
+        if '_owned_files_' not in self.__dict__:
+            self._owned_files_ = []
+            self._owned_files_finalizer_ = weakref.finalize(self, _remove_files, self._owned_files_)
+        self._owned_files_.append(path)
+    def _take_files_(self) -> List[str]:
+        "Hands the temporary files the configuration owns over to the caller, such as the session opened with it."
+        if '_owned_files_' not in self.__dict__:
+            return []
+        self.__dict__.pop('_owned_files_finalizer_').detach()
+        return self.__dict__.pop('_owned_files_')
+    @staticmethod
     def from_file(filename: str):
         """
         Reads the configuration from a file.
         The file's extension must be json, json5 or yaml.
         """
         c = super(Config, Config).from_file(filename)
-        return c
+        return Config._upgrade_(c)
     @staticmethod
     def from_obj(obj):
         """
@@ -39,6 +226,14 @@ class Config(_Config):
         c = Config.from_json5(json.dumps(obj))
         return c
     @staticmethod
+    def from_dict(d: Dict[str, Any]):
+        """
+        Reads the configuration from a nested dictionary, such as one returned by ``Config.to_dict``.
+        """
+        if not isinstance(d, dict):
+            raise TypeError("`Config.from_dict` expects a `dict`")
+        return Config.from_obj(d)
+    @staticmethod
     def from_json5(json: str):
         """
         Reads the configuration from a JSON5 string.
@@ -46,8 +241,30 @@ class Config(_Config):
         JSON5 is a superset of JSON, so any JSON string is a valid input for this function.
         """
         c =  super(Config, Config).from_json5(json)
-        return c
+        return Config._upgrade_(c)
     
+    @staticmethod
+    def test(network: str = None) -> 'Config':
+        """
+        Returns a configuration for hermetic tests: a peer that neither listens, connects nor scouts,
+        and thus opens no sockets and never waits on timers.
+
+        The configuration's zenoh id is derived from ``network`` (or random, if ``network`` isn't set),
+        so that sessions opened with ``share_runtime=True`` on the configurations of a same ``network`` share a runtime,
+        and thus see each other's publications, queryables and subscribers, while those of other networks stay apart.
+        See ``zenoh.testing.Network``, which does just that.
+        """
+        digest = uuid.uuid4().hex if network is None else hashlib.sha256(network.encode()).hexdigest()
+        # zenoh ids are read as numbers: a leading zero would be dropped, and the id wouldn't round-trip
+        zid = "1" + digest[1:32]
+        return Config.from_obj({
+            "id": zid,
+            "mode": "peer",
+            "listen": {"endpoints": []},
+            "connect": {"endpoints": []},
+            "scouting": {"multicast": {"enabled": False}, "gossip": {"enabled": False}},
+        })
+
     def get_json(self, path: str) -> str:
         """
         Returns the part of the configuration at ``path``,
@@ -57,6 +274,171 @@ class Config(_Config):
         """
         return super().get_json(path)
     
+    def to_json(self) -> str:
+        """
+        Returns the whole configuration, in a JSON-serialized form.
+        """
+        return super().to_json()
+
+    def to_dict(self) -> Dict[str, Any]:
+        """
+        Returns the whole configuration as a nested dictionary.
+
+        The returned dictionary is a copy: modifying it won't affect the configuration,
+        but it may be turned back into one using ``Config.from_dict``.
+        """
+        return json.loads(self.to_json())
+    
+    def set_connect_endpoints(self, endpoints: List[IntoConnectEndpoint], timeout_ms: Optional[int] = None, exit_on_failure: Optional[bool] = None):
+        """
+        Sets the endpoints to connect to, by order of priority.
+
+        In ``client`` mode, the endpoints are tried in order until one succeeds, making the first one the primary router,
+        and the following ones its backups. Each endpoint may be paired with a :class:`RetryPolicy`,
+        which is then used for this endpoint only. ``Session.info().active_endpoint()`` tells which one is in use.
+
+        ``ws/<address>:<port>`` endpoints may be used to reach routers that are only exposed through WebSocket,
+        such as behind HTTP-only ingresses.
+
+        :param endpoints: The endpoints, as locators or ``(locator, RetryPolicy)`` pairs
+        :param timeout_ms: How long to try connecting before giving up, ``-1`` meaning forever
+        :param exit_on_failure: Whether opening the session should fail if no endpoint could be connected to
+
+        :Example:
+
+        >>> import zenoh
+        >>> conf = zenoh.Config()
+        >>> conf.set_connect_endpoints(["tcp/10.0.0.1:7447", ("tcp/10.0.0.2:7447", zenoh.RetryPolicy(period_max_ms=10000))])
+        """
+        locators = []
+        for endpoint in endpoints:
+            if isinstance(endpoint, tuple):
+                locator, retry = endpoint
+                separator = ";" if "#" in locator else "#"
+                locators.append(f"{_check_endpoint(locator)}{separator}{retry._endpoint_config_()}")
+            else:
+                locators.append(_check_endpoint(endpoint))
+        self.insert_json5(CONNECT_KEY, json.dumps(locators))
+        if timeout_ms is not None:
+            self.insert_json5("connect/timeout_ms", json.dumps(timeout_ms))
+        if exit_on_failure is not None:
+            self.insert_json5("connect/exit_on_failure", json.dumps(exit_on_failure))
+
+    def set_tls(self, tls: TlsConfig):
+        """
+        Applies a :class:`TlsConfig` to the TLS settings of the configuration.
+
+        :Example:
+
+        >>> import ssl, zenoh
+        >>> conf = zenoh.Config()
+        >>> context = ssl.create_default_context()
+        >>> conf.set_tls(zenoh.TlsConfig.from_ssl_context(context, cafile="ca.pem"))
+        >>> conf.set_connect_endpoints(["tls/router.example.com:7447"])
+        """
+        super().set_tls(tls)
+
+    def set_usrpwd_auth(self, user: str = None, password: str = None, dictionary: Dict[str, str] = None, dictionary_file: str = None):
+        """
+        Configures user/password authentication.
+
+        ``user`` and ``password`` are the credentials this node presents when connecting,
+        while the dictionary lists the credentials accepted from the nodes connecting to it.
+        The dictionary may be given in memory, in which case it is written to a private temporary file,
+        as zenoh only reads it from a file. That file is removed once the session opened with this configuration
+        is closed, or once the configuration is garbage collected if no session was opened with it.
+        """
+        if (user is None) != (password is None):
+            raise ValueError("`user` and `password` must be provided together")
+        if dictionary is not None and dictionary_file is not None:
+            raise ValueError("`dictionary` and `dictionary_file` are mutually exclusive")
+        if dictionary is not None:
+            for u in dictionary:
+                if ":" in u:
+                    raise ValueError(f"user names may not contain `:` (found {u!r})")
+            # mkstemp creates the file readable and writable by its owner only
+            fd, dictionary_file = tempfile.mkstemp(prefix="zenoh-usrpwd-")
+            self._own_file_(dictionary_file)
+            with os.fdopen(fd, "w") as f:
+                for u, p in dictionary.items():
+                    f.write(f"{u}:{p}\n")
+        settings = {"user": user, "password": password, "dictionary_file": dictionary_file}
+        for key, value in settings.items():
+            if value is not None:
+                self.insert_json5(f"{AUTH_KEY}/usrpwd/{key}", json.dumps(value))
+
+    def set_pubkey_auth(self, public_key_pem: str = None, private_key_pem: str = None, public_key_file: str = None,
+                        private_key_file: str = None, known_keys_file: str = None, key_size: int = None):
+        """
+        Configures public key authentication.
+
+        The key pair may be given either in memory, as PEM strings, or as paths to PEM files.
+        ``known_keys_file`` lists the public keys accepted from the nodes connecting to this one.
+        """
+        if public_key_pem is not None and public_key_file is not None:
+            raise ValueError("`public_key_pem` and `public_key_file` are mutually exclusive")
+        if private_key_pem is not None and private_key_file is not None:
+            raise ValueError("`private_key_pem` and `private_key_file` are mutually exclusive")
+        settings = {
+            "public_key_pem": public_key_pem,
+            "private_key_pem": private_key_pem,
+            "public_key_file": public_key_file,
+            "private_key_file": private_key_file,
+            "known_keys_file": known_keys_file,
+            "key_size": key_size,
+        }
+        for key, value in settings.items():
+            if value is not None:
+                self.insert_json5(f"{AUTH_KEY}/pubkey/{key}", json.dumps(value))
+
+    def set_listen_endpoints(self, endpoints: List[str]):
+        """
+        Sets the endpoints to listen on.
+
+        Besides network locators such as ``tcp/0.0.0.0:7447``, this accepts ``unixsock-stream/<path>`` locators
+        to communicate with other processes on the same host through a unix socket, without going through TCP,
+        and ``ws/<address>:<port>`` locators to accept WebSocket connections.
+        """
+        self.insert_json5(LISTEN_KEY, json.dumps([_check_endpoint(endpoint) for endpoint in endpoints]))
+
+    def resolve_endpoints(self) -> bool:
+        """
+        Replaces the connect endpoints whose scheme has a resolver registered through
+        ``register_endpoint_resolver`` by the locators it returns, returning whether any was.
+
+        This is done automatically when opening a session, and again whenever the session loses all its transports:
+        should the endpoints then resolve to other locators, the session reconnects through them, see ``Session.rebuild``.
+        """
+        if not _RESOLVERS:
+            return False
+        endpoints = json.loads(self.get_json(CONNECT_KEY))
+        resolved = []
+        for endpoint in endpoints:
+            scheme, _, address = endpoint.partition("/")
+            resolver = _RESOLVERS.get(scheme)
+            if resolver is None:
+                resolved.append(endpoint)
+            else:
+                resolved.extend(resolver(address))
+        if resolved == endpoints:
+            return False
+        self.insert_json5(CONNECT_KEY, json.dumps(resolved))
+        return True
+
+    @property
+    def gossip(self) -> GossipScoutingConfig:
+        """
+        A typed accessor to the gossip scouting options.
+
+        :Example:
+
+        >>> import zenoh
+        >>> conf = zenoh.Config()
+        >>> conf.gossip.enabled = True
+        >>> conf.gossip.set_autoconnect("peer", "router|peer")
+        """
+        return GossipScoutingConfig(self)
+
     def insert_json5(self, path: str, value: str) -> str:
         """
         Inserts the provided value (read as a JSON string) at the given path in the configuration.
@@ -67,4 +449,6 @@ class Config(_Config):
 
 MODE_KEY = "mode"
 CONNECT_KEY = "connect/endpoints"
-LISTEN_KEY = "listen/endpoints"
\ No newline at end of file
+LISTEN_KEY = "listen/endpoints"
+GOSSIP_KEY = "scouting/gossip"
+AUTH_KEY = "transport/auth"
@@ -0,0 +1,147 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+End-to-end payload encryption, for data that must stay confidential even from the routers it goes through.
+"""
+from typing import Any, Callable, Optional
+
+from .zenoh import seal as _seal, unseal as _unseal, ZError
+from .closures import IntoHandler, Handler, Closure
+from .enums import Priority, CongestionControl, Reliability, QueryConsolidation, QueryTarget, SampleKind
+from .keyexpr import KeyExpr, IntoKeyExpr, IntoSelector
+from .value import IntoValue, Value, Sample, Reply
+from .session import Session, Subscriber
+
+KeyProvider = Callable[[str], bytes]
+
+class SealedReply(Reply):
+    "A reply whose sample was unsealed by a ``SealingSession``."
+    def __new__(cls, reply: Reply, sample: Sample):
+        this = super().__new__(cls, reply)
+        this._sample_ = sample
+        return this
+
+    @property
+    def ok(self) -> Sample:
+        return self._sample_
+
+class SealingPublisher:
+    "A ``Publisher`` sealing the payloads it publishes, as returned by ``SealingSession.declare_publisher``."
+    def __init__(self, sealer: 'SealingSession', publisher):
+        self._sealer_ = sealer
+        self._publisher_ = publisher
+
+    @property
+    def key_expr(self) -> KeyExpr:
+        return self._publisher_.key_expr
+
+    def put(self, value: IntoValue, encoding=None):
+        self._publisher_.put(self._sealer_.seal(self.key_expr, value, encoding))
+
+    def delete(self):
+        self._publisher_.delete()
+
+    def undeclare(self):
+        self._publisher_.undeclare()
+
+class SealingSession:
+    """
+    A wrapper around ``session`` encrypting the payloads it puts, and decrypting those its subscribers and queries receive.
+
+    ``key_provider`` is called with the key expression of each payload to seal or unseal, and must return the 32 bytes key
+    to use for it, which lets each part of the key space have keys of its own. It is called once per payload: providers
+    that look keys up in a slow store should cache them, for example with ``functools.lru_cache``.
+
+    Payloads are sealed with AES-256-GCM, in Rust and without holding the GIL. The key expression is authenticated along
+    with the payload, so sealed payloads can't be replayed on other key expressions; as it is the one the application sees,
+    both sides must map their key spaces the same way. Encodings, timestamps and key expressions themselves are sent in clear.
+
+    Samples that fail to unseal, such as ones that weren't sealed or were sealed with another key, are dropped;
+    ``on_error`` is called with each such sample and the error, if set.
+
+    :Example:
+
+    >>> import os, zenoh, zenoh.crypto
+    >>> key = os.urandom(32)
+    >>> s = zenoh.crypto.SealingSession(zenoh.open({}), lambda key_expr: key)
+    >>> sub = s.declare_subscriber('demo/secret/**', lambda sample: print(sample.payload))
+    >>> s.put('demo/secret/plans', 'Attack at dawn')
+    """
+    def __init__(self, session: Session, key_provider: KeyProvider, on_error: Callable[[Sample, ZError], Any] = None):
+        self.session = session
+        self.key_provider = key_provider
+        self.on_error = on_error
+
+    def seal(self, keyexpr: IntoKeyExpr, value: IntoValue, encoding=None) -> Value:
+        "Returns the sealed counterpart of ``Value(value, encoding)``, for publication on ``keyexpr``."
+        value = Value(value, encoding)
+        key_expr = str(KeyExpr(keyexpr))
+        return Value.new(_seal(self.key_provider(key_expr), key_expr, value.payload), value.encoding)
+
+    def unseal(self, sample: Sample) -> Optional[Sample]:
+        "Returns the unsealed counterpart of ``sample``, or ``None`` if it fails to unseal. Deletions are passed through."
+        if sample.kind == SampleKind.DELETE():
+            return sample
+        key_expr = str(sample.key_expr)
+        try:
+            payload = _unseal(self.key_provider(key_expr), key_expr, sample.payload)
+        except ZError as e:
+            if self.on_error is not None:
+                self.on_error(sample, e)
+            return None
+        return Sample(sample.key_expr, Value.new(payload, sample.encoding), sample.kind, sample.timestamp)
+
+    def put(self, keyexpr: IntoKeyExpr, value: IntoValue, encoding=None,
+            priority: Priority = None, congestion_control: CongestionControl = None):
+        "Seals ``value`` and puts it, see ``Session.put``."
+        self.session.put(keyexpr, self.seal(keyexpr, value, encoding), priority=priority, congestion_control=congestion_control)
+
+    def delete(self, keyexpr: IntoKeyExpr, priority: Priority = None, congestion_control: CongestionControl = None):
+        "See ``Session.delete``: deletions carry no payload to seal."
+        self.session.delete(keyexpr, priority=priority, congestion_control=congestion_control)
+
+    def declare_publisher(self, keyexpr: IntoKeyExpr, priority: Priority = None, congestion_control: CongestionControl = None) -> SealingPublisher:
+        "Declares a publisher sealing the payloads it publishes, see ``Session.declare_publisher``."
+        return SealingPublisher(self, self.session.declare_publisher(keyexpr, priority, congestion_control))
+
+    def declare_subscriber(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any], reliability: Reliability = None) -> Subscriber:
+        "Declares a subscriber whose ``handler`` receives unsealed samples, see ``Session.declare_subscriber``."
+        handler = Handler(handler)
+        closure = handler.closure
+        def call(sample: Sample):
+            sample = self.unseal(sample)
+            if sample is not None:
+                closure.call(sample)
+        subscriber = self.session.declare_subscriber(keyexpr, Closure((call, closure.drop)), reliability)
+        subscriber.receiver = handler.receiver
+        return subscriber
+
+    def get(self, selector: IntoSelector, handler: IntoHandler[Reply, Any, Any], consolidation: QueryConsolidation = None,
+            target: QueryTarget = None, value: IntoValue = None, timeout: float = None):
+        """
+        Sends a query, whose ``ok`` replies are unsealed before reaching ``handler``, see ``Session.get``.
+
+        ``value`` is sent in clear, as it isn't bound to a single key expression. Replies that fail to unseal are dropped.
+        """
+        handler = Handler(handler)
+        closure = handler.closure
+        def call(reply: Reply):
+            if reply.is_ok:
+                sample = self.unseal(reply.ok)
+                if sample is None:
+                    return
+                reply = SealedReply(reply, sample)
+            closure.call(reply)
+        self.session.get(selector, Closure((call, closure.drop)), consolidation, target, value, timeout=timeout)
+        return handler.receiver
@@ -11,14 +11,19 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from .zenoh import _Encoding, _SampleKind, _CongestionControl, _Priority, _Reliability, _QueryTarget, _QueryConsolidation
+from .zenoh import _Encoding, _SampleKind, _ChangeKind, _CongestionControl, _Priority, _Reliability, _Locality, _QueryTarget, _QueryConsolidation
 
 class Priority(_Priority):
     """
     The priority of a sending operation.
 
     They are ordered à la Linux priority:
-    ``Priority.REAL_TIME() < Priority.INTERACTIVE_HIGH() < Priority.INTERACTIVE_LOW() < Priority.DATA() < Priority.BACKGROUND()``
+    ``Priority.REAL_TIME() < Priority.INTERACTIVE_HIGH() < Priority.INTERACTIVE_LOW() < Priority.DATA_HIGH() < Priority.DATA() < Priority.DATA_LOW() < Priority.BACKGROUND()``
+
+    Each priority is carried by a queue of its own in the transports, the queues of higher priorities being emptied first,
+    so that latency-critical topics, such as control loops, are sent ahead of bulk data, such as logs or file transfers.
+    Publications default to ``Priority.DATA()``; ``Session.put``, ``Session.delete`` and ``Session.declare_publisher``
+    take a ``priority``, those of a publisher applying to all its publications.
     """
     def __new__(cls, inner: _SampleKind):
         return super().__new__(cls, inner)
@@ -73,6 +78,28 @@ class SampleKind(_SampleKind):
     def __ne__(self, other) -> bool:
         return not self.__eq__(other)
 
+class ChangeKind(_ChangeKind):
+    """
+    The kind of a ``Change``: PUT, PATCH or DELETE.
+
+    Zenoh doesn't currently emit patches: changes are only ever PUT or DELETE.
+    """
+    def __new__(cls, inner: _ChangeKind):
+        return super().__new__(cls, inner)
+    @staticmethod
+    def PUT() -> 'ChangeKind':
+        return ChangeKind(_ChangeKind.PUT)
+    @staticmethod
+    def PATCH() -> 'ChangeKind':
+        return ChangeKind(_ChangeKind.PATCH)
+    @staticmethod
+    def DELETE() -> 'ChangeKind':
+        return ChangeKind(_ChangeKind.DELETE)
+    def __eq__(self, other) -> bool:
+        return super().__eq__(other)
+    def __ne__(self, other) -> bool:
+        return not self.__eq__(other)
+
 class CongestionControl(_CongestionControl):
     """
     Defines the network's behaviour regarding a message when heavily congested.
@@ -193,6 +220,27 @@ class Reliability(_Reliability):
     def __ne__(self, other) -> bool:
         return not self.__eq__(other)
 
+class Locality(_Locality):
+    "Restricts the origin of the samples a subscriber receives."
+    def __new__(cls, inner: _Locality):
+        return super().__new__(cls, inner)
+    @staticmethod
+    def SESSION_LOCAL() -> 'Locality':
+        "Only samples published through the same session are received, which is handy for in-process event buses."
+        return Locality(_Locality.SESSION_LOCAL)
+    @staticmethod
+    def REMOTE() -> 'Locality':
+        "Only samples published by other sessions are received."
+        return Locality(_Locality.REMOTE)
+    @staticmethod
+    def ANY() -> 'Locality':
+        "Samples are received regardless of their origin, the default."
+        return Locality(_Locality.ANY)
+    def __eq__(self, other) -> bool:
+        return super().__eq__(other)
+    def __ne__(self, other) -> bool:
+        return not self.__eq__(other)
+
 class QueryTarget(_QueryTarget):
     def __new__(cls, inner: _QueryTarget):
         return super().__new__(cls, inner)
@@ -0,0 +1,72 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+File transfers over queryables, such as over-the-air updates, in chunks that let interrupted transfers resume.
+
+The files of a served directory are exposed below its key expression, each component of their relative path being a chunk
+of their key: ``serve_directory(s, 'ota/images', '/srv/images')`` serves ``/srv/images/v2/firmware.bin`` on
+``ota/images/v2/firmware.bin``. Querying a file's key with ``stat`` replies with a JSON object holding its ``size`` and
+``modified`` time, so that other clients can take part too, while querying it with ``offset=<n>;length=<m>`` replies with
+up to ``m`` of its bytes from offset ``n``.
+
+Both sides run in Rust, without holding the GIL.
+"""
+import os
+from typing import Union
+
+from .zenoh import serve_directory as _serve_directory, fetch as _fetch
+from .keyexpr import KeyExpr, IntoKeyExpr
+from .queryable import Queryable
+from .session import Session
+
+def serve_directory(session: Session, keyexpr: IntoKeyExpr, path: Union[str, os.PathLike], max_chunk_size: int = 1 << 20) -> Queryable:
+    """
+    Declares a queryable serving the files below the directory at ``path`` on ``keyexpr``,
+    in chunks of up to ``max_chunk_size`` bytes, whatever the size clients ask for.
+
+    Paths leading out of the directory, through ``..`` or symbolic links, are refused with error replies,
+    as are missing files. Wildcard queries are left unanswered. Files are read as queries arrive:
+    replacing a file while it is being fetched should be done by renaming a new one over it.
+
+    :Example:
+
+    >>> import zenoh, zenoh.filetransfer
+    >>> s = zenoh.open({})
+    >>> server = zenoh.filetransfer.serve_directory(s, 'ota/images', '/srv/images')
+    """
+    keyexpr = KeyExpr(keyexpr)
+    inner = _serve_directory(session, keyexpr, os.fspath(path), max_chunk_size)
+    return session._declarations_.track(Queryable(inner, None), "_inner_", "queryable", keyexpr, {"path": os.fspath(path)})
+
+def fetch(session: Session, keyexpr: IntoKeyExpr, dest: Union[str, os.PathLike], resume: bool = True,
+          chunk_size: int = 1 << 20, timeout: float = 10.0) -> int:
+    """
+    Downloads the file served on ``keyexpr`` to ``dest``, in chunks of up to ``chunk_size`` bytes, returning its size.
+
+    The chunks are written to ``dest`` with a ``.part`` suffix, which is renamed to ``dest`` once the file is complete.
+    With ``resume``, a transfer carries on from the chunks a previous attempt left in that file, provided the size and
+    modification time of the served file, kept with a ``.part.stat`` suffix meanwhile, didn't change in between;
+    otherwise it starts over.
+
+    Raises a ``ZError`` if a query fails or receives no reply within ``timeout`` seconds, the chunks received
+    so far being kept for the next attempt. Interrupting the call, with Ctrl-C for instance, returns right away,
+    but the transfer carries on in the background until it completes or fails.
+
+    :Example:
+
+    >>> import zenoh, zenoh.filetransfer
+    >>> s = zenoh.open({})
+    >>> size = zenoh.filetransfer.fetch(s, 'ota/images/v2/firmware.bin', '/tmp/firmware.bin')
+    """
+    return _fetch(session, KeyExpr(keyexpr), os.fspath(dest), resume, chunk_size, timeout)
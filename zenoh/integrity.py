@@ -0,0 +1,179 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+Payload checksums, for data that must reach the application intact, such as firmware or model files.
+
+The checksum travels in the sample's attachment, leaving its payload untouched: the ``CHECKSUM_ALGORITHM`` entry holds
+the algorithm's name in ASCII, and the ``CHECKSUM_DIGEST`` one the digest. Applications unaware of checksums thus
+receive the payloads as they were put, while the others use a ``ChecksumSession``, or ``checksum`` and ``verify``.
+"""
+import hashlib
+from typing import Any, Callable, Dict, Optional
+
+from .closures import IntoHandler, Handler, Closure
+from .enums import Priority, CongestionControl, Reliability, QueryConsolidation, QueryTarget, SampleKind
+from .keyexpr import KeyExpr, IntoKeyExpr, IntoSelector
+from .value import IntoValue, Value, Sample, Reply
+from .session import Session, Subscriber
+
+CHECKSUM_ALGORITHM = b"zenoh.checksum.algorithm"
+CHECKSUM_DIGEST = b"zenoh.checksum.digest"
+
+class IntegrityError(Exception):
+    "Raised by ``verify``, and passed to the ``on_mismatch`` callback of ``ChecksumSession``, when a payload doesn't match its checksum."
+    def __init__(self, message: str, sample: Sample = None):
+        super().__init__(message)
+        self.sample = sample
+
+def _digest(algorithm: str, payload: bytes) -> bytes:
+    if algorithm.startswith("xxh"):
+        try:
+            import xxhash
+        except ImportError:
+            raise ValueError(f"the `{algorithm}` checksum requires the `xxhash` package") from None
+        hasher = getattr(xxhash, algorithm, None)
+        if hasher is None:
+            raise ValueError(f"unknown checksum algorithm `{algorithm}`")
+        return hasher(payload).digest()
+    try:
+        return hashlib.new(algorithm, payload).digest()
+    except ValueError:
+        raise ValueError(f"unknown checksum algorithm `{algorithm}`") from None
+
+def checksum(value: IntoValue, encoding=None, algorithm: str = "sha256") -> Dict[bytes, bytes]:
+    """
+    Returns the attachment carrying the checksum of ``Value(value, encoding)``'s payload, to put along with it.
+
+    ``algorithm`` is the name of any ``hashlib`` algorithm, or of an ``xxhash`` one such as ``xxh64`` or ``xxh3_64``
+    if the ``xxhash`` package is installed, which is much faster for large payloads, but not tamper-proof.
+    """
+    digest = _digest(algorithm, Value(value, encoding).payload)
+    return {CHECKSUM_ALGORITHM: algorithm.encode("ascii"), CHECKSUM_DIGEST: digest}
+
+def verify(sample: Sample) -> Sample:
+    """
+    Returns ``sample``, raising an ``IntegrityError`` if its attachment carries no checksum,
+    or if its payload doesn't match it. Deletions are passed through.
+    """
+    if sample.kind == SampleKind.DELETE():
+        return sample
+    key_expr = sample.key_expr
+    attachment = sample.attachment
+    if CHECKSUM_ALGORITHM not in attachment or CHECKSUM_DIGEST not in attachment:
+        raise IntegrityError(f"the payload received on `{key_expr}` carries no checksum", sample)
+    try:
+        algorithm = attachment[CHECKSUM_ALGORITHM].decode("ascii")
+    except UnicodeDecodeError:
+        raise IntegrityError(f"the checksum of the payload received on `{key_expr}` is malformed", sample) from None
+    try:
+        expected = _digest(algorithm, sample.payload)
+    except ValueError as e:
+        raise IntegrityError(f"the payload received on `{key_expr}` can't be verified: {e}", sample) from None
+    if attachment[CHECKSUM_DIGEST] != expected:
+        raise IntegrityError(f"the payload received on `{key_expr}` doesn't match its {algorithm} checksum", sample)
+    return sample
+
+class ChecksumPublisher:
+    "A ``Publisher`` checksumming the payloads it publishes, as returned by ``ChecksumSession.declare_publisher``."
+    def __init__(self, session: 'ChecksumSession', publisher):
+        self._session_ = session
+        self._publisher_ = publisher
+
+    @property
+    def key_expr(self) -> KeyExpr:
+        return self._publisher_.key_expr
+
+    def put(self, value: IntoValue, encoding=None):
+        value = Value(value, encoding)
+        self._publisher_.put(value, attachment=checksum(value, algorithm=self._session_.algorithm))
+
+    def delete(self):
+        self._publisher_.delete()
+
+    def undeclare(self):
+        self._publisher_.undeclare()
+
+class ChecksumSession:
+    """
+    A wrapper around ``session`` attaching a checksum to the payloads it puts, and verifying those its subscribers
+    and queries receive.
+
+    Samples that fail verification, such as truncated or corrupted ones, or ones sent without checksum, are dropped
+    rather than handed to the application: ``on_mismatch`` is called with an ``IntegrityError`` for each, if set.
+
+    :Example:
+
+    >>> import zenoh, zenoh.integrity
+    >>> s = zenoh.integrity.ChecksumSession(zenoh.open({}), on_mismatch=lambda e: print("corrupted:", e.sample.key_expr))
+    >>> sub = s.declare_subscriber('demo/firmware/**', lambda sample: print(len(sample.payload)))
+    >>> s.put('demo/firmware/v2', open('firmware.bin', 'rb').read())
+    """
+    def __init__(self, session: Session, algorithm: str = "sha256", on_mismatch: Callable[[IntegrityError], Any] = None):
+        # unknown algorithms are reported now, rather than on the first put
+        _digest(algorithm, b"")
+        self.session = session
+        self.algorithm = algorithm
+        self.on_mismatch = on_mismatch
+
+    def verify(self, sample: Sample) -> Optional[Sample]:
+        "Returns the verified counterpart of ``sample``, or ``None`` if it fails verification."
+        try:
+            return verify(sample)
+        except IntegrityError as e:
+            if self.on_mismatch is not None:
+                self.on_mismatch(e)
+            return None
+
+    def put(self, keyexpr: IntoKeyExpr, value: IntoValue, encoding=None,
+            priority: Priority = None, congestion_control: CongestionControl = None):
+        "Checksums ``value`` and puts it, see ``Session.put``."
+        value = Value(value, encoding)
+        self.session.put(keyexpr, value, priority=priority, congestion_control=congestion_control,
+                         attachment=checksum(value, algorithm=self.algorithm))
+
+    def delete(self, keyexpr: IntoKeyExpr, priority: Priority = None, congestion_control: CongestionControl = None):
+        "See ``Session.delete``: deletions carry no payload to checksum."
+        self.session.delete(keyexpr, priority=priority, congestion_control=congestion_control)
+
+    def declare_publisher(self, keyexpr: IntoKeyExpr, priority: Priority = None, congestion_control: CongestionControl = None) -> ChecksumPublisher:
+        "Declares a publisher checksumming the payloads it publishes, see ``Session.declare_publisher``."
+        return ChecksumPublisher(self, self.session.declare_publisher(keyexpr, priority, congestion_control))
+
+    def declare_subscriber(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any], reliability: Reliability = None) -> Subscriber:
+        "Declares a subscriber whose ``handler`` only receives verified samples, see ``Session.declare_subscriber``."
+        handler = Handler(handler)
+        closure = handler.closure
+        def call(sample: Sample):
+            sample = self.verify(sample)
+            if sample is not None:
+                closure.call(sample)
+        subscriber = self.session.declare_subscriber(keyexpr, Closure((call, closure.drop)), reliability)
+        subscriber.receiver = handler.receiver
+        return subscriber
+
+    def get(self, selector: IntoSelector, handler: IntoHandler[Reply, Any, Any], consolidation: QueryConsolidation = None,
+            target: QueryTarget = None, value: IntoValue = None, timeout: float = None):
+        """
+        Sends a query, whose ``ok`` replies are verified before reaching ``handler``, see ``Session.get``.
+
+        ``value`` is sent without checksum. Replies that fail verification are dropped.
+        """
+        handler = Handler(handler)
+        closure = handler.closure
+        def call(reply: Reply):
+            if reply.is_ok and self.verify(reply.ok) is None:
+                return
+            closure.call(reply)
+        self.session.get(selector, Closure((call, closure.drop)), consolidation, target, value, timeout=timeout)
+        return handler.receiver
@@ -0,0 +1,121 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+The interfaces of the core zenoh entities, implemented by the native types as well as by ``zenoh.testing.MockSession``.
+
+Application code that only relies on them can be handed either, and thus be tested without a zenoh network.
+"""
+import abc
+from typing import Any
+
+from .closures import IntoHandler, Queue
+from .enums import Priority, CongestionControl, Reliability, QueryConsolidation, QueryTarget
+from .keyexpr import KeyExpr, IntoKeyExpr, IntoSelector
+from .value import IntoValue, Sample, Reply
+
+class IPublisher(abc.ABC):
+    "The interface of ``Publisher``."
+    @property
+    @abc.abstractmethod
+    def key_expr(self) -> KeyExpr:
+        "The key expression the publisher publishes on."
+        ...
+    @abc.abstractmethod
+    def put(self, value: IntoValue, encoding=None):
+        "Publishes ``value`` on the publisher's key expression."
+        ...
+    @abc.abstractmethod
+    def delete(self):
+        "Publishes a deletion of the publisher's key expression."
+        ...
+    @abc.abstractmethod
+    def undeclare(self):
+        "Stops the publisher."
+        ...
+
+class ISubscriber(abc.ABC):
+    "The interface of ``Subscriber``, whose ``receiver`` attribute holds its handler's receiver."
+    receiver: Any = None
+    @abc.abstractmethod
+    def undeclare(self):
+        "Undeclares the subscription."
+        ...
+    def __iter__(self):
+        "Iterates over the received samples, if the handler's receiver is iterable, as the ``Queue`` of ``ISession.subscribe`` is."
+        if self.receiver is None or not hasattr(self.receiver, '__iter__'):
+            raise TypeError("only subscribers whose handler's receiver is iterable, such as a `zenoh.Queue`, can be iterated over")
+        return iter(self.receiver)
+
+class IQueryable(abc.ABC):
+    "The interface of ``Queryable``, whose ``receiver`` attribute holds its handler's receiver."
+    receiver: Any = None
+    @abc.abstractmethod
+    def undeclare(self):
+        "Stops the queryable."
+        ...
+    def __iter__(self):
+        "Iterates over the received queries, if the handler's receiver is iterable, as that of a queryable declared without handler is."
+        if self.receiver is None or not hasattr(self.receiver, '__iter__'):
+            raise TypeError("only queryables whose handler's receiver is iterable, such as a `zenoh.Queue`, can be iterated over")
+        return iter(self.receiver)
+
+class ISession(abc.ABC):
+    "The interface of ``Session``, restricted to the operations most applications build upon."
+    @abc.abstractmethod
+    def put(self, keyexpr: IntoKeyExpr, value: IntoValue, encoding=None,
+            priority: Priority = None, congestion_control: CongestionControl = None):
+        "Sends ``value`` on ``keyexpr``."
+        ...
+    @abc.abstractmethod
+    def delete(self, keyexpr: IntoKeyExpr,
+               priority: Priority = None, congestion_control: CongestionControl = None):
+        "Deletes the values associated with the keys included in ``keyexpr``."
+        ...
+    @abc.abstractmethod
+    def get(self, selector: IntoSelector, handler: IntoHandler[Reply, Any, Any], consolidation: QueryConsolidation = None,
+            target: QueryTarget = None, value: IntoValue = None):
+        "Sends a query, passing its replies to ``handler`` and returning ``handler``'s receiver."
+        ...
+    @abc.abstractmethod
+    def declare_publisher(self, keyexpr: IntoKeyExpr, priority: Priority = None,
+                          congestion_control: CongestionControl = None) -> IPublisher:
+        "Declares a publisher on ``keyexpr``."
+        ...
+    @abc.abstractmethod
+    def declare_subscriber(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any],
+                           reliability: Reliability = None) -> ISubscriber:
+        "Declares a subscription on ``keyexpr``, passing its samples to ``handler``."
+        ...
+    def subscribe(self, keyexpr: IntoKeyExpr, bound: int = 256, reliability: Reliability = None) -> ISubscriber:
+        """
+        Declares a subscription on ``keyexpr`` whose samples are buffered in a ``Queue`` of up to ``bound`` samples,
+        for the returned subscriber to be iterated over, rather than calling back into Python from zenoh's threads:
+
+        >>> for sample in session.subscribe('demo/**'):
+        ...     print(sample.key_expr)
+
+        The iteration ends once the subscription is undeclared and its remaining samples consumed.
+        ``recv`` and ``try_recv`` also read from the queue. When it is full, the reception of further samples is
+        held back until some are consumed: this pushes back on the network, rather than losing samples or memory.
+        """
+        return self.declare_subscriber(keyexpr, Queue(bound), reliability)
+    @abc.abstractmethod
+    def declare_queryable(self, keyexpr: IntoKeyExpr, handler: IntoHandler['Query', Any, Any] = None,
+                          complete: bool = None) -> IQueryable:
+        "Declares a queryable on ``keyexpr``, passing its queries to ``handler``, or queueing them to be iterated over if omitted."
+        ...
+    @abc.abstractmethod
+    def close(self, drain_timeout: float = None) -> bool:
+        "Closes the session, waiting for up to ``drain_timeout`` seconds for its pending publications and callbacks if given."
+        ...
@@ -12,6 +12,7 @@
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
 from typing import Union, Dict
+import re
 from .zenoh import _KeyExpr, _Selector
 
 IntoKeyExpr = Union['KeyExpr', _KeyExpr, str]
@@ -73,7 +74,7 @@ class KeyExpr(_KeyExpr):
             return expr
         else:
             e = _KeyExpr.autocanonize(expr)
-            return KeyExpr(e.as_str())
+            return KeyExpr(e)
     
     def intersects(self, other: IntoKeyExpr) -> bool:
         """
@@ -94,6 +95,9 @@ class KeyExpr(_KeyExpr):
         Undeclares a key expression previously declared on the session.
         """
         super().undeclare(session)
+        declarations = getattr(session, "_declarations_", None)
+        if declarations is not None:
+            declarations.forget_keyexpr(self)
     
     def __eq__(self, other: IntoKeyExpr) -> bool:
         """
@@ -115,6 +119,145 @@ class KeyExpr(_KeyExpr):
     def __hash__(self):
         return super().__hash__()
 
+    def __copy__(self) -> 'KeyExpr':
+        return _KeyExpr.__new__(KeyExpr, self)
+
+    def __deepcopy__(self, memo) -> 'KeyExpr':
+        return _KeyExpr.__new__(KeyExpr, self)
+
+# Characters MQTT topic levels may contain, but zenoh chunks may not. `%` itself is escaped to keep the mapping reversible.
+_MQTT_ESCAPED = "%*$?#"
+
+def mqtt_topic_to_keyexpr(topic: str) -> KeyExpr:
+    """
+    Maps an MQTT topic name or filter onto the equivalent key expression.
+
+    The ``+`` and ``#`` wildcards become ``*`` and ``**``, empty levels (such as the one a leading ``/`` implies) become ``%``,
+    and the characters zenoh forbids in keys are percent-escaped, so that ``keyexpr_to_mqtt_topic`` can map the result back.
+    Raises a ``ValueError`` if ``topic`` isn't a valid MQTT topic.
+
+    >>> str(zenoh.mqtt_topic_to_keyexpr("/sensors/+/temp"))
+    '%/sensors/*/temp'
+    """
+    levels = topic.split("/")
+    chunks = []
+    for i, level in enumerate(levels):
+        if level == "+":
+            chunks.append("*")
+        elif level == "#":
+            if i != len(levels) - 1:
+                raise ValueError(f"`#` may only be the last level of an MQTT topic: {topic!r}")
+            chunks.append("**")
+        elif "+" in level or "#" in level:
+            raise ValueError(f"MQTT wildcards must occupy whole levels: {topic!r}")
+        elif level == "":
+            chunks.append("%")
+        else:
+            chunks.append("".join(f"%{ord(c):02X}" if c in _MQTT_ESCAPED else c for c in level))
+    return KeyExpr.autocanonize("/".join(chunks))
+
+def _mqtt_unescape(match: 're.Match') -> str:
+    c = chr(int(match[1], 16))
+    return c if c in _MQTT_ESCAPED else match[0]
+
+def keyexpr_to_mqtt_topic(keyexpr: IntoKeyExpr) -> str:
+    """
+    Maps a key expression back onto an MQTT topic name or filter, reversing ``mqtt_topic_to_keyexpr``.
+
+    Raises a ``ValueError`` if the key expression has no MQTT equivalent, such as ``**`` anywhere but at its end, or ``$*``.
+    """
+    chunks = str(KeyExpr(keyexpr)).split("/")
+    levels = []
+    for i, chunk in enumerate(chunks):
+        if chunk == "*":
+            levels.append("+")
+        elif chunk == "**":
+            if i != len(chunks) - 1:
+                raise ValueError(f"`**` may only be mapped onto MQTT's `#` as the last chunk: {keyexpr}")
+            levels.append("#")
+        elif "$*" in chunk:
+            raise ValueError(f"`$*` has no MQTT equivalent: {keyexpr}")
+        elif chunk == "%":
+            levels.append("")
+        else:
+            levels.append(re.sub("%([0-9A-F]{2})", _mqtt_unescape, chunk))
+    return "/".join(levels)
+
+def glob_to_keyexpr(pattern: str) -> KeyExpr:
+    """
+    Converts a Unix-style glob into the equivalent key expression.
+
+    ``**`` matches any amount of path segments, and ``*`` any amount of characters within one: ``a/*.log`` becomes ``a/$*.log``.
+    Raises a ``ValueError`` for globs key expressions can't express, such as those using ``?``, ``[...]`` or ``{...}``,
+    or matching characters keys can't contain.
+
+    >>> str(zenoh.glob_to_keyexpr("logs/**/*.txt"))
+    'logs/**/$*.txt'
+    """
+    chunks = []
+    for segment in pattern.strip("/").split("/"):
+        if segment == "**" or segment == "*":
+            chunks.append(segment)
+            continue
+        if segment == "" or "**" in segment:
+            raise ValueError(f"glob {pattern!r} has no key expression equivalent")
+        for c in "?[]{}#$":
+            if c in segment:
+                raise ValueError(f"{c!r} in glob {pattern!r} has no key expression equivalent")
+        chunks.append(segment.replace("*", "$*"))
+    return KeyExpr.autocanonize("/".join(chunks))
+
+def regex_to_keyexpr(regex: str) -> KeyExpr:
+    r"""
+    Converts a simple regular expression into the equivalent key expression.
+
+    The regex must be made of ``/``-separated segments, each of which is either ``.*`` (any amount of segments),
+    or a concatenation of literal (or ``\``-escaped) characters and ``[^/]*`` (any amount of characters within the segment).
+    ``^`` and ``$`` anchors are accepted at the ends, as the regex is matched against whole keys.
+    Raises a ``ValueError`` for anything else, as other regexes can't be expressed as key expressions.
+
+    >>> str(zenoh.regex_to_keyexpr(r"^sensors/[^/]*/temp\.c$"))
+    'sensors/*/temp.c'
+    """
+    body = regex[1:] if regex.startswith("^") else regex
+    if body.endswith("$") and not body.endswith("\\$"):
+        body = body[:-1]
+    # `[^/]*` contains a `/`: the regex must be tokenized before being split into segments.
+    segments, chunk = [], []
+    while body:
+        if body.startswith("[^/]*"):
+            chunk.append("$*")
+            body = body[5:]
+        elif body.startswith(".*"):
+            chunk.append("**")
+            body = body[2:]
+        elif body[0] == "/":
+            segments.append(chunk)
+            chunk = []
+            body = body[1:]
+        elif body[0] == "\\" and len(body) > 1 and not body[1].isalnum():
+            chunk.append(body[1])
+            body = body[2:]
+        elif body[0] in ".^$*+?()[]{}|\\":
+            raise ValueError(f"{body!r} in regex {regex!r} has no key expression equivalent")
+        else:
+            chunk.append(body[0])
+            body = body[1:]
+    segments.append(chunk)
+    chunks = []
+    for tokens in segments:
+        if tokens == ["**"]:
+            chunks.append("**")
+            continue
+        if tokens == ["$*"]:
+            chunks.append("*")
+            continue
+        literal = "".join(token for token in tokens if token != "$*")
+        if not tokens or "**" in tokens or any(c in literal for c in "*#?$"):
+            raise ValueError(f"regex {regex!r} has no key expression equivalent")
+        chunks.append("".join(tokens))
+    return KeyExpr.autocanonize("/".join(chunks))
+
 IntoSelector = Union['Selector', _Selector, IntoKeyExpr]
 class Selector(_Selector):
     """
@@ -184,5 +327,9 @@ class Selector(_Selector):
         attempting to use HTTP Parameter Pollution like exploits.
         """
         return super().decode_parameters()
+    def __copy__(self) -> 'Selector':
+        return Selector._upgrade_(self)
+    def __deepcopy__(self, memo) -> 'Selector':
+        return Selector._upgrade_(self)
     def __str__(self):
         return super().__str__()
\ No newline at end of file
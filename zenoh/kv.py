@@ -0,0 +1,90 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+from typing import Dict, List, Iterator
+
+from .enums import QueryConsolidation
+from .keyexpr import KeyExpr
+from .closures import ListCollector
+from .value import IntoValue, Value, Sample
+
+class KV:
+    """
+    A dict-like view of the keys stored under ``prefix``, for applications that only need a simple distributed dict.
+
+    Writes are plain puts and deletes, while reads are queries addressed to storages only (see ``Session.get``'s ``storages_only``),
+    always consolidated to the latest value of each key. A storage must thus be configured on ``prefix/**`` for values to be read back.
+
+    :Example:
+
+    >>> import zenoh
+    >>> s = zenoh.open({})
+    >>> kv = zenoh.KV(s, 'demo/kv')
+    >>> kv['greeting'] = 'Hello World!'
+    >>> print(kv['greeting'].payload)
+    >>> del kv['greeting']
+    """
+    def __init__(self, session, prefix: str, timeout: float = None):
+        self.session = session
+        self.prefix = prefix.strip('/')
+        self.timeout = timeout
+
+    def _key_expr_(self, key: str) -> KeyExpr:
+        return KeyExpr(f"{self.prefix}/{key}")
+
+    def _query_(self, selector: str) -> Dict[str, Sample]:
+        replies = self.session.get(self._key_expr_(selector), ListCollector(self.timeout),
+                                   consolidation=QueryConsolidation.LATEST(), storages_only=True, timeout=self.timeout)()
+        latest = dict()
+        for reply in replies:
+            if not reply.is_ok:
+                continue
+            sample = reply.ok
+            key = str(sample.key_expr)[len(self.prefix) + 1:]
+            # several storages may reply for a same key
+            known = latest.get(key)
+            if known is None or (sample.timestamp is not None and (known.timestamp is None or known.timestamp < sample.timestamp)):
+                latest[key] = sample
+        return latest
+
+    def __setitem__(self, key: str, value: IntoValue):
+        self.session.put(self._key_expr_(key), value)
+
+    def __getitem__(self, key: str) -> Value:
+        "Returns the latest value stored for ``key``, raising a ``KeyError`` if there is none."
+        sample = self._query_(key).get(key)
+        if sample is None:
+            raise KeyError(key)
+        return sample.value
+
+    def __delitem__(self, key: str):
+        self.session.delete(self._key_expr_(key))
+
+    def __contains__(self, key: str) -> bool:
+        return key in self._query_(key)
+
+    def __iter__(self) -> Iterator[str]:
+        return iter(self.keys())
+
+    def get(self, key: str, default: Value = None) -> Value:
+        "Returns the latest value stored for ``key``, or ``default`` if there is none."
+        sample = self._query_(key).get(key)
+        return default if sample is None else sample.value
+
+    def keys(self, glob: str = "**") -> List[str]:
+        "Returns the keys matching the ``glob`` key expression, relative to the prefix."
+        return sorted(self._query_(glob))
+
+    def items(self, glob: str = "**") -> List[tuple]:
+        "Returns the ``(key, value)`` pairs whose keys match the ``glob`` key expression."
+        return sorted((key, sample.value) for key, sample in self._query_(glob).items())
@@ -0,0 +1,122 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+A persistent cache of last values, for intermittently connected nodes such as edge gateways to keep answering
+queries from what they last received while the network is down.
+"""
+import time
+from typing import Any, List
+
+from .zenoh import offline_cache as _offline_cache
+from .closures import IntoHandler, Handler
+from .enums import QueryConsolidation, QueryTarget
+from .keyexpr import KeyExpr, IntoKeyExpr, IntoSelector, Selector
+from .value import IntoValue, Reply
+from .session import Session
+
+class CachedReply(Reply):
+    "A reply served by an ``OfflineCache`` from what it last received, rather than by a live queryable."
+    def __new__(cls, inner, received_at: float):
+        this = super().__new__(cls, inner)
+        this._received_at_ = received_at
+        return this
+
+    @property
+    def stale(self) -> bool:
+        return True
+
+    @property
+    def received_at(self) -> float:
+        "When the cached sample was received, in seconds since the epoch."
+        return self._received_at_
+
+    @property
+    def age(self) -> float:
+        "How long ago the cached sample was received, in seconds."
+        return time.time() - self._received_at_
+
+class OfflineCache:
+    """
+    Records the last value of each key received on ``keyexpr`` into the file at ``path``, and serves ``get``
+    from them while ``session`` has no router nor peer to reach.
+
+    The recording is done by the bindings, without going through Python: every sample is appended to the file
+    as it arrives, and the file is compacted down to one record per key when opened, and then whenever it grows
+    too large. Opening an existing file restores the values it holds, which survive restarts.
+    Deletions received on ``keyexpr`` remove the corresponding values.
+
+    Replies served from the cache are ``CachedReply`` objects: their ``stale`` attribute is ``True``, unlike that of
+    live replies, and their ``received_at`` and ``age`` tell how old the values are.
+
+    :Example:
+
+    >>> import zenoh, zenoh.offline
+    >>> s = zenoh.open({})
+    >>> cache = zenoh.offline.OfflineCache(s, 'plant/sensors/**', 'sensors.zcache')
+    >>> for reply in cache.get('plant/sensors/*/temperature', zenoh.Queue()):
+    ...     print(reply.ok.key_expr, reply.ok.payload, "(stale)" if reply.stale else "")
+    """
+    def __init__(self, session: Session, keyexpr: IntoKeyExpr, path: str):
+        self.session = session
+        self._inner_ = _offline_cache(session, KeyExpr(keyexpr), path)
+
+    @property
+    def online(self) -> bool:
+        "Whether the session currently reaches any router or peer, in which case ``get`` queries the network."
+        info = self.session.info()
+        return bool(info.routers_zid() or info.peers_zid())
+
+    def cached(self, selector: IntoSelector) -> List[CachedReply]:
+        """
+        Returns the cached values whose keys intersect ``selector``'s key expression, sorted by key, whether online or not.
+        The selector's parameters are ignored.
+        """
+        key_expr = Selector(selector).key_expr
+        return [CachedReply(reply, received_at) for reply, received_at in self._inner_.lookup(key_expr)]
+
+    def get(self, selector: IntoSelector, handler: IntoHandler[Reply, Any, Any], consolidation: QueryConsolidation = None,
+            target: QueryTarget = None, value: IntoValue = None, timeout: float = None):
+        """
+        Sends a query through ``session`` when ``online``, see ``Session.get``.
+
+        Otherwise, ``handler`` receives the cached values intersecting ``selector`` right away, as ``CachedReply`` objects,
+        the other parameters being ignored. Either way, ``handler``'s receiver is returned.
+        """
+        if self.online:
+            return self.session.get(selector, handler, consolidation, target, value, timeout=timeout)
+        handler = Handler(handler, prevent_direct_calls=False)
+        closure = handler.closure
+        try:
+            for reply in self.cached(selector):
+                closure.call(reply)
+        finally:
+            if closure.drop is not None:
+                closure.drop()
+        return handler.receiver
+
+    def __len__(self) -> int:
+        return len(self._inner_)
+
+    def close(self):
+        """
+        Stops recording and flushes the file, raising a ``ZError`` if writing it failed.
+        The values cached so far remain available to ``get`` and ``cached``.
+        """
+        self._inner_.close()
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *args):
+        self.close()
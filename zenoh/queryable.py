@@ -11,13 +11,24 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from typing import Dict, Optional
+import inspect
+from typing import Dict, Optional, Callable, Any
 
 from .zenoh import _Query, _Queryable
 from .keyexpr import KeyExpr, Selector
 from .value import Sample, Value, IntoValue, IntoSample
+from .interfaces import IQueryable
 
-class Queryable:
+class Cache:
+    """
+    The configuration of a queryable's reply cache, see ``Session.declare_queryable``: replies are kept for ``ttl`` seconds,
+    for up to ``max_entries`` distinct selectors, the least recently used ones being evicted first.
+    """
+    def __init__(self, ttl: float = 1.0, max_entries: int = 128):
+        self.ttl = ttl
+        self.max_entries = max_entries
+
+class Queryable(IQueryable):
     """
     A handle to a queryable.
     
@@ -34,6 +45,22 @@ class Queryable:
         "Stops the queryable."
         self._inner_ = None
 
+    def try_recv(self) -> Optional['Query']:
+        "Returns the next received query if there is one, or ``None``, without waiting: see ``Queue.try_recv``."
+        if not hasattr(self.receiver, 'try_recv'):
+            raise TypeError("`try_recv` requires the queryable's handler to be a `zenoh.Queue`")
+        return self.receiver.try_recv()
+
+    def recv(self, timeout: float = None) -> 'Query':
+        "Waits for the next received query, for up to ``timeout`` seconds if set: see ``Queue.recv``."
+        if not hasattr(self.receiver, 'recv'):
+            raise TypeError("`recv` requires the queryable's handler to be a `zenoh.Queue`")
+        return self.receiver.recv(timeout)
+
+    def clear_cache(self):
+        "Forgets the replies cached by a queryable declared with a ``cache``, for instance once the data it serves changed."
+        self._inner_.clear_cache()
+
 class Query(_Query):
     def __new__(cls, inner: _Query):
         return super().__new__(cls, inner)
@@ -81,3 +108,31 @@ class Query(_Query):
         Sending error responses does not exclude sending other responses.
         """
         super().reply_err(Value(value))
+    def _reply_all_(self, result, generator: bool):
+        """
+        Sends the replies of a queryable callback, as ``_replies`` lists them. Values that aren't samples are sent on the
+        query's key expression.
+        """
+        super().reply_all(_replies(result, generator), Value.autoencode)
+    def _reply_eval_(self, fn: Callable[[Dict[str, str]], Any], generator: bool):
+        """
+        Replies with the result of ``fn`` called with the query's decoded parameters, as ``_reply_all_`` does,
+        or with the exception it raises as an error.
+        """
+        super().reply_eval(fn, generator, Value.autoencode)
+
+def _replies(result, generator: bool):
+    """
+    The replies in the ``result`` of a queryable callback: the items it yields if the callback is a generator function,
+    ``generator`` being set, or else the value it returned unless ``None``.
+    """
+    if generator:
+        return result
+    return () if result is None else (result,)
+
+def _replying(callback: Callable[['Query'], Any]) -> Callable[['Query'], None]:
+    "Wraps a queryable callback so that the values it returns, or yields if it is a generator function, are sent as replies."
+    generator = inspect.isgeneratorfunction(callback)
+    def call(query: Query):
+        query._reply_all_(callback(query), generator)
+    return call
@@ -0,0 +1,52 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+Ordering and merging of replies by timestamp, done in Rust, to assemble consistent snapshots from several storages.
+
+The functions take receivers: iterables of replies (or samples), such as lists or ``Queue`` receivers,
+which are read until closed, or functions returning one, such as ``ListCollector`` receivers.
+Error replies are skipped. The replies returned are the objects the receivers provided.
+"""
+from typing import Any, List
+
+from .zenoh import merge_sorted as _merge_sorted, dedup_latest as _dedup_latest
+from .value import Reply
+
+def merge_sorted(*receivers: Any) -> List[Reply]:
+    """
+    Returns the replies of all ``receivers``, by increasing timestamp.
+
+    Replies without timestamp come last, in the order they were received.
+
+    :Example:
+
+    >>> import zenoh, zenoh.replies
+    >>> s = zenoh.open({})
+    >>> a = s.get('storage/a/**', zenoh.ListCollector())
+    >>> b = s.get('storage/b/**', zenoh.ListCollector())
+    >>> for reply in zenoh.replies.merge_sorted(a, b):
+    ...     print(reply.ok.timestamp, reply.ok.key_expr)
+    """
+    return _merge_sorted(*receivers)
+
+def dedup_latest(replies: Any) -> List[Reply]:
+    """
+    Returns the reply with the greatest timestamp for each key expression in ``replies``, ordered by key expression:
+    the most recent snapshot of the replicas that replied, when queried with ``QueryConsolidation.NONE()``
+    so that zenoh passes all their replies on.
+
+    Replies without timestamp are only kept for the key expressions no timestamped reply has.
+    See ``zenoh.timestamps.conflicts`` to also detect replicas disagreeing on a timestamp.
+    """
+    return _dedup_latest(replies)
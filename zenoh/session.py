@@ -11,27 +11,72 @@
 # Contributors:
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
-from typing import Union, Any, List
-
-from .zenoh import _Session, _Config, _Publisher, _Subscriber, _PullSubscriber
+from typing import Union, Any, List, Dict, Set, Tuple, Callable, Optional, Mapping, Iterable, Iterator
+from threading import Event, Lock, RLock, Thread
+import weakref
+import logging
+import asyncio
+import concurrent.futures
+import time
+import json
+import urllib.parse
+import uuid
+import inspect
+from datetime import datetime, timezone
+
+from .zenoh import _Session, _Config, _Publisher, _Subscriber, _PullSubscriber, _Dispatcher, _CallbackProfile, _PeriodicPublisher, _MetricsExporter, ZError, configure_runtime
+from .zenoh import register_schema as _register_schema, schema_of as _schema_of
 
 from .keyexpr import KeyExpr, IntoKeyExpr, Selector, IntoSelector
-from .config import Config
-from .closures import IntoHandler, Handler, Receiver
+from .config import Config, CONNECT_KEY, _remove_files
+from .closures import IntoHandler, Handler, Closure, Receiver, ListCollector, Queue, IHandler, IClosure, QueueProfile, CallbackError, _CallbackErrors, _coroutine_closure, _executor_closure
 from .enums import *
-from .value import IntoValue, Value, Sample, Reply, ZenohId
-from .queryable import Queryable, Query
+from .value import IntoValue, Value, Sample, Change, SampleFilter, Reply, ZenohId
+from .queryable import Cache, Queryable, Query, _replying
+from .admin import MatchingEntity, RouterClient, matching_entities, _admin_get
+from .workspace import Workspace
+from .interfaces import IPublisher, ISubscriber, ISession
+
+
+class Outbox:
+    """
+    The configuration of a publisher's outbox, see ``Session.declare_publisher``: up to ``max_messages`` publications
+    are held back while the session reaches no router nor peer, for up to ``max_age`` seconds if set, the oldest being
+    evicted first. With ``path``, they are also persisted to that file, and restored from it when a publisher is declared
+    with the same ``path``, so that they survive restarts.
+    """
+    def __init__(self, path: str = None, max_messages: int = 1000, max_age: float = None):
+        self.path = path
+        self.max_messages = max_messages
+        self.max_age = max_age
+
+class Publisher(IPublisher):
+    """
+    Use ``Publisher`` (constructed with ``Session.declare_publisher``) when you want to send values often for the same key expression, as declaring them informs Zenoh that this is you intent, and optimizations will be set up to do so.
 
+    The priority, congestion control and express flag the publisher was declared with apply to all its publications.
+    Publishers may be used as context managers, which undeclare them on exit:
 
-class Publisher:
-    "Use ``Publisher`` (constructed with ``Session.declare_publisher``) when you want to send values often for the same key expression, as declaring them informs Zenoh that this is you intent, and optimizations will be set up to do so."
+    >>> with s.declare_publisher('demo/telemetry', priority=zenoh.Priority.DATA_HIGH()) as pub:
+    ...     for reading in readings():
+    ...         pub.put(reading)
+    """
 
-    def __init__(self, p: _Publisher):
+    def __init__(self, p: _Publisher, priority: Priority = None, congestion_control: CongestionControl = None, express: bool = None):
         self._inner_ = p
+        self._priority_ = Priority.DATA() if priority is None else priority
+        self._congestion_control_ = CongestionControl.DROP() if congestion_control is None else congestion_control
+        self._express_ = bool(express)
+
+    def __enter__(self) -> 'Publisher':
+        return self
 
-    def put(self, value: IntoValue, encoding: Encoding = None):
-        "An optimised version of ``session.put(self.key_expr, value, encoding=encoding)``"
-        self._inner_.put(Value(value, encoding))
+    def __exit__(self, *args):
+        self.undeclare()
+
+    def put(self, value: IntoValue, encoding: Encoding = None, attachment: Dict[Union[bytes, str], Union[bytes, str]] = None):
+        "An optimised version of ``session.put(self.key_expr, value, encoding=encoding, attachment=attachment)``"
+        self._inner_.put(Value(value, encoding), attachment)
 
     def delete(self):
         "An optimised version of ``session.delete(self.key_expr)``"
@@ -42,12 +87,118 @@ class Publisher:
         "This ``Publisher``'s key expression"
         return KeyExpr(self._inner_.key_expr)
 
+    @property
+    def priority(self) -> Priority:
+        "The priority the publisher's publications are routed with."
+        return self._priority_
+
+    @property
+    def congestion_control(self) -> CongestionControl:
+        "The congestion control the publisher's publications are sent with."
+        return self._congestion_control_
+
+    @property
+    def express(self) -> bool:
+        "Whether the publisher's publications bypass batching, being sent right away."
+        return self._express_
+
     def undeclare(self):
         "Stops the publisher."
         self._inner_ = None
 
+    def pressure(self, latency_scale: float = 0.01) -> float:
+        """
+        How hard the publisher is currently being pushed back, from ``0.0`` (not at all) to ``1.0``, for control loops
+        adapting the rate or quality of what they publish, such as video or point cloud streams.
+
+        Zenoh doesn't expose the occupancy of its queues, but puts wait for room in them when the publisher's congestion
+        control is ``CongestionControl.BLOCK()``: the pressure grows with the recent average time puts took, reaching ``1.0``
+        at ``latency_scale`` seconds. Publications dropped by the session's rate limit (see ``Session.set_rate_limit``)
+        or held back by an outbox count as fully pushed back. With ``CongestionControl.DROP()``, zenoh drops publications
+        silently when its queues are full, which this can't observe.
+
+        The averages weigh recent puts the most, the past weighing half as much every second, and decay while
+        nothing is published. See ``pressure_stats`` for the averages themselves.
+
+        :Example:
+
+        >>> pub = s.declare_publisher('demo/video', congestion_control=zenoh.CongestionControl.BLOCK())
+        >>> while True:
+        ...     quality = 90 if pub.pressure() < 0.5 else 50
+        ...     pub.put(encode(next_frame(), quality))
+        """
+        latency, held_back = self._inner_.pressure()
+        return min(1.0, max(held_back, latency / latency_scale))
+
+    def pressure_stats(self) -> Dict[str, float]:
+        """
+        Returns the recent averages ``pressure`` is computed from: ``latency``, the time puts took in seconds,
+        and ``held_back``, the share of publications dropped by the session's rate limit or held back by an outbox.
+        """
+        latency, held_back = self._inner_.pressure()
+        return {"latency": latency, "held_back": held_back}
+
+    @property
+    def outbox_pending(self) -> int:
+        "The number of publications held back by the publisher's outbox, if it was declared with one."
+        return self._inner_.outbox_len
+
+    @property
+    def outbox_dropped(self) -> int:
+        "The number of publications the publisher's outbox evicted, for lack of room or because they expired."
+        return self._inner_.outbox_dropped
+
+    def flush_outbox(self) -> int:
+        """
+        Forwards the publications held back by the publisher's outbox right away, if the session reaches the network,
+        rather than on the outbox's next periodic check. Returns how many were forwarded.
+
+        Raises a ``ZError`` if the outbox failed to write its file, in which case it keeps holding publications in memory only.
+        """
+        return self._inner_.flush_outbox()
+
+
+class PeriodicPublisher:
+    """
+    A handle to a periodic publication, as returned by ``Session.publish_periodically``.
+
+    The publication stops when ``stop`` is called, or when this handle is destroyed.
+    """
+    def __init__(self, inner: _PeriodicPublisher):
+        self._inner_ = inner
+
+    @property
+    def running(self) -> bool:
+        "Whether the publication is still running: it stops early if its value function raises or publication fails."
+        return self._inner_ is not None and self._inner_.running
+
+    def stop(self):
+        "Stops the publication, raising the exception that stopped it early, if any."
+        if self._inner_ is not None:
+            inner, self._inner_ = self._inner_, None
+            inner.stop()
+
+
+class MetricsExporter:
+    """
+    A handle to the HTTP server started by ``Session.export_metrics``.
+
+    The server runs until ``stop`` is called or this handle is dropped.
+    """
+    def __init__(self, inner: _MetricsExporter):
+        self._inner_ = inner
+
+    @property
+    def port(self) -> int:
+        "The port the metrics are served on, useful when letting the system pick one."
+        return self._inner_.port
+
+    def stop(self):
+        "Stops serving the metrics."
+        self._inner_.stop()
 
-class Subscriber:
+
+class Subscriber(ISubscriber):
     """
     A handle to a subscription.
 
@@ -60,11 +211,126 @@ class Subscriber:
     def __init__(self, s: _Subscriber, receiver=None):
         self._subscriber_ = s
         self.receiver = receiver
+        self._profile_ = None
 
     def undeclare(self):
         "Undeclares the subscription"
         self._subscriber_ = None
 
+    def try_recv(self) -> Optional[Sample]:
+        "Returns the next received sample if there is one, or ``None``, without waiting: see ``Queue.try_recv``."
+        if not hasattr(self.receiver, 'try_recv'):
+            raise TypeError("`try_recv` requires the subscriber's handler to be a `zenoh.Queue`")
+        return self.receiver.try_recv()
+
+    def recv(self, timeout: float = None) -> Sample:
+        "Waits for the next received sample, for up to ``timeout`` seconds if set: see ``Queue.recv``."
+        if not hasattr(self.receiver, 'recv'):
+            raise TypeError("`recv` requires the subscriber's handler to be a `zenoh.Queue`")
+        return self.receiver.recv(timeout)
+
+    def profile(self) -> Dict[str, float]:
+        """
+        Returns the timing statistics of a subscription declared with ``profile=True``, durations being in seconds:
+
+        - ``calls``: the number of samples delivered
+        - ``gil_wait_avg``, ``gil_wait_max``: how long the bindings waited for the GIL before handing a sample to Python
+        - ``gil_held_avg``, ``gil_held_max``: how long the GIL was then held to do so
+        - ``queue_wait_avg``, ``queue_wait_max``: how long samples waited in the handler's queue
+        - ``callback_avg``, ``callback_max``: how long the callback took to process each sample
+
+        High GIL waits hint at contention with other Python threads, high queue waits at a callback too slow to keep up.
+        """
+        if self._profile_ is None:
+            raise ZError("this subscriber wasn't declared with `profile=True`")
+        gil, queue = self._profile_
+        stats = gil.stats()
+        stats.update(queue.stats())
+        return stats
+
+    def next_sample(self, timeout: float = None) -> Optional[Sample]:
+        """
+        Waits for the next sample the subscription delivers, returning ``None`` if ``timeout`` (in seconds) expires first.
+
+        The sample is still passed to the subscription's handler as usual: this only lets you observe it once,
+        which is handy to wait for some state in tests or orchestration scripts.
+        """
+        sample = self._subscriber_.next_sample(timeout)
+        return None if sample is None else Sample._upgrade_(sample)
+
+    async def next_sample_async(self, timeout: float = None) -> Optional[Sample]:
+        "The awaitable counterpart of ``next_sample``, which waits from the event loop's default executor."
+        import asyncio
+        return await asyncio.get_running_loop().run_in_executor(None, self.next_sample, timeout)
+
+    def on_silence(self, timeout: float, callback: Callable[[], Any]):
+        """
+        Calls ``callback`` whenever the subscription goes ``timeout`` seconds without delivering a sample,
+        once per such silence, which lets monitoring scripts detect dead publishers without polling.
+
+        The timer is managed by the bindings, and ``callback`` is called from their thread: it should return quickly.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> sub = s.declare_subscriber('demo/heartbeat', lambda sample: None)
+        >>> sub.on_silence(5.0, lambda: print("No heartbeat for 5s!"))
+        """
+        self._subscriber_.on_silence(timeout, callback)
+
+    def count(self) -> int:
+        "The number of samples received by the subscription's sink. Raises a ``ZError`` if it wasn't declared with a ``sink``."
+        return self._subscriber_.count()
+
+    @property
+    def dropped(self) -> int:
+        "The number of samples dropped so far by the subscription's ``overflow`` policy, which is 0 without one."
+        return self._subscriber_.dropped
+
+    def latest(self) -> Optional[Sample]:
+        """The latest sample received by the subscription's ``"latest"`` sink, if any."""
+        sample = self._subscriber_.latest()
+        return None if sample is None else Sample._upgrade_(sample)
+
+
+class Dispatcher:
+    """
+    A single wildcard subscription, dispatching its samples to the handlers of more specific key expressions.
+
+    Routing one subscription instead of dozens lowers the network's and the session's overhead,
+    and the dispatching itself is done in Rust: samples that match no route never reach Python.
+    A sample is passed to each route whose key expression intersects with its own.
+
+    :Example:
+
+    >>> import zenoh
+    >>> s = zenoh.open({})
+    >>> dispatcher = s.declare_dispatcher('demo/sensor/**')
+    >>> dispatcher.add('demo/sensor/*/temp', lambda sample: print("temp", sample.payload))
+    >>> dispatcher.add('demo/sensor/*/hum', lambda sample: print("humidity", sample.payload))
+    """
+    def __init__(self, inner: _Dispatcher, prevent_direct_calls: bool = True, on_error: CallbackError = None):
+        self._inner_ = inner
+        self._prevent_direct_calls_ = prevent_direct_calls
+        self._on_error_ = on_error
+
+    def add(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any]):
+        """
+        Routes the samples intersecting with ``keyexpr`` to ``handler``, returning the handler's receiver.
+        """
+        handler = Handler(handler, lambda x: Sample._upgrade_(x), prevent_direct_calls=self._prevent_direct_calls_, on_error=self._on_error_)
+        self._inner_.add(KeyExpr(keyexpr), handler.closure)
+        return handler.receiver
+
+    def remove(self, keyexpr: IntoKeyExpr) -> bool:
+        "Removes the routes declared on ``keyexpr``, returning whether there were any."
+        return self._inner_.remove(KeyExpr(keyexpr))
+
+    def undeclare(self):
+        "Undeclares the subscription, dropping all routes."
+        self._inner_ = None
+
 
 class PullSubscriber:
     """
@@ -94,23 +360,365 @@ class PullSubscriber:
         self._subscriber_ = None
 
 
-class Session(_Session):
+class Declaration:
+    """
+    Describes an entity declared through a ``Session``, as listed by ``Session.declarations``.
+
+    ``kind`` is one of ``"publisher"``, ``"subscriber"``, ``"pull_subscriber"``, ``"queryable"`` or ``"keyexpr"``,
+    and ``options`` holds the options that were explicitly passed at declaration.
+    """
+    def __init__(self, kind: str, key_expr: KeyExpr, options: Dict[str, Any]):
+        self.kind = kind
+        self.key_expr = key_expr
+        self.options = options
+
+    def __repr__(self):
+        return f"Declaration(kind={self.kind!r}, key_expr='{self.key_expr}', options={self.options!r})"
+
+
+class _Declarations:
+    "Keeps track of the entities declared through a session, without extending their lifetime."
+    def __init__(self):
+        self._lock_ = Lock()
+        self._handles_ = []
+        self._keyexprs_ = dict()
+
+    def track(self, handle, attr: str, kind: str, key_expr: KeyExpr, options: Dict[str, Any],
+              redeclare: Callable[[_Session], Any] = None):
+        with self._lock_:
+            self._handles_ = [entry for entry in self._handles_ if self._alive_(entry)]
+            self._handles_.append((weakref.ref(handle), attr, Declaration(kind, key_expr, options), redeclare))
+        return handle
+
+    def track_keyexpr(self, key_expr: KeyExpr):
+        with self._lock_:
+            self._keyexprs_[str(key_expr)] = Declaration("keyexpr", key_expr, dict())
+        return key_expr
+
+    def forget_keyexpr(self, key_expr: KeyExpr):
+        with self._lock_:
+            self._keyexprs_.pop(str(key_expr), None)
+
+    def handles(self) -> List[Any]:
+        with self._lock_:
+            return [entry[0]() for entry in self._handles_ if self._alive_(entry)]
+
+    def snapshot(self) -> List[Declaration]:
+        with self._lock_:
+            return [entry[2] for entry in self._handles_ if self._alive_(entry)] + list(self._keyexprs_.values())
+
+    def redeclarable(self) -> List[Tuple[Any, str, Callable[[_Session], Any]]]:
+        "The live handles that may be declared anew, with the attribute holding their native entity and the function declaring it."
+        with self._lock_:
+            return [(entry[0](), entry[1], entry[3]) for entry in self._handles_ if entry[3] is not None and self._alive_(entry)]
+
+    @staticmethod
+    def _alive_(entry) -> bool:
+        ref, attr, _, _ = entry
+        handle = ref()
+        return handle is not None and getattr(handle, attr, None) is not None
+
+
+def _rebindable(closure: IClosure) -> Callable[[], IClosure]:
+    """
+    Returns a function making a closure for each declaration of an entity that a resilient session may declare anew:
+    dropping the previous declarations doesn't drop ``closure``, only dropping the latest one does.
+    """
+    generation = 0
+    def bind() -> IClosure:
+        nonlocal generation
+        generation += 1
+        bound = generation
+        def drop():
+            if bound == generation:
+                closure.drop()
+        return Closure((closure.call, drop))
+    return bind
+
+# How often resilient sessions probe their runtime, how long they wait for it, and after how many failures in a row they rebuild it.
+_PROBE_INTERVAL = 5.0
+_PROBE_TIMEOUT = 2.0
+_PROBE_FAILURES = 2
+
+def _watch_runtime(ref: 'weakref.ref[Session]', stopped: Event):
+    "Rebuilds the runtime of the session ``ref`` refers to whenever it stops responding, until ``stopped`` is set."
+    failed = 0
+    while not stopped.wait(_PROBE_INTERVAL):
+        session = ref()
+        if session is None:
+            return
+        if session._runtime_responsive_():
+            failed = 0
+        else:
+            failed += 1
+        if failed >= _PROBE_FAILURES and not stopped.is_set():
+            failed = 0
+            logging.getLogger("zenoh").warning("the session's runtime stopped responding, rebuilding it")
+            try:
+                session.rebuild()
+            except Exception:
+                logging.getLogger("zenoh").exception("the session's runtime failed to be rebuilt, retrying on the next probe")
+        del session
+
+def _re_resolving(ref: 'weakref.ref[Session]', zids: Set[str]) -> Closure:
+    """
+    Returns the closure of the subscriber that the session ``ref`` refers to declares on its own transports, ``zids``
+    being those open: once the last of them closes, the session's connect endpoints are resolved anew.
+    """
+    def call(sample: Sample):
+        zid = str(sample.key_expr).rsplit("/", 1)[-1]
+        if sample.kind == SampleKind.PUT():
+            zids.add(zid)
+            return
+        zids.discard(zid)
+        session = ref()
+        if not zids and session is not None:
+            # resolvers may block, which zenoh's thread must not
+            Thread(target=session._re_resolve_, name="zenoh-resolve", daemon=True).start()
+    return Closure((call, lambda: None), lambda x: Sample._upgrade_(x))
+
+class _Sessions:
+    """
+    The sessions of the process: those open, for the shutdown handler to close, and among them those registered
+    by name through ``zenoh.init``, which are kept alive until released or closed.
+    """
+    def __init__(self):
+        # reentrant, as opening a session to register it adds it
+        self._lock_ = RLock()
+        self._open_ = weakref.WeakSet()
+        self._named_: Dict[Optional[str], 'Session'] = dict()
+
+    def add(self, session: 'Session'):
+        with self._lock_:
+            self._open_.add(session)
+
+    def discard(self, session: 'Session'):
+        "Forgets ``session``, once closed, under whatever name it was registered."
+        with self._lock_:
+            self._open_.discard(session)
+            for name in [name for name, named in self._named_.items() if named is session]:
+                del self._named_[name]
+
+    def register(self, name: Optional[str], open: Callable[[], 'Session'], exclusive: bool) -> 'Session':
+        """
+        Returns the session registered under ``name``, opening one with ``open`` if there is none,
+        or raising a ``ValueError`` if there is one and ``exclusive`` is set.
+        """
+        with self._lock_:
+            session = self._named_.get(name)
+            if session is None:
+                session = self._named_[name] = open()
+            elif exclusive:
+                raise ValueError("the default session was already initialized" if name is None else f"a session named {name!r} is already registered")
+            return session
+
+    def get(self, name: str) -> 'Session':
+        with self._lock_:
+            return self._named_[name]
+
+    def release(self, name: Optional[str]) -> Optional['Session']:
+        with self._lock_:
+            return self._named_.pop(name, None)
+
+    def close_all(self, drain_timeout: float = None):
+        with self._lock_:
+            sessions = list(self._open_)
+        for session in sessions:
+            session.close(drain_timeout)
+
+_SESSIONS = _Sessions()
+
+
+class Session(_Session, ISession):
     """
     A Zenoh Session, the core interraction point with a Zenoh network.
 
     Note that most applications will only need a single instance of ``Session``. You should _never_ construct one session per publisher/subscriber, as this will significantly increase the size of your Zenoh network, while preventing potential locality-based optimizations.
+
+    With ``share_runtime=True``, sessions opened with the same configuration in a process share a single zenoh runtime,
+    and thus its threads, transports and sockets. Each ``Session`` still keeps track of its own declarations and statistics,
+    but they all have the same ``zid``, and the runtime is only closed once all of them are.
+
+    With ``namespace``, all the key expressions the session sends are prefixed with ``namespace``, which is stripped
+    from those it receives: this is a shorthand for ``add_keyexpr_mapping('**', f'{namespace}/**')``.
+
+    With ``callback_threads``, the callbacks of the session's subscribers, queryables and dispatchers are called from a pool of
+    that many threads rather than each from a thread of its own. Each callback is bound to one of the pool's threads, so it is
+    still called for one sample or query at a time, in order; but a slow callback delays the others bound to the same thread.
+
+    With ``audit``, every put, delete, query and declaration made through the session, including through its publishers,
+    is recorded as a line of JSON holding its ``time`` (in seconds since the epoch), ``operation``, ``key_expr``,
+    the payload's ``size`` and the QoS passed along. ``audit`` is either the path of a file to append the records to,
+    or a ``logging.Logger`` to log them through at the ``INFO`` level. Records are written by the Rust layer, before
+    the operation is carried out: an operation that fails to be recorded fails as well.
+
+    Callbacks may use the session, including to put on the key expressions they subscribe to, query, declare or undeclare
+    entities (their own included): the bindings release the GIL whenever they wait on zenoh, and queued callbacks handle
+    the samples they cause themselves to receive once they return. Callbacks that zenoh calls directly (``reuse_samples``
+    and ``Closure`` handlers) must however not wait for the replies to their own queries, as zenoh may need the very thread
+    they hold to deliver them: pass such queries a callback rather than waiting on a ``ListCollector`` or ``Queue``.
+
+    ``callback_errors`` decides what becomes of the exceptions raised by the callbacks of the session's subscribers,
+    queryables, dispatchers and queries: with ``"log"``, they are logged to the ``zenoh`` logger, and with ``"ignore"``,
+    they are dropped. With ``"raise"``, the first of them is raised by the next put, delete, query or declaration
+    made through the session, instead of carrying it out, letting the application notice it from its own thread. In any case, the callback keeps
+    receiving the following samples or queries; ``on_error`` is called with each of the exceptions beforehand, if set,
+    from the thread that ran the callback.
+
+    ``runtime_threads`` sizes the async runtime backing all the sessions of the process, which must be done before the first
+    one is opened, and before other threads are started: see ``zenoh.configure_runtime``.
+
+    With ``resilient=True``, the session probes its runtime every few seconds, through a healthcheck queryable of its own
+    (see ``enable_healthcheck``), and rebuilds it with ``rebuild`` when it stops replying, such as after a fatal transport
+    error left it stuck. The publishers, subscribers and queryables declared through the session are then declared anew
+    on the new runtime, their handles and handlers carrying on as they were. Other entities, such as pull subscribers,
+    dispatchers and periodic publications, are not: they keep their previous, dead, runtime.
     """
-    def __new__(cls, config: Union[Config, Any] = None):
+    def __new__(cls, config: Union[Config, Any] = None, share_runtime: bool = False, namespace: str = None,
+                callback_threads: int = None, audit: Union[str, logging.Logger] = None,
+                callback_errors: str = "log", on_error: CallbackError = None, runtime_threads: int = None,
+                resilient: bool = False):
+        callback_errors = _CallbackErrors(callback_errors, on_error)
+        if resilient and share_runtime:
+            raise ValueError("a session sharing its runtime can't rebuild it: `resilient` and `share_runtime` are mutually exclusive")
+        if runtime_threads is not None:
+            configure_runtime(worker_threads=runtime_threads)
+        if config is not None and not isinstance(config, _Config):
+            config = Config.from_obj(config)
+        # kept before the endpoints are resolved, for rebuilds to resolve them anew
+        unresolved = config.to_dict() if isinstance(config, Config) else dict()
+        resolved = False
         if config is None:
-            return super().__new__(cls)
-        elif isinstance(config, _Config):
-            return super().__new__(cls, config)
+            session = super().__new__(cls, None, share_runtime, callback_threads, audit)
         else:
-            return super().__new__(cls, Config.from_obj(config))
+            if isinstance(config, Config):
+                resolved = config.resolve_endpoints()
+            session = super().__new__(cls, config, share_runtime, callback_threads, audit)
+        reopen_config = unresolved if resilient or resolved else None
+        # the session may still need them to rebuild its runtime, until it is closed
+        owned_files = config._take_files_() if isinstance(config, Config) else []
+        session._owned_files_ = weakref.finalize(session, _remove_files, owned_files)
+        session._declarations_ = _Declarations()
+        session._reopen_config_ = reopen_config
+        session._watchdog_ = None
+        session._transports_ = session._watch_transports_() if resolved else None
+        session._prevent_direct_calls_ = callback_threads is None
+        session._callback_errors_ = callback_errors
+        session._namespace_ = None
+        if namespace:
+            session._namespace_ = namespace.strip('/')
+            session.add_keyexpr_mapping('**', f'{session._namespace_}/**')
+        _SESSIONS.add(session)
+        if resilient:
+            session._probe_key_ = f"zenoh-python/resilience/{uuid.uuid4().hex}"
+            session._probe_ = _Session.enable_healthcheck(session, KeyExpr(session._probe_key_))
+            session._watchdog_ = Event()
+            Thread(target=_watch_runtime, args=(weakref.ref(session), session._watchdog_), name="zenoh-watchdog", daemon=True).start()
+        return session
+
+    @property
+    def resilient(self) -> bool:
+        "Whether the session was opened with ``resilient=True``."
+        return self._watchdog_ is not None
+
+    def rebuild(self):
+        """
+        Replaces the session's runtime by a new one, opened with the configuration the session was opened with,
+        and declares the publishers, subscribers and queryables declared through the session anew on it.
+
+        This is done automatically when the runtime stops responding, or when the session's connect endpoints resolve
+        to other locators (see ``Config.resolve_endpoints``), but may also be called after detecting a failure by other means.
+        It is only available to sessions opened with ``resilient=True``, or whose connect endpoints were resolved. The previous
+        runtime and the entities declared on it are closed in the background, as a runtime that stopped responding may never
+        finish closing. Entities that fail to be declared anew are logged to the ``zenoh`` logger, and stay undeclared.
+        """
+        if self._reopen_config_ is None:
+            raise ZError("only sessions opened with `resilient=True`, or whose connect endpoints were resolved, can be rebuilt")
+        config = Config.from_dict(self._reopen_config_)
+        config.resolve_endpoints()
+        self._rebuild_(config)
+
+    def _rebuild_(self, config: Config):
+        "Replaces the session's runtime by one opened with ``config``, see ``rebuild``."
+        super().reopen(config)
+        stale = []
+        if self._watchdog_ is not None:
+            stale.append(self._probe_)
+            self._probe_ = _Session.enable_healthcheck(self, KeyExpr(self._probe_key_))
+        if self._transports_ is not None:
+            stale.append(self._transports_)
+            self._transports_ = self._watch_transports_()
+        for handle, attr, redeclare in self._declarations_.redeclarable():
+            stale.append(getattr(handle, attr))
+            try:
+                setattr(handle, attr, redeclare(self))
+            except Exception:
+                setattr(handle, attr, None)
+                logging.getLogger("zenoh").exception("an entity failed to be declared anew on the rebuilt runtime")
+        # undeclaring from the previous runtime may block if it stopped responding
+        Thread(target=stale.clear, name="zenoh-rebuild", daemon=True).start()
+
+    def _watch_transports_(self) -> _Subscriber:
+        "Declares the subscriber resolving the session's connect endpoints anew once it lost all its transports."
+        zids = {str(zid) for zid in self.routers_zid() + self.peers_zid()}
+        keyexpr = KeyExpr(f"@/session/{self.zid()}/transport/unicast/*")
+        return _Session.declare_subscriber(self, keyexpr, _re_resolving(weakref.ref(self), zids))
+
+    def _re_resolve_(self):
+        "Resolves the connect endpoints anew, rebuilding the runtime on the resulting locators if they changed."
+        try:
+            config = Config.from_dict(self._reopen_config_)
+            config.resolve_endpoints()
+            if self._transports_ is None or config.get_json(CONNECT_KEY) == self.config().get_json(CONNECT_KEY):
+                return
+            logging.getLogger("zenoh").info("the session's connect endpoints resolved to other locators, reconnecting through them")
+            self._rebuild_(config)
+        except Exception:
+            logging.getLogger("zenoh").exception("the session's connect endpoints failed to be resolved anew")
+
+    def _runtime_responsive_(self) -> bool:
+        "Whether the session's runtime replies to the session's own healthcheck query in time."
+        results = []
+        def probe():
+            try:
+                # bypasses `get`, which may raise the errors of the application's callbacks
+                handler = Handler(ListCollector(_PROBE_TIMEOUT))
+                _Session.get(self, Selector(self._probe_key_), handler.closure, timeout=_PROBE_TIMEOUT)
+                results.append(any(reply.is_ok for reply in handler.receiver()))
+            except Exception:
+                results.append(False)
+        # sending the query may itself block on a deadlocked runtime
+        thread = Thread(target=probe, name="zenoh-probe", daemon=True)
+        thread.start()
+        thread.join(2 * _PROBE_TIMEOUT)
+        return bool(results) and results[0]
+
+    def _declarer_(self, declare: Callable[..., Any], keyexpr: KeyExpr, kwargs: Dict[str, Any], *closure) -> Callable[[_Session], Any]:
+        """
+        Returns a function declaring an entity through ``declare`` on the session it is passed, the way it is declared now.
+
+        Sessions that may be rebuilt keep it to declare the entity anew on their new runtimes, each declaration being handed
+        its own counterpart of ``closure``, if any.
+        """
+        kwargs = dict(kwargs)
+        if closure and closure[0] is not None and self._reopen_config_ is not None:
+            bind = _rebindable(closure[0])
+            return lambda session: declare(session, keyexpr, bind(), **kwargs)
+        return lambda session: declare(session, keyexpr, *closure, **kwargs)
+
+    def _redeclare_(self, declare: Callable[[_Session], Any]) -> Optional[Callable[[_Session], Any]]:
+        "``declare`` if the session may be rebuilt, for ``_Declarations.track``, or ``None``."
+        return declare if self._reopen_config_ is not None else None
+
+    @property
+    def namespace(self) -> Optional[str]:
+        "The namespace the session was opened with, if any."
+        return self._namespace_
 
     def put(self, keyexpr: IntoKeyExpr, value: IntoValue, encoding=None,
             priority: Priority = None, congestion_control: CongestionControl = None,
-            sample_kind: SampleKind = None):
+            sample_kind: SampleKind = None, express: bool = None,
+            attachment: Dict[Union[bytes, str], Union[bytes, str]] = None):
         """
         Sends a value over Zenoh.
 
@@ -122,6 +730,10 @@ class Session(_Session):
         :param priority: The priority to use when routing the published data
         :param congestion_control: The congestion control to use when routing the published data
         :param sample_kind: The kind of sample to send
+        :param express: if ``True``, the publication is sent right away rather than batched with others,
+            which lowers its latency at the cost of throughput
+        :param attachment: name/value pairs sent along with the value, as ``bytes`` or ``str``,
+            which subscribers read from ``Sample.attachment``, and interceptors may replace
 
         :Examples:
 
@@ -129,6 +741,7 @@ class Session(_Session):
         >>> s = zenoh.open({})
         >>> s.put('key/expression', 'value')
         """
+        self._callback_errors_.raise_pending()
         value = Value(value, encoding)
         keyexpr = KeyExpr(keyexpr)
         kwargs = dict()
@@ -138,6 +751,10 @@ class Session(_Session):
             kwargs['congestion_control'] = congestion_control
         if sample_kind is not None:
             kwargs['sample_kind'] = sample_kind
+        if express is not None:
+            kwargs['express'] = express
+        if attachment is not None:
+            kwargs['attachment'] = attachment
         return super().put(keyexpr, value, **kwargs)
 
     def config(self) -> Config:
@@ -146,19 +763,21 @@ class Session(_Session):
         Note that in Python specifically, the config you passed to the session becomes the result of this
         function if you passed one, letting you keep using it.
         """
-        return super().config()
+        return Config._upgrade_(super().config())
 
     def delete(self, keyexpr: IntoKeyExpr,
-               priority: Priority = None, congestion_control: CongestionControl = None):
+               priority: Priority = None, congestion_control: CongestionControl = None, express: bool = None):
         """
         Deletes the values associated with the keys included in ``keyexpr``.
         
-        This uses the same mechanisms as ``session.put``, and will be received by subscribers.
-        This operation is especially useful with storages.
+        This uses the same mechanisms as ``session.put``, and will be received by subscribers,
+        as a sample whose ``kind`` is ``SampleKind.DELETE()`` and whose payload is empty.
+        This operation is especially useful with storages, which drop the values they hold for the deleted keys.
 
         :param keyexpr: The key expression to publish
         :param priority: The priority to use when routing the delete
         :param congestion_control: The congestion control to use when routing the delete
+        :param express: if ``True``, the delete is sent right away rather than batched with other messages
 
         :Examples:
 
@@ -166,15 +785,19 @@ class Session(_Session):
         >>> s = zenoh.open({})
         >>> s.delete('key/expression')
         """
+        self._callback_errors_.raise_pending()
         keyexpr = KeyExpr(keyexpr)
         kwargs = dict()
         if priority is not None:
             kwargs['priority'] = priority
         if congestion_control is not None:
             kwargs['congestion_control'] = congestion_control
+        if express is not None:
+            kwargs['express'] = express
         return super().delete(keyexpr, **kwargs)
 
-    def get(self, selector: IntoSelector, handler: IntoHandler[Reply, Any, Receiver], consolidation: QueryConsolidation = None, target: QueryTarget = None, value: IntoValue = None) -> Receiver:
+    def get(self, selector: IntoSelector, handler: IntoHandler[Reply, Any, Receiver] = None, consolidation: QueryConsolidation = None, target: QueryTarget = None, value: IntoValue = None,
+            storages_only: bool = False, timeout: float = None) -> Receiver:
         """
         Emits a query, which queryables with intersecting selectors will be able to reply to.
 
@@ -182,11 +805,18 @@ class Session(_Session):
         The `handler` can typically be a queue, a single callback or a pair of callbacks.
         The `handler`'s receiver is returned by the `get` function.
 
+        Without `handler`, the replies are delivered to a ``Queue``, which is returned: it may be iterated over as the replies
+        arrive, with ``for`` or with ``async for`` from a coroutine, rather than after collecting them all, which lets
+        applications start processing large datasets served by many storages right away.
+
         :param selector: The selection of keys to query
         :param handler:
         :param consolidation: The consolidation to apply to replies
         :param target: The queryables that should be target to this query
         :param value: An optional value to attach to this query
+        :param storages_only: if ``True``, only complete queryables, such as storages, are targeted,
+            so that reads of persisted state don't reach ephemeral queryables. Shorthand for ``target=QueryTarget.ALL_COMPLETE()``.
+        :param timeout: how long to wait for replies, in seconds, before the query is considered over
         :return: The receiver of the handler
         :rtype: Receiver
 
@@ -202,6 +832,12 @@ class Session(_Session):
         ...     except:
         ...         print(f"Received ERROR: '{reply.err.payload.decode('utf-8')}'")
 
+        Lazily, from a coroutine:
+
+        >>> async def main():
+        ...     async for reply in s.get('key/expression'):
+        ...         print(f"Received '{reply.ok.key_expr}'")
+
         Using a single callback:
 
         >>> s.get('key/expression', lambda reply:
@@ -217,7 +853,14 @@ class Session(_Session):
         ...     lambda:
         ...         print("No more replies")))
         """
-        handler = Handler(handler, lambda x: Reply(x))
+        self._callback_errors_.raise_pending()
+        if storages_only:
+            if target is not None and target != QueryTarget.ALL_COMPLETE():
+                raise ValueError("`storages_only` can't be combined with another `target`")
+            target = QueryTarget.ALL_COMPLETE()
+        if handler is None:
+            handler = Queue()
+        handler = Handler(handler, lambda x: Reply(x), on_error=self._callback_errors_)
         kwargs = dict()
         if consolidation is not None:
             kwargs["consolidation"] = consolidation
@@ -225,9 +868,125 @@ class Session(_Session):
             kwargs["target"] = target
         if value is not None:
             kwargs["value"] = Value(value)
+        if timeout is not None:
+            kwargs["timeout"] = timeout
         super().get(Selector(selector), handler.closure, **kwargs)
         return handler.receiver
 
+    async def get_async(self, selector: IntoSelector, consolidation: QueryConsolidation = None, target: QueryTarget = None, value: IntoValue = None,
+                        storages_only: bool = False, timeout: float = None) -> List[Reply]:
+        """
+        The awaitable counterpart of ``get``, resolving to the list of all the replies once the query is complete.
+
+        The replies are collected by the bindings' threads, and the query is sent from the event loop's default executor,
+        so that awaiting it never blocks the event loop.
+
+        :Examples:
+
+        >>> import zenoh, asyncio
+        >>> async def main():
+        ...     s = zenoh.open({})
+        ...     for reply in await s.get_async('demo/example/**'):
+        ...         print(reply.ok.payload)
+        >>> asyncio.run(main())
+        """
+        loop = asyncio.get_running_loop()
+        done = loop.create_future()
+        replies = []
+        def complete():
+            if not done.done():
+                done.set_result(replies)
+        def drop():
+            try:
+                loop.call_soon_threadsafe(complete)
+            except RuntimeError:
+                # the event loop was closed while the query was running
+                pass
+        get = lambda: self.get(selector, Closure((replies.append, drop)), consolidation, target, value, storages_only, timeout)
+        await loop.run_in_executor(None, get)
+        return await done
+
+    def get_history(self, keyexpr: IntoKeyExpr, start: Union[datetime, str] = None, stop: Union[datetime, str] = None,
+                    consolidation: QueryConsolidation = None, target: QueryTarget = None, timeout: float = None) -> List[Reply]:
+        """
+        Queries the values of ``keyexpr`` stored between ``start`` and ``stop``, typically from time-series storages.
+
+        ``start`` and ``stop`` may be ``datetime`` objects (naive ones being taken as local time),
+        or strings in the storages' time range syntax, such as ``"now(-1h)"``. Omitting either leaves the range open on that end.
+
+        All replies are awaited, and returned sorted by timestamp, oldest first.
+        Replies without timestamps come first, and error replies last.
+
+        :Examples:
+
+        >>> import zenoh, datetime
+        >>> s = zenoh.open({})
+        >>> for reply in s.get_history('demo/series', start=datetime.datetime.now() - datetime.timedelta(hours=1)):
+        ...     print(reply.ok.timestamp, reply.ok.payload)
+        """
+        keyexpr = KeyExpr(keyexpr)
+        selector = f"{keyexpr}?_time=[{_time_bound(start)}..{_time_bound(stop)}]"
+        kwargs = dict()
+        if consolidation is not None:
+            kwargs["consolidation"] = consolidation
+        if target is not None:
+            kwargs["target"] = target
+        replies = self.get(selector, ListCollector(timeout), **kwargs)()
+        untimed, timed, errors = [], [], []
+        for reply in replies:
+            if not reply.is_ok:
+                errors.append(reply)
+            elif reply.ok.timestamp is None:
+                untimed.append(reply)
+            else:
+                timed.append(reply)
+        timed.sort(key=lambda reply: reply.ok.timestamp)
+        return untimed + timed + errors
+
+    def rpc_call(self, keyexpr: IntoKeyExpr, request: IntoValue = None, timeout: float = 10.0) -> Value:
+        """
+        Calls the remote procedure served on ``keyexpr`` by ``Session.rpc_serve``, returning its result.
+
+        Only the first reply is considered. Raises a ``ZError`` carrying the remote exception's description if the procedure failed,
+        and a ``TimeoutError`` if no reply arrived within ``timeout`` seconds.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> print(s.rpc_call('demo/rpc/add', '[1, 2]').payload)
+        """
+        replies = self.get(keyexpr, Queue(), target=QueryTarget.BEST_MATCHING(), value=request, timeout=timeout)
+        try:
+            reply = replies.get(timeout)
+        except (StopIteration, TimeoutError):
+            raise TimeoutError(f"No reply to RPC call on {keyexpr} within {timeout}s")
+        if not reply.is_ok:
+            raise ZError(reply.err.payload.decode("utf-8", errors="replace"))
+        return reply.ok.value
+
+    def rpc_serve(self, keyexpr: IntoKeyExpr, handler: Callable[[Optional[Value]], IntoValue]) -> Queryable:
+        """
+        Serves a remote procedure on ``keyexpr``, callable through ``Session.rpc_call``.
+
+        ``handler`` is called with the request's value (``None`` if the caller sent none), and its result is sent as the single reply.
+        If it raises, the exception is sent back to the caller, which raises it as a ``ZError``.
+
+        :Examples:
+
+        >>> import zenoh, json
+        >>> s = zenoh.open({})
+        >>> rpc = s.rpc_serve('demo/rpc/add', lambda request: str(sum(json.loads(request.payload))))
+        """
+        def serve(query: Query):
+            try:
+                result = handler(query.value)
+            except Exception as e:
+                query.reply_err(f"{type(e).__name__}: {e}")
+                return
+            query.reply(Sample(query.key_expr, Value(result)))
+        return self.declare_queryable(keyexpr, Handler(serve), complete=True)
+
     def declare_keyexpr(self, keyexpr: IntoKeyExpr) -> KeyExpr:
         """Informs Zenoh that you intend to use the provided Key Expression repeatedly.
 
@@ -237,9 +996,10 @@ class Session(_Session):
         a queryable, or a publisher will also inform Zenoh of your intent to use their
         key expressions repeatedly.
         """
-        return KeyExpr(super().declare_keyexpr(KeyExpr(keyexpr)))
+        return self._declarations_.track_keyexpr(KeyExpr(super().declare_keyexpr(KeyExpr(keyexpr))))
 
-    def declare_queryable(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Query, Any, Any], complete: bool = None):
+    def declare_queryable(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Query, Any, Any] = None, complete: bool = None,
+                          executor: concurrent.futures.Executor = None, cache: Cache = None):
         """Declares a queryable, which will receive queries intersecting with ``keyexpr``.
 
         These queries are passed to the `handler` as instances of the `Query` class.
@@ -247,6 +1007,24 @@ class Session(_Session):
         The `handler`'s receiver is returned as the `receiver` field of the returned `Queryable`.
         The replies can be sent back by calling the `reply`function of the `Query`.
 
+        Without `handler`, the queries are queued, up to 256 of them, for the application to process them at its own pace,
+        by iterating over the returned `Queryable` or through its ``recv`` and ``try_recv`` methods. Each query is finalized,
+        ending it for the querier, once its last reference is dropped: it should then be dropped as soon as replied to.
+        When the queue is full, the reception of further queries is held back until some are consumed.
+
+        When `handler` is a plain callback, it may also simply ``return`` its reply, or ``yield`` its replies if it is a
+        generator function: samples are sent as is, while other values are sent on the query's key expression, so that
+        a returned list is a single JSON reply. The query is finalized once the callback returns or its generator is exhausted.
+
+        With ``executor``, a plain callback ``handler`` is submitted to ``executor`` for each query, rather than called
+        from zenoh's threads. Queries can only be replied to from the process that received them, so ``executor`` can't be
+        a ``concurrent.futures.ProcessPoolExecutor``.
+
+        With ``cache``, a ``Cache``, the replies sent to each query are recorded by the bindings once the query is finalized,
+        and the following queries with the same selector are answered from them, without reaching `handler`,
+        until they are ``cache.ttl`` seconds old. Queries carrying a value are never cached. This suits handlers
+        whose replies are expensive to compute and may be a little stale, such as those polled by dashboards.
+
         :Examples:
 
         Using a callback:
@@ -256,6 +1034,15 @@ class Session(_Session):
         >>> qabl = s.declare_queryable('key/expression', lambda query:
         ...     query.reply(zenoh.Sample('key/expression', 'value')))
 
+        Using a generator:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> def replies(query):
+        ...     for i in range(3):
+        ...         yield zenoh.Sample(f'key/expression/{i}', 'value')
+        >>> qabl = s.declare_queryable('key/expression/*', replies)
+
         Using a queue:
 
         >>> import zenoh
@@ -266,26 +1053,191 @@ class Session(_Session):
         ...     query.reply(zenoh.Sample('key/expression', 'value'))
         ...     del query
 
+        Iterating over the queries:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> for query in s.declare_queryable('key/expression'):
+        ...     query.reply(zenoh.Sample('key/expression', 'value'))
+        ...     del query
+
         IMPORTANT: due to how RAII and Python work, you MUST bind this function's return value to a variable in order for it to function as expected.
         This is because as soon as a value is no longer referenced in Python, that value's destructor will run, which will undeclare your queryable, stopping it immediately.
         """
-        handler = Handler(handler, lambda x: Query(x))
+        self._callback_errors_.raise_pending()
+        if handler is None:
+            if executor is not None:
+                raise ValueError("`executor` only applies to plain callback handlers")
+            handler = Queue(256)
+        elif callable(handler) and not isinstance(handler, (IHandler, IClosure)):
+            handler = _replying(handler)
+            if executor is not None:
+                if isinstance(executor, concurrent.futures.ProcessPoolExecutor):
+                    raise TypeError("queries can't be sent to other processes, `executor` must not be a ProcessPoolExecutor")
+                handler = _executor_closure(handler, executor, self._callback_errors_)
+        elif executor is not None:
+            raise ValueError("`executor` only applies to plain callback handlers")
+        handler = Handler(handler, lambda x: Query(x), prevent_direct_calls=self._prevent_direct_calls_, on_error=self._callback_errors_)
+        kwargs = dict()
+        if complete is not None:
+            kwargs['complete'] = complete
+        if cache is not None:
+            kwargs['cache'] = (cache.ttl, cache.max_entries)
+        keyexpr = KeyExpr(keyexpr)
+        declare = self._declarer_(_Session.declare_queryable, keyexpr, kwargs, handler.closure)
+        inner = declare(self)
+        return self._declarations_.track(Queryable(inner, handler.receiver), "_inner_", "queryable", keyexpr, kwargs, self._redeclare_(declare))
+
+    def register_eval(self, keyexpr: IntoKeyExpr, fn: Callable[[Dict[str, str]], IntoValue], complete: bool = None) -> Queryable:
+        """
+        Declares a queryable that replies to each query on ``keyexpr`` with the value returned by ``fn``.
+
+        ``fn`` is called with the query's decoded parameters. Its result is sent on the query's key expression,
+        unless it is a ``Sample``. If ``fn`` returns ``None``, no reply is sent; if it raises, its exception is sent back
+        to the querier through ``Query.reply_err``.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> qabl = s.register_eval('demo/eval', lambda params: f"Hello {params.get('name', 'World')}!")
+        """
+        generator = inspect.isgeneratorfunction(fn)
+        def eval(query: Query):
+            query._reply_eval_(fn, generator)
+        return self.declare_queryable(keyexpr, eval, complete)
+
+    def serve_mapping(self, keyexpr_prefix: IntoKeyExpr, mapping: Mapping[str, IntoValue], complete: bool = None) -> Queryable:
+        """
+        Exposes ``mapping`` through a queryable on ``keyexpr_prefix/**``, each of its keys being served on ``keyexpr_prefix/key``.
+
+        The mapping only needs to support ``keys()`` and ``__getitem__``; it is read on every query, so changes to it are
+        visible to the following queries. Matching queried key expressions against the mapping's keys, wildcards included,
+        is done by the bindings, and only matching items are converted into values.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> state = {'temperature': 21.5, 'mode': 'auto'}
+        >>> qabl = s.serve_mapping('demo/thermostat', state)
+        >>> replies = s.get('demo/thermostat/*', zenoh.ListCollector())()
+        """
         kwargs = dict()
         if complete is not None:
             kwargs['complete'] = complete
-        inner = super().declare_queryable(KeyExpr(keyexpr), handler.closure, **kwargs)
-        return Queryable(inner, handler.receiver)
+        keyexpr = KeyExpr(keyexpr_prefix)
+        inner = super().serve_mapping(keyexpr, mapping, Value.autoencode, **kwargs)
+        return self._declarations_.track(Queryable(inner, None), "_inner_", "queryable", keyexpr, kwargs)
+
+    def declare_paged_queryable(self, keyexpr: IntoKeyExpr, source: Callable[[Optional[str], int, Query], Iterable[Sample]],
+                                max_limit: int = 1000, complete: bool = None) -> Queryable:
+        """
+        Declares a queryable serving the samples returned by ``source`` a page at a time, following the convention ``get_paged`` relies on.
+
+        Queries may pass a ``_limit`` parameter, the maximum number of replies they accept (``max_limit`` if absent),
+        which is refused with an error reply if larger than ``max_limit``, and an ``_after`` parameter, the key expression of the last sample of the previous page. Pages list samples by
+        increasing key expression: a page with fewer than ``_limit`` samples is the last one.
+
+        ``source`` is called with the ``after`` key expression (or ``None``), the ``limit`` and the query, and returns the samples
+        of the page, or more of them: those that don't intersect with the query's key expression or don't come after ``after``
+        are dropped, and the others are sorted and cut down to ``limit``. Storages indexing their keys should only read those
+        after ``after``, and ``limit`` of them, so that serving a page doesn't cost the whole key space.
 
-    def declare_publisher(self, keyexpr: IntoKeyExpr, priority: Priority = None, congestion_control: CongestionControl = None):
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> data = {f'demo/items/{i:06}': i for i in range(100_000)}
+        >>> qabl = s.declare_paged_queryable('demo/items/**', lambda after, limit, query:
+        ...     (zenoh.Sample(k, v) for k, v in sorted(data.items()) if after is None or k > after))
+        """
+        if max_limit < 1:
+            raise ValueError("`max_limit` must be positive")
+        def serve(query: Query):
+            try:
+                parameters = query.decode_parameters()
+                limit = int(parameters.get('_limit', max_limit))
+                if limit < 1:
+                    raise ValueError("`_limit` must be positive")
+                # serving fewer samples than asked for would pass for the last page
+                if limit > max_limit:
+                    raise ValueError(f"`_limit` can't exceed {max_limit}")
+            except ValueError as e:
+                query.reply_err(f"invalid paging parameters: {e}")
+                return
+            after = parameters.get('_after')
+            page = []
+            for sample in source(after, limit, query):
+                if not isinstance(sample, Sample):
+                    raise TypeError("`source` must return samples")
+                key = str(sample.key_expr)
+                if (after is None or key > after) and query.key_expr.intersects(sample.key_expr):
+                    page.append((key, sample))
+            page.sort(key=lambda item: item[0])
+            for _, sample in page[:limit]:
+                query.reply(sample)
+        return self.declare_queryable(keyexpr, serve, complete)
+
+    def get_paged(self, selector: IntoSelector, page_size: int = 100, timeout: float = None) -> Iterator[Sample]:
+        """
+        Yields the samples served for ``selector`` by a queryable declared with ``declare_paged_queryable``, querying them ``page_size`` at a time.
+
+        Each page is queried once the previous one was consumed, passing on its last key expression as ``_after``,
+        so that no query's replies grow with the size of the key space. The pages are sent to the best matching
+        queryable. An error reply raises a ``ZError``, as does a ``page_size`` larger than the queryable's ``max_limit``;
+        ``timeout`` applies to each page's query.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> for sample in s.get_paged('demo/items/**', page_size=500):
+        ...     print(sample.key_expr)
+        """
+        if page_size < 1:
+            raise ValueError("`page_size` must be positive")
+        return self._get_pages_(Selector(selector), page_size, timeout)
+
+    def _get_pages_(self, selector: Selector, page_size: int, timeout: Optional[float]) -> Iterator[Sample]:
+        parameters = selector.parameters
+        after = None
+        while True:
+            paging = f"_limit={page_size}"
+            if after is not None:
+                paging += f"&_after={urllib.parse.quote(after, safe='')}"
+            page_selector = Selector(f"{selector.key_expr}?{parameters + '&' if parameters else ''}{paging}")
+            replies = self.get(page_selector, ListCollector(), QueryConsolidation.NONE(), QueryTarget.BEST_MATCHING(), timeout=timeout)()
+            samples = []
+            for reply in replies:
+                if not reply.is_ok:
+                    raise ZError(f"paged query on {page_selector} failed: {reply.err.payload.decode(errors='replace')}")
+                samples.append(reply.ok)
+            samples.sort(key=lambda sample: str(sample.key_expr))
+            yield from samples
+            if len(samples) < page_size:
+                return
+            after = str(samples[-1].key_expr)
+
+    def declare_publisher(self, keyexpr: IntoKeyExpr, priority: Priority = None, congestion_control: CongestionControl = None,
+                          outbox: Outbox = None, express: bool = None):
         """
         Declares a publisher, which may be used to send values repeatedly onto a same key expression.
 
         Written resources that match the given key will only be sent on the network
         if matching subscribers exist in the system.
 
+        With ``outbox``, an ``Outbox``, the publications issued while the session reaches no router nor peer are held back
+        by the bindings rather than lost, and forwarded in the order they were issued once it does again, which the outbox
+        checks twice a second. Note that zenoh timestamps publications when they are sent: forwarded ones are timestamped
+        anew if the session timestamps publications, their original times only being kept in the outbox's file.
+
         :param keyexpr: The key expression to publish to
         :param priority: The priority to use when routing the published data
         :param congestion_control: The congestion control to use when routing the published data
+        :param outbox: an ``Outbox`` holding publications back while the session is disconnected
+        :param express: if ``True``, the publisher's publications are sent right away rather than batched with others,
+            which lowers their latency at the cost of throughput. Publications forwarded by an outbox aren't express.
         :rtype: Publisher
 
         :Examples:
@@ -295,14 +1247,54 @@ class Session(_Session):
         >>> pub = s.declare_publisher('key/expression')
         >>> pub.put('value')
         """
+        self._callback_errors_.raise_pending()
         kwargs = dict()
         if priority is not None:
             kwargs['priority'] = priority
         if congestion_control is not None:
             kwargs['congestion_control'] = congestion_control
-        return Publisher(super().declare_publisher(KeyExpr(keyexpr), **kwargs))
+        if outbox is not None:
+            kwargs['outbox'] = (outbox.path, outbox.max_messages, outbox.max_age)
+        if express is not None:
+            kwargs['express'] = express
+        keyexpr = KeyExpr(keyexpr)
+        declare = self._declarer_(_Session.declare_publisher, keyexpr, kwargs)
+        publisher = Publisher(declare(self), priority, congestion_control, express)
+        return self._declarations_.track(publisher, "_inner_", "publisher", keyexpr, kwargs, self._redeclare_(declare))
+
+    def publish_periodically(self, keyexpr: IntoKeyExpr, value_fn: Callable[[], IntoValue], period: float,
+                             priority: Priority = None, congestion_control: CongestionControl = None) -> PeriodicPublisher:
+        """
+        Publishes the value returned by ``value_fn`` on ``keyexpr`` every ``period`` seconds, from a timer managed by the bindings.
+
+        Returning ``None`` from ``value_fn`` skips a publication. If ``value_fn`` raises, the publication stops,
+        and the exception is raised again by ``PeriodicPublisher.stop``.
 
-    def declare_subscriber(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any], reliability: Reliability = None) -> Subscriber:
+        :Examples:
+
+        >>> import zenoh, time
+        >>> s = zenoh.open({})
+        >>> task = s.publish_periodically('demo/clock', lambda: str(time.time()), 1.0)
+        >>> time.sleep(10)
+        >>> task.stop()
+        """
+        def value():
+            v = value_fn()
+            return None if v is None else Value(v)
+        kwargs = dict()
+        if priority is not None:
+            kwargs['priority'] = priority
+        if congestion_control is not None:
+            kwargs['congestion_control'] = congestion_control
+        keyexpr = KeyExpr(keyexpr)
+        task = PeriodicPublisher(super().publish_periodically(keyexpr, value, period, **kwargs))
+        kwargs['period'] = period
+        return self._declarations_.track(task, "_inner_", "publisher", keyexpr, kwargs)
+
+    def declare_subscriber(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any] = None, reliability: Reliability = None, reuse_samples: bool = False,
+                           batch: Tuple[int, float] = None, changes: bool = False, allowed_origin: Locality = None, sink: str = None,
+                           filter: SampleFilter = None, profile: bool = False, loop: asyncio.AbstractEventLoop = None,
+                           executor: concurrent.futures.Executor = None, overflow: Tuple[int, str] = None) -> Subscriber:
         """
         Declares a subscriber, which will receive any published sample with a key expression intersecting ``keyexpr``.
 
@@ -310,9 +1302,39 @@ class Session(_Session):
         The `handler` can typically be a queue or a callback.
         The `handler`'s receiver is returned as the `receiver` field of the returned `Subscriber`.
 
+        The callback may also be a coroutine function (``async def``), in which case a task is created for each sample on ``loop``,
+        or on an event loop run by the bindings in a thread of their own if ``loop`` isn't passed.
+
         :param keyexpr: The key expression to subscribe to
         :param handler:
         :param reliability: the reliability to use when routing the subscribed samples
+        :param reuse_samples: if ``True``, the same ``Sample`` object is overwritten for each received sample instead of allocating a new one,
+            which lowers the GC pressure of high-rate subscriptions. The sample is then only valid during the callback, which is
+            called directly from zenoh's threads: it must not be stored nor passed to a queue.
+        :param batch: a ``(max_count, max_delay)`` pair: if set, samples are accumulated on the Rust side, and the handler receives lists
+            of up to ``max_count`` samples, at most ``max_delay`` seconds after the first sample of each list was received.
+            This amortizes the cost of calling into Python for high-frequency streams.
+        :param changes: if ``True``, the `handler` receives ``Change`` objects instead of samples.
+        :param allowed_origin: restricts the samples received to those published locally (``Locality.SESSION_LOCAL()``) or remotely (``Locality.REMOTE()``).
+            The filtering happens before the samples reach Python.
+        :param sink: ``"count"`` or ``"latest"``: instead of calling `handler`, which must then be omitted, the samples are
+            handled entirely in Rust, only counting them, or also keeping the latest one. Their state is read through
+            ``Subscriber.count()`` and ``Subscriber.latest()``, which is handy to measure message rates without any Python callback.
+        :param filter: a ``SampleFilter``: the samples it rejects are dropped in Rust, never reaching Python.
+        :param profile: if ``True``, the subscription measures how long its callback waits for and holds the GIL,
+            as well as how long samples wait in the handler's queue, see ``Subscriber.profile()``.
+            This helps telling whether the bindings or the callback are the bottleneck, at the cost of some overhead.
+        :param loop: the event loop to run a coroutine function `handler` on.
+        :param executor: a ``concurrent.futures.Executor`` that a plain callback `handler` is submitted to for each sample,
+            rather than called from zenoh's threads, which puts the application in control of the callback's parallelism:
+            unless ``executor`` has a single worker, samples may then be handled concurrently and out of order.
+            With a ``ProcessPoolExecutor``, `handler` must be picklable, such as a module-level function; samples are pickled
+            along with their ``key_expr``, ``value``, ``kind`` and ``timestamp``. Exceptions raised by `handler` are printed.
+        :param overflow: a ``(capacity, policy)`` pair: if set, samples are buffered in Rust, up to ``capacity`` of them, and
+            `handler` is called from a thread of the subscription's own, so that a slow callback doesn't hold zenoh's threads.
+            ``policy`` tells what happens once the buffer is full: ``"block"`` waits for room, holding the zenoh thread
+            delivering samples, and so the publishers putting with ``CongestionControl.BLOCK()``, while ``"drop_oldest"`` and ``"drop_newest"`` drop the oldest
+            buffered sample or the new one. ``Subscriber.dropped`` counts the samples dropped so.
         :rtype: Subscriber
 
         :Examples:
@@ -332,15 +1354,100 @@ class Session(_Session):
         >>> for sample in sub.receiver:
         >>>     print(f"{sample.key_expr}: {sample.payload.decode('utf-8')}")
 
+        Which ``subscribe`` shortens, with a bounded queue:
+
+        >>> for sample in s.subscribe('key/expression'):
+        >>>     print(f"{sample.key_expr}: {sample.payload.decode('utf-8')}")
+
+        Using a coroutine function:
+
+        >>> import zenoh, asyncio
+        >>> async def listener(sample):
+        ...     await asyncio.sleep(1)
+        ...     print(f"Received '{sample.key_expr}'")
+        >>> s = zenoh.open({})
+        >>> sub = s.declare_subscriber('key/expression', listener)
+
         IMPORTANT: due to how RAII and Python work, you MUST bind this function's return value to a variable in order for it to function as expected.
         This is because as soon as a value is no longer referenced in Python, that value's destructor will run, which will undeclare your subscriber, deactivating the subscription immediately.
         """
-        handler = Handler(handler, lambda x: Sample._upgrade_(x))
+        self._callback_errors_.raise_pending()
+        if asyncio.iscoroutinefunction(handler):
+            if reuse_samples:
+                raise ValueError("`reuse_samples` can't be used with a coroutine function, whose samples outlive the callback")
+            handler = _coroutine_closure(handler, loop, self._callback_errors_)
+        elif loop is not None:
+            raise ValueError("`loop` only applies to coroutine function handlers")
+        if executor is not None:
+            if not callable(handler) or isinstance(handler, (IHandler, IClosure)):
+                raise ValueError("`executor` only applies to plain callback handlers")
+            if reuse_samples or profile:
+                raise ValueError("`executor` can't be combined with `reuse_samples` nor `profile`")
+            handler = _executor_closure(handler, executor, self._callback_errors_)
+        keyexpr = KeyExpr(keyexpr)
+        kwargs = dict()
+        if reliability is not None:
+            kwargs['reliability'] = reliability
+        if allowed_origin is not None:
+            kwargs['allowed_origin'] = allowed_origin
+        if filter is not None:
+            kwargs['filter'] = filter
+        if overflow is not None:
+            kwargs['overflow'] = tuple(overflow)
+        if sink is not None:
+            if handler is not None:
+                raise ValueError("`handler` and `sink` are mutually exclusive")
+            if reuse_samples or changes or batch is not None or profile:
+                raise ValueError("`sink` can't be combined with `reuse_samples`, `changes`, `batch` nor `profile`")
+            kwargs['sink'] = sink
+            declare = self._declarer_(_Session.declare_subscriber, keyexpr, kwargs, None)
+            return self._declarations_.track(Subscriber(declare(self)), "_subscriber_", "subscriber", keyexpr, kwargs, self._redeclare_(declare))
+        if handler is None:
+            raise TypeError("declare_subscriber requires either a `handler` or a `sink`")
+        if profile and (reuse_samples or changes or batch is not None):
+            raise ValueError("`profile` can't be combined with `reuse_samples`, `changes` nor `batch`")
+        combined = [name for name, value in [("reuse_samples", reuse_samples), ("changes", changes), ("batch", batch is not None)] if value]
+        if len(combined) > 1:
+            raise ValueError(f"`{combined[0]}` can't be combined with `{combined[1]}`")
+        if reuse_samples:
+            handler = Handler(handler, prevent_direct_calls=False, on_error=self._callback_errors_)
+            kwargs['reuse_samples'] = Sample(keyexpr, b"")
+        elif changes:
+            handler = Handler(handler, lambda x: Change(x), prevent_direct_calls=self._prevent_direct_calls_, on_error=self._callback_errors_)
+            kwargs['changes'] = True
+        elif batch is not None:
+            handler = Handler(handler, lambda batch: [Sample._upgrade_(x) for x in batch], prevent_direct_calls=self._prevent_direct_calls_,
+                              on_error=self._callback_errors_)
+            kwargs['batch'] = batch
+        elif profile:
+            queue_profile = QueueProfile()
+            handler = Handler(handler, lambda x: Sample._upgrade_(x), profile=queue_profile, on_error=self._callback_errors_)
+            kwargs['profile'] = _CallbackProfile()
+        else:
+            handler = Handler(handler, lambda x: Sample._upgrade_(x), prevent_direct_calls=self._prevent_direct_calls_, on_error=self._callback_errors_)
+        declare = self._declarer_(_Session.declare_subscriber, keyexpr, kwargs, handler.closure)
+        subscriber = Subscriber(declare(self), handler.receiver)
+        if reuse_samples:
+            kwargs['reuse_samples'] = True
+        if 'profile' in kwargs:
+            subscriber._profile_ = (kwargs['profile'], queue_profile)
+            kwargs['profile'] = True
+        return self._declarations_.track(subscriber, "_subscriber_", "subscriber", keyexpr, kwargs, self._redeclare_(declare))
+
+    def declare_dispatcher(self, keyexpr: IntoKeyExpr, reliability: Reliability = None) -> Dispatcher:
+        """
+        Declares a single subscription on ``keyexpr``, whose samples are dispatched to the handlers
+        later added to the returned ``Dispatcher`` according to their key expressions.
+
+        Like other declarations, the returned value must be kept alive for the subscription to last.
+        """
+        self._callback_errors_.raise_pending()
+        keyexpr = KeyExpr(keyexpr)
         kwargs = dict()
         if reliability is not None:
             kwargs['reliability'] = reliability
-        s = super().declare_subscriber(KeyExpr(keyexpr), handler.closure, **kwargs)
-        return Subscriber(s, handler.receiver)
+        dispatcher = Dispatcher(super().declare_dispatcher(keyexpr, **kwargs), self._prevent_direct_calls_, self._callback_errors_)
+        return self._declarations_.track(dispatcher, "_inner_", "subscriber", keyexpr, kwargs)
 
     def declare_pull_subscriber(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any], reliability: Reliability = None) -> PullSubscriber:
         """
@@ -364,24 +1471,385 @@ class Session(_Session):
         ...
         >>> sub.pull()
         """
-        handler = Handler(handler, lambda x: Sample._upgrade_(x))
+        self._callback_errors_.raise_pending()
+        handler = Handler(handler, lambda x: Sample._upgrade_(x), prevent_direct_calls=self._prevent_direct_calls_, on_error=self._callback_errors_)
         kwargs = dict()
         if reliability is not None:
             kwargs['reliability'] = reliability
-        s = super().declare_pull_subscriber(KeyExpr(keyexpr), handler.closure, **kwargs)
-        return PullSubscriber(s, handler.receiver)
+        keyexpr = KeyExpr(keyexpr)
+        s = super().declare_pull_subscriber(keyexpr, handler.closure, **kwargs)
+        return self._declarations_.track(PullSubscriber(s, handler.receiver), "_subscriber_", "pull_subscriber", keyexpr, kwargs)
+
+    def workspace(self, prefix: str = None) -> Workspace:
+        """
+        Returns a ``Workspace`` resolving paths relative to ``prefix``,
+        for applications that only need simple put/get/delete/subscribe operations.
+        """
+        return Workspace(self, prefix)
+
+    def add_keyexpr_mapping(self, local: str, remote: str):
+        """
+        Maps the ``local`` key space onto the ``remote`` one for all the operations made through this session from now on,
+        both being of the form ``prefix/**`` (or ``**``, for the whole key space).
+
+        Outgoing key expressions starting with the local prefix (publications, queries, declarations, replies) have it replaced
+        by the remote one, while the key expressions of the samples, queries and replies received get the local prefix back,
+        so that applications can be isolated into a namespace without changing their code. Key expressions that don't start
+        with the prefix chunk by chunk (such as ``*/x`` for ``public/**``) are left untouched. When several mappings apply,
+        the one with the longest prefix wins. The rewriting is done by the bindings, without calling into Python.
+
+        Registering a mapping for an already mapped ``local`` key space replaces it.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> s.add_keyexpr_mapping('public/**', 'tenant42/public/**')
+        >>> s.put('public/hello', 'Hello World!') # published on 'tenant42/public/hello'
+        """
+        super().add_keyexpr_mapping(local, remote)
+
+    def remove_keyexpr_mapping(self, local: str) -> bool:
+        "Removes the mapping of the ``local`` key space, returning whether there was one."
+        return super().remove_keyexpr_mapping(local)
 
-    def close(self):
+    def keyexpr_mappings(self) -> List[Tuple[str, str]]:
+        "Lists the ``(local, remote)`` key space mappings of this session."
+        return super().keyexpr_mappings()
+
+    def add_interceptor(self, callback: Callable[[Sample, Dict[bytes, bytes]], Union[bool, Dict[bytes, bytes], None]] = None, keyexpr: IntoKeyExpr = None,
+                        filter: SampleFilter = None, ingress: bool = True, egress: bool = True) -> int:
+        """
+        Registers a hook on all the samples this session publishes (``egress``: puts, deletes and publishers)
+        and receives through its subscribers (``ingress``), returning an id for ``remove_interceptor``.
+
+        The samples are selected by ``keyexpr`` and ``filter`` in Rust, without taking the GIL. Without ``callback``,
+        the selected samples are simply dropped. Otherwise ``callback`` is called with each of them and its attachment,
+        as a ``dict`` of ``bytes`` (empty if none), from whichever thread handles it. It returns ``False`` to drop
+        the sample, ``True`` or ``None`` to let it through as is, or a ``dict`` to let it through with that attachment
+        instead, names and values being ``bytes`` or ``str``. Payloads can't be changed. Hooks run in the order they were
+        added; exceptions they raise are printed, and let the sample through.
+
+        Key expressions are seen as in the session's key space, before key expression mappings are applied.
+
+        :Example:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> audit = s.add_interceptor(lambda sample, attachment: print(f"{sample.kind} {sample.key_expr}"))
+        >>> tag = s.add_interceptor(lambda sample, attachment: {**attachment, b"origin": b"site-a"}, ingress=False)
+        >>> s.add_interceptor(keyexpr='demo/secret/**', egress=False) # never deliver these to subscribers
+        """
+        if callback is not None:
+            def hook(sample, attachment, callback=callback):
+                return callback(Sample._upgrade_(sample), attachment)
+            callback = hook
+        return super().add_interceptor(callback, None if keyexpr is None else KeyExpr(keyexpr), filter, ingress, egress)
+
+    def remove_interceptor(self, id: int) -> bool:
+        "Removes the interceptor ``id``, returning whether it existed."
+        return super().remove_interceptor(id)
+
+    def set_rate_limit(self, bytes_per_s: Optional[float], burst: float = None):
+        """
+        Artificially limits the session's outgoing bandwidth to ``bytes_per_s``, to test how applications behave under congestion;
+        ``None`` lifts the limit.
+
+        The limit applies to the payloads the session puts, directly or through its publishers, in Rust: up to ``burst`` bytes
+        (which defaults to a second's worth) may be sent at once. Beyond that, what happens depends on the congestion control
+        of each put, as it would when zenoh's queues are full: with ``CongestionControl.DROP()``, the sample is silently dropped,
+        while with ``CongestionControl.BLOCK()``, the call blocks until the bandwidth allows it through.
+        Deletions, queries and replies are not limited.
+
+        :Example:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> s.set_rate_limit(10_000) # 10kB/s
+        >>> pub = s.declare_publisher('demo/video', congestion_control=zenoh.CongestionControl.BLOCK())
+        """
+        super().set_rate_limit(bytes_per_s, burst)
+
+    @property
+    def rate_limit(self) -> Optional[Tuple[float, float]]:
+        "The session's ``(bytes_per_s, burst)`` outgoing bandwidth limit, if any: see ``set_rate_limit``."
+        return super().rate_limit
+
+    @property
+    def testing(self) -> 'FaultInjector':
+        "The session's failure injection hooks, for resilience tests: see ``zenoh.testing.FaultInjector``."
+        injector = getattr(self, '_testing_', None)
+        if injector is None:
+            from .testing import FaultInjector
+            injector = self._testing_ = FaultInjector(self)
+        return injector
+
+    def metrics(self) -> str:
+        """
+        Returns the session's statistics in the Prometheus text exposition format.
+
+        These include the number of puts, deletes and queries sent, the number of samples and queries received,
+        and the number of currently declared publishers, subscribers and queryables, all labelled with the session's ``zid``.
+        """
+        return super().metrics()
+
+    def stats(self, by_key: bool = False) -> Dict[str, Any]:
+        """
+        Returns the session's statistics: the counters of ``metrics``, by name, along with the session's ``uptime`` in seconds.
+
+        With ``by_key``, returns the traffic attributed to each key expression prefix instead, as dictionaries of
+        ``messages_sent``, ``bytes_sent``, ``messages_received`` and ``bytes_received``, which shows the topics dominating
+        a constrained link. Prefixes are the first chunk of key expressions by default, see ``set_stats_key_depth``.
+        The traffic counted is that of puts, deletions, samples received by subscribers and replies received to queries,
+        their bytes being those of their payloads, under the key expressions they have on the network.
+        Beyond 1024 distinct prefixes, the traffic of new ones is attributed to ``"<other>"``.
+
+        :Example:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> for prefix, traffic in sorted(s.stats(by_key=True).items(), key=lambda item: -item[1]['bytes_received']):
+        ...     print(prefix, traffic['bytes_received'])
+        """
+        return super().stats(by_key)
+
+    def set_stats_key_depth(self, depth: int):
+        """
+        Sets how many chunks of key expressions ``stats(by_key=True)`` attributes traffic to, ``2`` counting ``demo/a/x``
+        and ``demo/a/y`` as ``demo/a``, and ``0`` disabling the attribution, which then costs nothing.
+        This resets the traffic counted so far.
+        """
+        if depth < 0:
+            raise ValueError("`depth` must not be negative")
+        super().set_stats_key_depth(depth)
+
+    @property
+    def stats_key_depth(self) -> int:
+        "How many chunks of key expressions traffic is attributed to, see ``set_stats_key_depth``."
+        return super().stats_key_depth
+
+    def serve_metrics(self, keyexpr: IntoKeyExpr = None) -> Queryable:
+        """
+        Declares a queryable replying with ``Session.metrics``, on ``zenoh-python/<zid>/metrics`` by default.
+
+        Replies are built without calling into Python, so they are sent even while the interpreter is busy.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> qabl = s.serve_metrics()
+        >>> for reply in s.get('zenoh-python/*/metrics', zenoh.Queue()):
+        ...     print(reply.ok.payload.decode())
+        """
+        if keyexpr is None:
+            keyexpr = f"zenoh-python/{super().zid()}/metrics"
+        keyexpr = KeyExpr(keyexpr)
+        inner = super().serve_metrics(keyexpr)
+        return self._declarations_.track(Queryable(inner, None), "_inner_", "queryable", keyexpr, dict())
+
+    def enable_healthcheck(self, keyexpr: IntoKeyExpr) -> Queryable:
+        """
+        Declares a queryable on ``keyexpr`` replying with a JSON health report, for orchestrators to probe.
+
+        The report holds ``status`` (always ``"alive"``), the session's ``zid``, its ``uptime`` in seconds,
+        and the counters of ``Session.metrics`` under ``stats``. It is built without calling into Python,
+        so a busy interpreter doesn't make the service look dead.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> health = s.enable_healthcheck('demo/service/health')
+        >>> for reply in s.get('demo/service/health', zenoh.Queue()):
+        ...     print(reply.ok.deserialize(dict)['uptime'])
+        """
+        keyexpr = KeyExpr(keyexpr)
+        inner = super().enable_healthcheck(keyexpr)
+        return self._declarations_.track(Queryable(inner, None), "_inner_", "queryable", keyexpr, dict())
+
+    def register_schema(self, keyexpr: IntoKeyExpr, schema: IntoValue, encoding: Encoding = None) -> Queryable:
+        """
+        Publishes ``schema`` as the schema of the data published on ``keyexpr``, for ``Session.schema_of`` to fetch,
+        for as long as the returned queryable is kept alive.
+
+        The schema is served on the well-known ``@/schema/<keyexpr>`` key expression, by a queryable replying without
+        calling into Python. It may be anything a ``Value`` holds: a ``dict`` is served as a JSON Schema, with the
+        ``application/json`` encoding, while a protobuf descriptor would be passed as bytes with an ``encoding`` telling it apart.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> schema = s.register_schema('sensors/*/temperature', {"type": "object", "properties": {"celsius": {"type": "number"}}})
+        """
+        keyexpr = KeyExpr(keyexpr)
+        value = Value(schema, encoding)
+        declare = lambda session: _register_schema(session, keyexpr, value)
+        queryable = Queryable(declare(self), None)
+        return self._declarations_.track(queryable, "_inner_", "queryable", keyexpr, {"schema": True}, self._redeclare_(declare))
+
+    def schema_of(self, keyexpr: IntoKeyExpr, timeout: float = 2.0, max_age: float = 60.0, refresh: bool = False) -> Optional[Value]:
+        """
+        Fetches the schema registered for ``keyexpr`` with ``Session.register_schema``, by this session or another,
+        returning ``None`` if none replied within ``timeout`` seconds.
+
+        ``keyexpr`` is typically the key expression of a received sample: a schema registered on a key expression
+        intersecting it answers. If several do, the first reply wins. The schemas fetched are cached in Rust for ``max_age``
+        seconds, so that subscribers may call this for each sample; ``refresh`` bypasses the cache.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> schema = s.schema_of('sensors/kitchen/temperature')
+        >>> if schema is not None:
+        ...     print(schema.deserialize(dict))
+        """
+        schema = _schema_of(self, KeyExpr(keyexpr), timeout, max_age, refresh)
+        return None if schema is None else Value._upgrade_(schema)
+
+    def export_metrics(self, port: int = 0, address: str = "127.0.0.1") -> MetricsExporter:
+        """
+        Serves ``Session.metrics`` over HTTP on ``address:port``, for Prometheus to scrape.
+
+        With ``port=0``, the system picks a free port, available as ``MetricsExporter.port``.
+        The server runs on a thread of its own, and keeps answering while the interpreter is busy.
+        """
+        return MetricsExporter(super().export_metrics(address, port))
+
+    def close(self, drain_timeout: float = None) -> bool:
         """Attempts to close the Session.
-        
-        The session will only be closed if all publishers, subscribers and queryables based on it have been undeclared, and there are no more python references to it.
+
+        All the publishers, subscribers and queryables declared through it are undeclared, and its periodic publications are stopped,
+        which also joins the threads running their handlers.
+        The session itself will only be closed once there are no more python references to it.
+
+        With ``drain_timeout``, the session first spends up to that many seconds on what it still has to deliver:
+        the publications held back by outboxes are forwarded if the session reaches the network, the threads running
+        handlers are joined once they handled the samples and queries already queued, and the calls queued on the
+        session's ``callback_threads`` are run. Whatever is left once the timeout expires keeps being handled in the
+        background, but ``close`` returns ``False`` rather than waiting for it. Without ``drain_timeout``, the handler
+        threads are joined however long they take, and the held back publications are forwarded if the session reaches
+        the network right away, staying in their outboxes otherwise.
+
+        The publications already handed to zenoh are sent from its transmission queues on its own.
+
+        :param drain_timeout: how long to wait for the pending publications and callbacks, in seconds
+        :returns: whether everything pending was delivered before ``drain_timeout`` expired, always ``True`` without it
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> if not s.close(drain_timeout=2.0):
+        ...     print("some publications or callbacks were still pending")
         """
-        pass
+        if self._watchdog_ is not None:
+            self._watchdog_.set()
+        self._transports_ = None
+        self._owned_files_()
+        _SESSIONS.discard(self)
+        if drain_timeout is None:
+            handles = self._declarations_.handles()
+            for handle in handles:
+                if isinstance(handle, Publisher) and handle.outbox_pending:
+                    try:
+                        handle.flush_outbox()
+                    except ZError:
+                        # the outbox still holds the publications, in memory at least
+                        pass
+            self._undeclare_all_(handles)
+            return True
+        deadline = time.monotonic() + drain_timeout
+        remaining = lambda: max(0.0, deadline - time.monotonic())
+        handles = self._declarations_.handles()
+        drained = True
+        for handle in handles:
+            if isinstance(handle, Publisher):
+                while handle.outbox_pending and remaining() > 0:
+                    try:
+                        if handle.flush_outbox() == 0:
+                            time.sleep(min(0.05, remaining()))
+                    except ZError:
+                        # the outbox still holds the publications in memory, which flushing keeps forwarding
+                        time.sleep(min(0.05, remaining()))
+                drained = drained and handle.outbox_pending == 0
+        # joining the handler threads has no timeout of its own
+        closing = Thread(target=self._undeclare_all_, args=(handles,), name="zenoh-close", daemon=True)
+        closing.start()
+        closing.join(remaining())
+        drained = super().drain_callbacks(remaining()) and drained
+        return drained and not closing.is_alive()
+
+    @staticmethod
+    def _undeclare_all_(handles: List[Any]):
+        for handle in handles:
+            if isinstance(handle, PeriodicPublisher):
+                try:
+                    handle.stop()
+                except Exception:
+                    # the error has nowhere to go once the session is closing
+                    pass
+            else:
+                handle.undeclare()
 
     def info(self):
         "Returns an accessor for informations about this Session"
         return Info(self)
 
+    def links(self) -> List[str]:
+        """Returns the links between this session and the routers it is connected to, such as ``tcp/...`` or ``unixsock-stream/...`` locators.
+
+        This information is obtained by querying the routers' admin space: links to peers are not listed.
+        """
+        zid = str(self.zid())
+        links = []
+        for router in self.routers_zid():
+            for transport in RouterClient(self, router).transports():
+                if transport.zid == zid:
+                    links.extend(transport.links)
+        return links
+
+    def declarations(self) -> List[Declaration]:
+        """Returns the publishers, subscribers, queryables and key expressions currently declared through this session.
+
+        Entities are listed until they are undeclared, or garbage collected.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> sub = s.declare_subscriber('key/expression', lambda sample: None)
+        >>> s.declarations()
+        [Declaration(kind='subscriber', key_expr='key/expression', options={})]
+        """
+        return self._declarations_.snapshot()
+
+    def matching_entities(self, keyexpr: IntoKeyExpr) -> List[MatchingEntity]:
+        """Returns the remote subscribers and queryables whose key expressions intersect with ``keyexpr``.
+
+        This information is obtained by querying the admin space of the routers reachable by this session,
+        meaning that this function blocks until they have all replied, and that entities only known
+        to peers will not be listed.
+
+        :Examples:
+
+        >>> import zenoh
+        >>> s = zenoh.open({})
+        >>> for entity in s.matching_entities('demo/example/**'):
+        ...     print(f"{entity.kind} on '{entity.key_expr}' (via {entity.zid})")
+        """
+        return matching_entities(self, keyexpr)
+
+
+def _time_bound(bound: Union[datetime, str, None]) -> str:
+    "Formats a bound of a ``_time`` selector parameter."
+    if bound is None:
+        return ""
+    if isinstance(bound, datetime):
+        return bound.astimezone(timezone.utc).strftime("%Y-%m-%dT%H:%M:%S.%fZ")
+    return str(bound)
 
 class Info:
     def __init__(self, session: _Session):
@@ -398,3 +1866,26 @@ class Info:
     def peers_zid(self) -> List[ZenohId]:
         "Returns the neighbooring peers' identifiers"
         return [ZenohId._upgrade_(id) for id in self.session.peers_zid()]
+
+    def active_endpoint(self) -> Optional[str]:
+        """
+        Returns the connect endpoint this session currently reaches the network through, without its configuration:
+        the first of ``connect/endpoints``, by order of priority, that one of the session's links leads to.
+        ``None`` if none does, such as while the session is reconnecting.
+
+        The links are read from the session's admin space, under ``@/session/<zid>/transport/unicast/*/link/*``.
+        """
+        zid = self.session.zid()
+        destinations = set()
+        for sample in _admin_get(self.session, f"@/session/{zid}/transport/unicast/*/link/*"):
+            destination = json.loads(sample.payload).get("dst")
+            if destination is not None:
+                destinations.add(_locator(destination))
+        for endpoint in json.loads(self.session.config().get_json(CONNECT_KEY)):
+            if _locator(endpoint) in destinations:
+                return endpoint.split("#", 1)[0]
+        return None
+
+def _locator(endpoint: str) -> str:
+    "Strips an endpoint of its metadata and configuration, leaving the locator links are compared with."
+    return endpoint.split("#", 1)[0].split("?", 1)[0]
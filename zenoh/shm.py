@@ -0,0 +1,89 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+Shared memory payloads, for Python processes on the same host to exchange large samples without copying them
+through their transports, which is how most multi-process Python deployments run.
+
+Shared memory requires the bindings to be built with the ``shared-memory`` feature
+(``maturin build --features shared-memory``), and both the publishing and the subscribing sessions to enable it in their
+configurations (see ``enable``). The publisher copies each payload once into a segment of its own, created in ``/dev/shm``
+on Linux, accessible to the processes of the same user only. zenoh then sends subscribers on the same host the location
+of the payload in that segment, which they map on reception; subscribers on other hosts, or that didn't enable shared
+memory, receive the payload itself. Receiving requires no code: the samples' payloads read from the segment.
+
+A segment is removed when the ``ShmManager`` owning it is garbage collected, or its process exits. Those of processes
+that crashed may remain in ``/dev/shm``, and can be removed once no process maps them anymore.
+"""
+import os
+import uuid
+
+from .config import Config
+from .enums import Encoding
+from .keyexpr import IntoKeyExpr
+from .value import IntoValue, Value
+try:
+    from .zenoh import _ShmManager
+except ImportError:
+    _ShmManager = None
+
+available = _ShmManager is not None
+"Whether the bindings were built with the ``shared-memory`` feature."
+
+def enable(config: Config) -> Config:
+    "Enables shared memory transports in ``config``, which publishers and subscribers must both do. Returns ``config``."
+    config.insert_json5("transport/shared_memory/enabled", "true")
+    return config
+
+class ShmManager:
+    """
+    A shared memory segment of ``size`` bytes, for a process to publish payloads from.
+
+    Segments are identified by ``id``, which must be unique on the host: by default, it is built from the process id
+    and a random suffix. The buffers the payloads are copied to are released once all the subscribers that mapped them
+    dropped them, and are reclaimed when the segment runs out of room, or with ``collect``. A payload that still finds no
+    room raises a ``ZError``, in which case it may be put as usual, without shared memory.
+
+    :Example:
+
+    >>> import zenoh, zenoh.shm
+    >>> s = zenoh.open(zenoh.shm.enable(zenoh.Config()))
+    >>> shm = zenoh.shm.ShmManager(64 * 1024 * 1024)
+    >>> s.put('demo/frame', shm.value(frame_bytes))
+    """
+    def __init__(self, size: int, id: str = None):
+        if _ShmManager is None:
+            raise RuntimeError("these zenoh bindings were built without the `shared-memory` feature")
+        self.id = f"zenoh-python-{os.getpid()}-{uuid.uuid4().hex[:8]}" if id is None else id
+        self.size = size
+        self._inner_ = _ShmManager(self.id, size)
+
+    def value(self, payload: IntoValue, encoding: Encoding = None) -> Value:
+        """
+        Copies ``payload`` into the segment, returning a ``Value`` to put or reply with, as any other.
+
+        Tensors implementing ``__dlpack__`` are copied straight from their memory, as ``Value.from_dlpack`` would:
+        subscribers read them back with ``numpy.from_dlpack(sample)`` or ``torch.from_dlpack(sample)``.
+        """
+        if encoding is None and hasattr(payload, "__dlpack__") and not isinstance(payload, Value):
+            return Value._upgrade_(self._inner_.from_dlpack(payload))
+        value = Value(payload, encoding)
+        return Value._upgrade_(self._inner_.value(value.payload, value.encoding))
+
+    def put(self, session, keyexpr: IntoKeyExpr, payload: IntoValue, encoding: Encoding = None, **kwargs):
+        "A shorthand for ``session.put(keyexpr, self.value(payload, encoding), **kwargs)``."
+        session.put(keyexpr, self.value(payload, encoding), **kwargs)
+
+    def collect(self) -> int:
+        "Reclaims the buffers all subscribers released, returning how many bytes were."
+        return self._inner_.collect()
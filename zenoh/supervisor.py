@@ -0,0 +1,184 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+A watchdog for unattended deployments, such as kiosks and edge nodes, reopening sessions whose runtime stopped responding.
+"""
+import logging
+import uuid
+from threading import Event, Lock, Thread
+from typing import Any, Callable, List, Optional
+
+from .closures import ListCollector
+from .session import Session
+
+_LOGGER = logging.getLogger("zenoh")
+
+class Registration:
+    "An entity declared by a ``Supervisor`` on each session it opens, as returned by ``Supervisor.register``."
+    def __init__(self, factory: Callable[[Session], Any]):
+        self.factory = factory
+        self._entity_ = None
+
+    @property
+    def entity(self) -> Any:
+        "What ``factory`` returned for the current session, such as a subscriber, or ``None`` if it raised."
+        return self._entity_
+
+class Supervisor:
+    """
+    Keeps a session opened by ``open_session`` alive, probing its runtime every ``interval`` seconds.
+
+    zenoh doesn't tell whether its keepalives progress, so the probe goes through the runtime itself: the supervisor
+    declares a healthcheck (see ``Session.enable_healthcheck``) on a private key expression, and queries it. Its replies
+    are built in Rust, so a busy interpreter doesn't fail the probe; but a runtime whose threads are dead, deadlocked or
+    starved doesn't reply within ``timeout`` seconds. ``failures`` probes failing in a row make the session unresponsive.
+
+    ``on_event`` is then called with ``"unresponsive"`` and the session, and with ``restart``, the session is closed in the
+    background and replaced by a new one from ``open_session``, on which the registered entities are declared again.
+    ``on_event`` is called with ``"reopened"`` and the new session once they are, or with ``"reopen_failed"`` and the
+    exception if ``open_session`` raised, in which case the next probe retries. Exceptions raised by ``on_event``, and by
+    the factories of registrations, are logged to the ``zenoh`` logger.
+
+    The first session is opened by the constructor, which raises what ``open_session`` raises. The probes then run on a
+    daemon thread, until ``stop`` is called or the supervisor is used as a context manager and exited.
+
+    :Example:
+
+    >>> import zenoh, zenoh.supervisor
+    >>> supervisor = zenoh.supervisor.Supervisor(lambda: zenoh.open({}), on_event=lambda event, detail: print(event))
+    >>> sub = supervisor.register(lambda s: s.declare_subscriber('kiosk/commands', handle_command))
+    >>> supervisor.session.put('kiosk/status', 'started')
+    """
+    def __init__(self, open_session: Callable[[], Session], interval: float = 5.0, timeout: float = 2.0, failures: int = 2,
+                 restart: bool = True, on_event: Callable[[str, Any], Any] = None):
+        if not (interval > 0 and timeout > 0):
+            raise ValueError("`interval` and `timeout` must be positive")
+        if failures < 1:
+            raise ValueError("`failures` must be at least 1")
+        self.open_session = open_session
+        self.interval = interval
+        self.timeout = timeout
+        self.failures = failures
+        self.restart = restart
+        self.on_event = on_event
+        self._lock_ = Lock()
+        self._registrations_: List[Registration] = []
+        self._session_: Optional[Session] = None
+        self._probe_ = None
+        self._stopped_ = Event()
+        self._open_()
+        self._thread_ = Thread(target=self._run_, name="zenoh-supervisor", daemon=True)
+        self._thread_.start()
+
+    @property
+    def session(self) -> Optional[Session]:
+        "The session currently supervised, or ``None`` while it couldn't be reopened."
+        return self._session_
+
+    def register(self, factory: Callable[[Session], Any]) -> Registration:
+        "Calls ``factory`` with the current session, and with each session the supervisor reopens, keeping what it returns alive."
+        registration = Registration(factory)
+        with self._lock_:
+            self._registrations_.append(registration)
+            if self._session_ is not None:
+                self._declare_(registration, self._session_)
+        return registration
+
+    def stop(self):
+        "Stops probing. The current session stays open."
+        self._stopped_.set()
+
+    def __enter__(self) -> 'Supervisor':
+        return self
+
+    def __exit__(self, *args):
+        self.stop()
+
+    def _open_(self):
+        session = self.open_session()
+        self._probe_key_ = f"zenoh-python/{session.zid()}/supervisor/{uuid.uuid4().hex}"
+        self._probe_ = session.enable_healthcheck(self._probe_key_)
+        with self._lock_:
+            self._session_ = session
+            for registration in self._registrations_:
+                self._declare_(registration, session)
+
+    @staticmethod
+    def _declare_(registration: Registration, session: Session):
+        try:
+            registration._entity_ = registration.factory(session)
+        except Exception:
+            registration._entity_ = None
+            _LOGGER.exception("a supervised entity failed to be declared")
+
+    def _notify_(self, event: str, detail: Any):
+        if self.on_event is not None:
+            try:
+                self.on_event(event, detail)
+            except Exception:
+                _LOGGER.exception("the supervisor's `on_event` callback raised")
+
+    def _responsive_(self) -> bool:
+        session, results = self._session_, []
+        def probe():
+            try:
+                replies = session.get(self._probe_key_, ListCollector(self.timeout), timeout=self.timeout)()
+                results.append(any(reply.is_ok for reply in replies))
+            except Exception:
+                results.append(False)
+        # sending the query may itself block on a deadlocked runtime
+        thread = Thread(target=probe, name="zenoh-supervisor-probe", daemon=True)
+        thread.start()
+        thread.join(2 * self.timeout)
+        return bool(results) and results[0]
+
+    def _run_(self):
+        failed = 0
+        while not self._stopped_.wait(self.interval):
+            if self._session_ is None:
+                self._reopen_()
+                continue
+            if self._responsive_():
+                failed = 0
+                continue
+            failed += 1
+            if failed < self.failures:
+                continue
+            failed = 0
+            self._notify_("unresponsive", self._session_)
+            if self.restart:
+                with self._lock_:
+                    stale = [self._session_, self._probe_] + [registration._entity_ for registration in self._registrations_]
+                    self._session_, self._probe_ = None, None
+                    for registration in self._registrations_:
+                        registration._entity_ = None
+                # a stuck runtime may never finish closing, nor undeclaring the entities dropped with the session
+                Thread(target=self._discard_, args=(stale,), name="zenoh-supervisor-close", daemon=True).start()
+                self._reopen_()
+
+    @staticmethod
+    def _discard_(stale: List[Any]):
+        try:
+            stale[0].close()
+        except Exception:
+            _LOGGER.exception("an unresponsive session failed to close")
+        stale.clear()
+
+    def _reopen_(self):
+        try:
+            self._open_()
+        except Exception as e:
+            self._notify_("reopen_failed", e)
+            return
+        self._notify_("reopened", self._session_)
@@ -0,0 +1,329 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+Helpers for fast and hermetic unit tests of zenoh applications.
+"""
+import threading
+import uuid
+from typing import Any, Dict, List, Optional, Tuple
+
+from .closures import IntoHandler, Handler, ListCollector, Queue, IHandler, IClosure
+from .config import Config
+from .enums import Priority, CongestionControl, Reliability, QueryConsolidation, QueryTarget, SampleKind
+from .interfaces import ISession, IPublisher, ISubscriber, IQueryable
+from .keyexpr import KeyExpr, Selector, IntoKeyExpr, IntoSelector
+from .queryable import _replies, _replying
+from .session import Session
+from .value import IntoValue, Value, Sample, ZenohId
+
+class Network:
+    """
+    An in-process zenoh network, whose sessions exchange data without any socket.
+
+    All the sessions a ``Network`` opens share a single zenoh runtime (see ``Session``'s ``share_runtime``),
+    configured by ``Config.test`` to neither listen, connect nor scout: data is routed within the process,
+    as soon as it is put. As there are no transports, there are no leases nor scouting timers to wait for either,
+    and sessions can talk to each other as soon as they are open.
+
+    Since the sessions share a runtime, they also share a zenoh id. Distinct ``Network`` instances are isolated
+    from each other, and from any real zenoh network.
+
+    :Example:
+
+    >>> import zenoh.testing
+    >>> with zenoh.testing.Network() as network:
+    ...     app, probe = network.open(), network.open()
+    ...     sub = probe.declare_subscriber('demo/**', zenoh.Queue())
+    ...     app.put('demo/hello', 'Hello World!')
+    ...     assert sub.receiver.get(timeout=1).payload == b'Hello World!'
+    """
+    def __init__(self, name: str = None):
+        self.name = uuid.uuid4().hex if name is None else name
+        self._sessions_: List[Session] = []
+
+    def open(self, namespace: str = None, **kwargs) -> Session:
+        "Opens a session on this network, passing ``namespace`` and ``kwargs`` on to ``Session``."
+        session = Session(Config.test(self.name), share_runtime=True, namespace=namespace, **kwargs)
+        self._sessions_.append(session)
+        return session
+
+    def close(self):
+        "Closes all the sessions opened on this network, shutting its runtime down."
+        sessions, self._sessions_ = self._sessions_, []
+        for session in sessions:
+            session.close()
+
+    def __enter__(self) -> 'Network':
+        return self
+
+    def __exit__(self, *args):
+        self.close()
+
+class FaultInjector:
+    """
+    Simulates network failures on a session's traffic, as returned by ``Session.testing``.
+
+    Faults are interceptors (see ``Session.add_interceptor``) running in Rust: they apply to the samples the session
+    publishes (``egress``) and those its subscribers receive (``ingress``), but not to queries nor replies.
+    A sample may be dropped, with probability ``drop``, then delayed by ``delay`` plus up to ``jitter`` seconds.
+    Ingress delays are timed off zenoh's threads, so the samples behind a delayed one may overtake it, as with jitter;
+    egress ones block the publishing call.
+
+    ``disconnect`` doesn't close the session's links, which zenoh doesn't allow applications to do: it drops all
+    the session's samples until ``reconnect`` instead, which is what peers observe of a lost link, minus the liveliness
+    they may be tracking.
+
+    :Example:
+
+    >>> import zenoh
+    >>> s = zenoh.open({})
+    >>> fault = s.testing.inject(drop=0.1, delay=0.05, keyexpr='demo/**')
+    >>> s.testing.disconnect(duration=2) # reconnects by itself after 2 seconds
+    >>> s.testing.clear()
+    """
+    def __init__(self, session: Session):
+        self._session_ = session
+        self._faults_: List[int] = []
+        self._disconnection_: Optional[int] = None
+        self._lock_ = threading.Lock()
+
+    def inject(self, drop: float = 0., delay: float = 0., jitter: float = 0., keyexpr: IntoKeyExpr = None,
+               ingress: bool = True, egress: bool = True) -> int:
+        "Starts dropping and/or delaying the samples intersecting with ``keyexpr`` (all if ``None``), returning an id for ``remove``."
+        fault = self._session_.inject_fault(None if keyexpr is None else KeyExpr(keyexpr), drop, delay, jitter, ingress, egress)
+        with self._lock_:
+            self._faults_.append(fault)
+        return fault
+
+    def remove(self, fault: int) -> bool:
+        "Stops the fault ``fault``, returning whether it was still injected."
+        with self._lock_:
+            if fault in self._faults_:
+                self._faults_.remove(fault)
+        return self._session_.remove_interceptor(fault)
+
+    def disconnect(self, duration: float = None):
+        "Cuts the session off, until ``reconnect`` is called or, if set, ``duration`` seconds have elapsed."
+        with self._lock_:
+            if self._disconnection_ is None:
+                self._disconnection_ = self._session_.inject_fault(None, 1.)
+            disconnection = self._disconnection_
+        if duration is not None:
+            timer = threading.Timer(duration, self._reconnect_, (disconnection,))
+            timer.daemon = True
+            timer.start()
+
+    def reconnect(self):
+        "Ends the current ``disconnect``, if any."
+        with self._lock_:
+            disconnection = self._disconnection_
+        if disconnection is not None:
+            self._reconnect_(disconnection)
+
+    def _reconnect_(self, disconnection: int):
+        # timers only end the disconnection they were started with
+        with self._lock_:
+            if self._disconnection_ != disconnection:
+                return
+            self._disconnection_ = None
+        self._session_.remove_interceptor(disconnection)
+
+    @property
+    def disconnected(self) -> bool:
+        return self._disconnection_ is not None
+
+    def clear(self):
+        "Removes all the faults, and reconnects the session."
+        with self._lock_:
+            faults, self._faults_ = self._faults_, []
+        for fault in faults:
+            self._session_.remove_interceptor(fault)
+        self.reconnect()
+
+class MockReply:
+    "A reply to a ``MockSession.get``, mirroring ``Reply``."
+    def __init__(self, sample: Sample = None, err: Value = None):
+        self._sample_ = sample
+        self._err_ = err
+
+    @property
+    def is_ok(self) -> bool:
+        return self._sample_ is not None
+
+    @property
+    def ok(self) -> Sample:
+        if self._sample_ is None:
+            raise ValueError("this reply is an error")
+        return self._sample_
+
+    @property
+    def err(self) -> Value:
+        if self._err_ is None:
+            raise ValueError("this reply isn't an error")
+        return self._err_
+
+    @property
+    def replier_id(self) -> Optional[ZenohId]:
+        "Always ``None``: mock replies have no replier."
+        return None
+
+class MockQuery:
+    "A query passed to the queryables of a ``MockSession``, mirroring ``Query``."
+    def __init__(self, selector: IntoSelector, value: IntoValue = None):
+        self.selector = Selector(selector)
+        self.value = None if value is None else Value(value)
+        self.replies: List[MockReply] = []
+
+    @property
+    def key_expr(self) -> KeyExpr:
+        return self.selector.key_expr
+
+    @property
+    def parameters(self) -> str:
+        return self.selector.parameters
+
+    def decode_parameters(self) -> Dict[str, str]:
+        return self.selector.decode_parameters()
+
+    def reply(self, sample: Sample):
+        self.replies.append(MockReply(sample))
+
+    def reply_err(self, value: IntoValue):
+        self.replies.append(MockReply(err=Value(value)))
+
+    def _reply_all_(self, result, generator: bool):
+        for reply in _replies(result, generator):
+            self.reply(reply if isinstance(reply, Sample) else Sample(self.key_expr, reply))
+
+class MockPublisher(IPublisher):
+    def __init__(self, session: 'MockSession', keyexpr: KeyExpr):
+        self._session_ = session
+        self._key_expr_ = keyexpr
+
+    @property
+    def key_expr(self) -> KeyExpr:
+        return self._key_expr_
+
+    def put(self, value: IntoValue, encoding=None):
+        self._session_.put(self._key_expr_, value, encoding)
+
+    def delete(self):
+        self._session_.delete(self._key_expr_)
+
+    def undeclare(self):
+        pass
+
+class _MockDeclaration(ISubscriber, IQueryable):
+    def __init__(self, declarations: list, keyexpr: KeyExpr, handler: Handler):
+        self._declarations_ = declarations
+        self.key_expr = keyexpr
+        self.closure = handler.closure
+        self.receiver = handler.receiver
+        declarations.append(self)
+
+    def undeclare(self):
+        if self in self._declarations_:
+            self._declarations_.remove(self)
+            drop = self.closure.drop
+            if drop is not None:
+                drop()
+
+class MockSession(ISession):
+    """
+    A stand-in for ``Session`` that never touches the network, for unit tests of application code written against ``ISession``.
+
+    Its subscribers receive the session's own puts and deletes, as well as the samples tests ``inject``;
+    its queries are answered by its own queryables and by the replies tests ``add_reply``. Tests may also ``query``
+    the application's queryables, and inspect the samples it ``published``.
+    Everything happens synchronously, on the calling thread: no handler is ever called from another one.
+
+    :Example:
+
+    >>> import zenoh.testing
+    >>> session = zenoh.testing.MockSession()
+    >>> sub = session.declare_subscriber('demo/**', zenoh.Queue())
+    >>> session.inject('demo/temperature', 21.5)
+    >>> assert sub.receiver.get().payload == b'21.5'
+    """
+    def __init__(self):
+        self.published: List[Sample] = []
+        self._subscribers_: List[_MockDeclaration] = []
+        self._queryables_: List[_MockDeclaration] = []
+        self._replies_: List[Tuple[KeyExpr, Any]] = []
+
+    def inject(self, keyexpr: IntoKeyExpr, value: IntoValue = b"", kind: SampleKind = None):
+        "Delivers a sample to the matching subscribers, as if it had been published elsewhere."
+        sample = Sample(keyexpr, value, kind)
+        for subscriber in list(self._subscribers_):
+            if subscriber.key_expr.intersects(sample.key_expr):
+                subscriber.closure.call(sample)
+
+    def add_reply(self, keyexpr: IntoKeyExpr, value: IntoValue):
+        "Scripts a reply to the queries intersecting with ``keyexpr``, sent on ``keyexpr`` (which must then be non-wild)."
+        self._replies_.append((KeyExpr(keyexpr), value))
+
+    def query(self, selector: IntoSelector, value: IntoValue = None) -> List[MockReply]:
+        "Sends a query to the session's queryables, returning their replies."
+        query = MockQuery(selector, value)
+        for queryable in list(self._queryables_):
+            if queryable.key_expr.intersects(query.key_expr):
+                queryable.closure.call(query)
+        return query.replies
+
+    def put(self, keyexpr: IntoKeyExpr, value: IntoValue, encoding=None,
+            priority: Priority = None, congestion_control: CongestionControl = None):
+        sample = Sample(keyexpr, Value(value, encoding))
+        self.published.append(sample)
+        self.inject(sample.key_expr, sample.value)
+
+    def delete(self, keyexpr: IntoKeyExpr,
+               priority: Priority = None, congestion_control: CongestionControl = None):
+        sample = Sample(keyexpr, b"", SampleKind.DELETE())
+        self.published.append(sample)
+        self.inject(sample.key_expr, b"", SampleKind.DELETE())
+
+    def get(self, selector: IntoSelector, handler: IntoHandler[MockReply, Any, Any] = None, consolidation: QueryConsolidation = None,
+            target: QueryTarget = None, value: IntoValue = None, timeout: float = None):
+        handler = Handler(ListCollector() if handler is None else handler, prevent_direct_calls=False)
+        replies = self.query(selector, value)
+        key_expr = Selector(selector).key_expr
+        replies.extend(MockReply(Sample(k, v)) for k, v in self._replies_ if k.intersects(key_expr))
+        closure = handler.closure
+        for reply in replies:
+            closure.call(reply)
+        if closure.drop is not None:
+            closure.drop()
+        return handler.receiver
+
+    def declare_publisher(self, keyexpr: IntoKeyExpr, priority: Priority = None,
+                          congestion_control: CongestionControl = None) -> MockPublisher:
+        return MockPublisher(self, KeyExpr(keyexpr))
+
+    def declare_subscriber(self, keyexpr: IntoKeyExpr, handler: IntoHandler[Sample, Any, Any],
+                           reliability: Reliability = None) -> ISubscriber:
+        return _MockDeclaration(self._subscribers_, KeyExpr(keyexpr), Handler(handler, prevent_direct_calls=False))
+
+    def declare_queryable(self, keyexpr: IntoKeyExpr, handler: IntoHandler[MockQuery, Any, Any] = None,
+                          complete: bool = None) -> IQueryable:
+        if handler is None:
+            handler = Queue(256)
+        elif callable(handler) and not isinstance(handler, (IHandler, IClosure)):
+            handler = _replying(handler)
+        return _MockDeclaration(self._queryables_, KeyExpr(keyexpr), Handler(handler, prevent_direct_calls=False))
+
+    def close(self, drain_timeout: float = None) -> bool:
+        # deliveries are synchronous, so nothing is ever pending
+        for declaration in self._subscribers_ + self._queryables_:
+            declaration.undeclare()
+        return True
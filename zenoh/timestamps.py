@@ -0,0 +1,126 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+"""
+Helpers to reason about the timestamps of samples and replies, for replicated storages written in Python.
+
+Zenoh timestamps come from the HLC (Hybrid Logical Clock) of the session or router that took them, and are unique
+to it: two samples from the same source can't share a timestamp unless one is a copy of the other. Replicas that
+reply different samples with the same timestamp for a key are thus in conflict, which ``conflicts`` detects.
+
+All the functions accept samples as well as replies, whose ``ok`` samples they consider, ignoring error replies.
+Samples without a timestamp are ignored too, except by ``validate`` when asked for timestamps.
+"""
+from typing import Dict, Iterable, List, Optional, Union
+
+from .value import Sample, Reply, Timestamp
+
+SampleOrReply = Union[Sample, Reply]
+
+def _samples(items: Iterable[SampleOrReply]) -> List[Sample]:
+    samples = []
+    for item in items:
+        if isinstance(item, Reply) or hasattr(item, 'is_ok'):
+            if not item.is_ok:
+                continue
+            item = item.ok
+        samples.append(item)
+    return samples
+
+def _same(a: Sample, b: Sample) -> bool:
+    return a.kind == b.kind and a.payload == b.payload and str(a.encoding) == str(b.encoding)
+
+class Conflict:
+    "Distinct samples sharing a ``timestamp`` on a same ``key_expr``."
+    def __init__(self, key_expr: str, timestamp: Timestamp, samples: List[Sample]):
+        self.key_expr = key_expr
+        self.timestamp = timestamp
+        self.samples = samples
+
+    def __repr__(self) -> str:
+        return f"Conflict({self.key_expr!r}, {self.timestamp}, {len(self.samples)} samples)"
+
+class TimestampError(ValueError):
+    "Raised by ``validate``, with the ``conflicts`` and the samples ``missing`` a timestamp it found."
+    def __init__(self, conflicts: List[Conflict], missing: List[Sample]):
+        problems = [f"{len(conflicts)} conflicting timestamps"] if conflicts else []
+        if missing:
+            problems.append(f"{len(missing)} samples without timestamp")
+        super().__init__(", ".join(problems))
+        self.conflicts = conflicts
+        self.missing = missing
+
+def latest(items: Iterable[SampleOrReply]) -> Optional[Sample]:
+    """
+    Returns the sample with the greatest timestamp, or ``None`` if there is none.
+
+    :Example:
+
+    >>> import zenoh, zenoh.timestamps
+    >>> s = zenoh.open({})
+    >>> newest = zenoh.timestamps.latest(s.get('demo/config', zenoh.ListCollector())())
+    """
+    best = None
+    for sample in _samples(items):
+        if sample.timestamp is not None and (best is None or sample.timestamp > best.timestamp):
+            best = sample
+    return best
+
+def latest_by_key(items: Iterable[SampleOrReply]) -> Dict[str, Sample]:
+    "Returns the sample with the greatest timestamp for each key expression, which is how storages consolidate replicas."
+    best: Dict[str, Sample] = {}
+    for sample in _samples(items):
+        if sample.timestamp is None:
+            continue
+        key = str(sample.key_expr)
+        if key not in best or sample.timestamp > best[key].timestamp:
+            best[key] = sample
+    return best
+
+def is_newer(sample: SampleOrReply, than: Optional[SampleOrReply]) -> bool:
+    "Whether ``sample`` should replace ``than`` (which may be ``None``) in a storage: timestamped samples always replace untimestamped ones."
+    samples = _samples([sample])
+    if not samples:
+        return False
+    sample = samples[0]
+    others = _samples([] if than is None else [than])
+    if not others or others[0].timestamp is None:
+        return True
+    return sample.timestamp is not None and sample.timestamp > others[0].timestamp
+
+def conflicts(items: Iterable[SampleOrReply]) -> List[Conflict]:
+    """
+    Returns the conflicts among ``items``: groups of samples on a same key expression and with a same timestamp,
+    but whose kind, payload or encoding differ. Identical samples, such as those replied by several replicas, aren't conflicts.
+    """
+    groups: Dict[tuple, List[Sample]] = {}
+    for sample in _samples(items):
+        if sample.timestamp is not None:
+            groups.setdefault((str(sample.key_expr), sample.timestamp), []).append(sample)
+    found = []
+    for (key_expr, timestamp), samples in groups.items():
+        distinct = []
+        for sample in samples:
+            if not any(_same(sample, other) for other in distinct):
+                distinct.append(sample)
+        if len(distinct) > 1:
+            found.append(Conflict(key_expr, timestamp, distinct))
+    return found
+
+def validate(items: Iterable[SampleOrReply], require_timestamps: bool = False):
+    "Raises a ``TimestampError`` if ``items`` hold ``conflicts``, or samples without timestamp when ``require_timestamps`` is set."
+    samples = _samples(items)
+    found = conflicts(samples)
+    missing = [sample for sample in samples if sample.timestamp is None] if require_timestamps else []
+    if found or missing:
+        raise TimestampError(found, missing)
@@ -0,0 +1,104 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+from typing import Generic, TypeVar, Type, Callable, Any
+
+from .enums import Encoding, Priority, CongestionControl, Reliability, SampleKind
+from .keyexpr import KeyExpr, IntoKeyExpr
+from .value import IntoValue, IValue, Value, Sample, _CODECS
+
+T = TypeVar("T")
+_BUILTIN_TYPES = (bytes, str, int, float, dict, list)
+
+class Codec(Generic[T]):
+    """
+    A pair of functions converting objects to and from payloads, for use with ``Topic``.
+
+    ``encode`` may return anything that converts into a ``Value``; if ``encoding`` is set, it must return ``bytes``,
+    which will be published with that encoding.
+    """
+    def __init__(self, encode: Callable[[T], IntoValue], decode: Callable[[bytes], T], encoding: Encoding = None):
+        self.encode = encode
+        self.decode = decode
+        self.encoding = encoding
+
+class Topic(Generic[T]):
+    """
+    A key expression bound to the type of the messages published on it, along with the codec and QoS used for them.
+
+    Without a ``codec``, ``type`` must be one of ``bytes``, ``str``, ``int``, ``float``, ``dict`` or ``list``,
+    or an ``IValue`` type registered through ``zenoh.register_codec``. Any other type raises a ``TypeError`` here rather than on first use.
+
+    :Example:
+
+    >>> import zenoh
+    >>> s = zenoh.open({})
+    >>> temperature = zenoh.Topic(s, 'demo/temperature', float)
+    >>> sub = temperature.subscribe(lambda t: print(f"{t}°C"))
+    >>> temperature.publish(21.5)
+    """
+    def __init__(self, session, key_expr: IntoKeyExpr, type: Type[T], codec: Codec[T] = None,
+                 priority: Priority = None, congestion_control: CongestionControl = None, reliability: Reliability = None):
+        if codec is None and not issubclass(type, _BUILTIN_TYPES) and not (type in _CODECS and issubclass(type, IValue)):
+            raise TypeError(f"Topic of type {type.__name__} requires a codec: pass `codec`, or make {type.__name__} an IValue registered through `zenoh.register_codec`")
+        self.session = session
+        self.key_expr = KeyExpr(key_expr)
+        self.type = type
+        self.codec = codec
+        self.priority = priority
+        self.congestion_control = congestion_control
+        self.reliability = reliability
+        self._publisher_ = None
+
+    def encode(self, obj: T) -> Value:
+        "Converts ``obj`` into the ``Value`` that ``publish`` would send."
+        if not isinstance(obj, self.type):
+            raise TypeError(f"Topic '{self.key_expr}' carries {self.type.__name__}, not {type(obj).__name__}")
+        if self.codec is None:
+            return Value(obj)
+        return Value(self.codec.encode(obj), self.codec.encoding)
+
+    def decode(self, sample: Sample) -> T:
+        "Converts a sample received on this topic back into an instance of its type."
+        if self.codec is None:
+            return sample.deserialize(self.type)
+        return self.codec.decode(sample.payload)
+
+    def publish(self, obj: T):
+        "Publishes ``obj``, through a publisher declared with this topic's QoS on first use."
+        if self._publisher_ is None:
+            self._publisher_ = self.session.declare_publisher(self.key_expr, self.priority, self.congestion_control)
+        self._publisher_.put(self.encode(obj))
+
+    def delete(self):
+        "Publishes a deletion of this topic's key expression, through the same publisher as ``publish``."
+        if self._publisher_ is None:
+            self._publisher_ = self.session.declare_publisher(self.key_expr, self.priority, self.congestion_control)
+        self._publisher_.delete()
+
+    def subscribe(self, callback: Callable[[T], Any]):
+        """
+        Subscribes ``callback`` to the messages published on this topic, which it receives already decoded.
+
+        Deletions are not messages, and are skipped. The returned ``Subscriber`` must be kept alive for the subscription to last.
+        """
+        def on_sample(sample: Sample):
+            if sample.kind == SampleKind.PUT():
+                callback(self.decode(sample))
+        return self.session.declare_subscriber(self.key_expr, on_sample, self.reliability)
+
+    def undeclare(self):
+        "Undeclares the publisher this topic declared, if any."
+        if self._publisher_ is not None:
+            self._publisher_.undeclare()
+            self._publisher_ = None
@@ -12,11 +12,11 @@
 #   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 #
 import abc
-from typing import Union, Tuple, Optional, List
+from typing import Union, Tuple, Optional, List, Dict, Callable, Type, TypeVar, Any
 import json
 
-from .enums import Encoding, SampleKind
-from .zenoh import _Value, _Encoding, _Sample, _SampleKind, _Reply, _ZenohId, _Timestamp, _Hello
+from .enums import Encoding, SampleKind, ChangeKind
+from .zenoh import _Value, _Encoding, _Sample, _Change, _SampleFilter, _SampleKind, _Reply, _ZenohId, _Timestamp, _Hello
 from .keyexpr import KeyExpr, IntoKeyExpr
 
 class IValue:
@@ -35,6 +35,24 @@ class IValue:
 
 IntoValue = Union[IValue, bytes, str, int, float, object]
 
+T = TypeVar("T")
+_CODECS: Dict[type, Callable[[bytes], Any]] = dict()
+
+def register_codec(t: Type[T], decode: Callable[[bytes], T]):
+    """
+    Registers ``decode`` as the way to deserialize payloads into instances of ``t``,
+    making ``t`` a valid argument for ``Value.deserialize`` and ``Sample.deserialize``.
+    """
+    _CODECS[t] = decode
+
+def _deserialize(inner, t: Type[T]) -> T:
+    if t is bytes:
+        return inner.payload
+    decode = _CODECS.get(t)
+    if decode is not None:
+        return decode(inner.payload)
+    return inner._deserialize_(t)
+
 class Value(_Value, IValue):
     """
     A Value is a pair of a binary payload, and a mime-type-like encoding string.
@@ -64,12 +82,38 @@ class Value(_Value, IValue):
             return Value.new(f"{value}".encode(), Encoding.APP_INTEGER())
         if isinstance(value, float):
             return Value.new(f"{value}".encode(), Encoding.APP_FLOAT())
+        if hasattr(value, "__dlpack__"):
+            return Value.from_dlpack(value)
         return Value.new(json.dumps(value).encode(), Encoding.APP_JSON())
     
     @staticmethod
     def new(payload: bytes, encoding: Encoding = None) -> 'Value':
         return Value._upgrade_(_Value.new(payload, encoding))
 
+    @staticmethod
+    def from_dlpack(tensor) -> 'Value':
+        """
+        Copies the bytes of a C-contiguous host tensor, such as a numpy array or a torch tensor, or of a DLPack capsule,
+        into a value, which is what ``autoencode`` does with objects implementing ``__dlpack__``. Its encoding is
+        ``application/octet-stream``, with a suffix recording the tensor's data type and shape for ``__dlpack__`` to
+        restore them.
+
+        Tensors in CUDA device memory raise a ``ValueError``: these bindings don't link the CUDA runtime, so they must be
+        copied to the host first, with ``tensor.cpu()`` in torch or ``array.get()`` in cupy. Tensors in pinned or managed
+        memory are read in place.
+        See ``zenoh.shm.ShmManager.value`` to copy tensors into shared memory instead.
+        """
+        return Value._upgrade_(_Value.from_dlpack(tensor))
+
+    def __dlpack__(self, stream=None):
+        """
+        Exports a copy of the payload as a DLPack capsule, for ``numpy.from_dlpack(value)`` or
+        ``torch.from_dlpack(value)`` to read it as a tensor of their own, which may be written to.
+        The tensor has the data type and shape recorded by ``from_dlpack``, or is a 1-D array of
+        ``uint8`` if the encoding has none.
+        """
+        return super().__dlpack__(stream)
+
     @property
     def payload(self) -> bytes:
         return super().payload
@@ -86,6 +130,26 @@ class Value(_Value, IValue):
     def encoding(self, encoding: Encoding):
         super().with_encoding(encoding)
 
+    def deserialize(self, t: Type[T]) -> T:
+        """
+        Deserializes the payload into an instance of ``t``, which may be ``bytes``, ``str``, ``int``, ``float``,
+        ``dict`` or ``list`` (the latter two being read as JSON), or a type registered through ``zenoh.register_codec``.
+
+        The conversion is performed in Rust, directly from the received buffers, and only when this method is called:
+        forwarding a value without deserializing it has no decoding cost.
+        """
+        return _deserialize(self, t)
+
+    def _deserialize_(self, t: type):
+        return super().deserialize(t)
+
+    def __copy__(self) -> 'Value':
+        return _Value.__new__(Value, self)
+
+    def __deepcopy__(self, memo) -> 'Value':
+        # payloads are immutable, sharing them is safe
+        return _Value.__new__(Value, self)
+
     @staticmethod
     def _upgrade_(inner: _Value) -> 'Value':
         if inner is None:
@@ -118,7 +182,7 @@ class Timestamp(_Timestamp):
         """
         Returns the time part, as generated by the Zenoh HLC in NTP64 format (See https://datatracker.ietf.org/doc/html/rfc5905#section-6).
         """
-        return super().time
+        return super().get_time
     @property
     def seconds_since_unix_epoch(self) -> float:
         """
@@ -128,6 +192,14 @@ class Timestamp(_Timestamp):
         Therefore, it should not be used for comparison. Directly comparing Timestamp objects is preferable.
         """
         return super().seconds_since_unix_epoch
+    def __str__(self) -> str:
+        return super().__str__()
+    def __repr__(self) -> str:
+        return str(self)
+    def __copy__(self) -> 'Timestamp':
+        return Timestamp._upgrade_(self)
+    def __deepcopy__(self, memo) -> 'Timestamp':
+        return Timestamp._upgrade_(self)
 
 
 IntoSample = Union[_Sample, Tuple[IntoKeyExpr, IntoValue, SampleKind], Tuple[KeyExpr, IntoValue]]
@@ -154,6 +226,11 @@ class Sample(_Sample):
     def encoding(self) -> Encoding:
         "A shortcut to ``self.value.encoding``"
         return Encoding(super().encoding)
+    def deserialize(self, t: Type[T]) -> T:
+        "A shortcut to ``self.value.deserialize(t)``"
+        return _deserialize(self, t)
+    def _deserialize_(self, t: type):
+        return super().deserialize(t)
     @property
     def kind(self) -> SampleKind:
         "The sample's kind"
@@ -163,12 +240,105 @@ class Sample(_Sample):
         "The sample's  timestamp. May be None."
         ts = super().timestamp
         return None if ts is None else Timestamp._upgrade_(ts)
+    @property
+    def source_id(self) -> Optional['ZenohId']:
+        """
+        The id of the session that published the sample, when the network conveyed it. May be None.
+
+        Note that routers may strip this information, and that it is only as trustworthy as the network it crossed.
+        """
+        zid = super().source_id
+        return None if zid is None else ZenohId._upgrade_(zid)
+    @property
+    def attachment(self) -> Dict[bytes, bytes]:
+        "The name/value pairs the sample was sent with, empty if it was sent without."
+        return super().attachment
+    @property
+    def fragments(self) -> int:
+        """
+        The number of transport fragments the sample's payload was received in, 1 for samples that fitted in a single
+        batch (``transport/link/tx/batch_size`` in the configuration), as well as for those built or deserialized locally,
+        or whose payload was read already.
+
+        zenoh only delivers large samples once all their fragments are reassembled, and sends them in a single ``put``,
+        so no progress is reported while they transfer: UIs moving large blobs should split them into several samples
+        to show progress, and use this to tell how the network carried them.
+        """
+        return super().fragments
+    def __dlpack__(self, stream=None):
+        "Exports the payload as a DLPack capsule, as ``Value.__dlpack__`` does."
+        return super().__dlpack__(stream)
+        """
+        Serializes the sample into a JSON object, with its ``key_expr``, ``encoding``, ``kind``,
+        ``timestamp`` (or ``null``) and base64-encoded ``payload``, which is handy to persist traffic captures.
+        """
+        return super().to_json()
+    @staticmethod
+    def from_json(json: str) -> 'Sample':
+        "Deserializes a sample serialized by ``Sample.to_json``. Only ``key_expr`` is mandatory."
+        return Sample._upgrade_(_Sample.from_json(json))
+    def __copy__(self) -> 'Sample':
+        return _Sample.__new__(Sample, self)
+    def __deepcopy__(self, memo) -> 'Sample':
+        return _Sample.__new__(Sample, self)
+    def __reduce__(self):
+        # lets samples be sent to other processes, such as those of a ``ProcessPoolExecutor``
+        return (Sample.from_json, (self.to_json(),))
     @staticmethod
     def _upgrade_(inner: _Sample) -> 'Sample':
         if isinstance(inner, Sample):
             return inner
         return _Sample.__new__(Sample, inner)
 
+class Change(_Change):
+    """
+    A sample seen as a change to the value of its key expression, as delivered to subscribers declared with ``changes=True``.
+
+    ``value`` is ``None`` for deletions.
+    """
+    def __new__(cls, inner: _Change):
+        return super().__new__(cls, inner)
+    @property
+    def path(self) -> KeyExpr:
+        "The key expression that changed"
+        return KeyExpr(super().path)
+    @property
+    def value(self) -> Optional[Value]:
+        "The new value, or ``None`` if the key was deleted"
+        value = super().value
+        return None if value is None else Value._upgrade_(value)
+    @property
+    def kind(self) -> ChangeKind:
+        "The change's kind"
+        return ChangeKind(super().kind)
+    @property
+    def timestamp(self) -> Optional[Timestamp]:
+        "The change's timestamp. May be None."
+        ts = super().timestamp
+        return None if ts is None else Timestamp._upgrade_(ts)
+    def __repr__(self):
+        return f"Change(path='{self.path}', kind={self.kind}, value={None if self.value is None else self.value.payload!r})"
+
+class SampleFilter(_SampleFilter):
+    """
+    A predicate on sample payloads, evaluated in Rust before samples are handed to Python,
+    so that subscribers interested in a small subset of a busy key space don't pay for the rest of it.
+    """
+    def __new__(cls, inner: _SampleFilter):
+        return super().__new__(cls, inner)
+    @staticmethod
+    def prefix(prefix: bytes) -> 'SampleFilter':
+        "Matches the samples whose payload starts with ``prefix``."
+        return SampleFilter(_SampleFilter.prefix(prefix))
+    @staticmethod
+    def json_field(path: str, value: Any) -> 'SampleFilter':
+        """
+        Matches the samples whose payload is a JSON document where the field at ``path`` equals ``value``.
+
+        ``path`` is a ``.``-separated list of object keys or array indices, such as ``"readings.0.unit"``.
+        """
+        return SampleFilter(_SampleFilter.json_field(path, json.dumps(value)))
+
 class Reply(_Reply):
     """
     A reply to a query (``Session.get``).
@@ -181,6 +351,11 @@ class Reply(_Reply):
     def replier_id(self) -> ZenohId:
         "The reply's sender's id."
         return ZenohId._upgrade_(super().replier_id)
+
+    @property
+    def stale(self) -> bool:
+        "Whether the reply was served from a local cache rather than by a live queryable, see ``zenoh.offline``."
+        return False
     
     @property
     def is_ok(self) -> bool:
@@ -189,7 +364,7 @@ class Reply(_Reply):
 
         Returns `True` if the reply is `ok`, `False` otherwise
         """
-        return super.is_ok()
+        return super().is_ok
 
     @property
     def ok(self) -> Sample:
@@ -221,8 +396,19 @@ class Hello(_Hello):
         return super().whatami
     @property
     def locators(self) -> List[str]:
-        "The locators through which this node may be adressed."
+        """
+        The locators through which this node may be adressed.
+
+        Unix socket locators, such as ``unixsock-stream//tmp/zenoh.sock``, are only reachable from the node's own host.
+        """
         return super().locators
+    @property
+    def parsed_locators(self) -> List[Tuple[str, str]]:
+        """
+        The locators, split into their protocol and address, such as ``("tcp", "192.168.1.10:7447")``
+        or ``("unixsock-stream", "/tmp/zenoh.sock")``, whose metadata is left out.
+        """
+        return super().parsed_locators
     @staticmethod
     def _upgrade_(inner: _Hello) -> 'Sample':
         if isinstance(inner, Hello):
@@ -0,0 +1,87 @@
+#
+# Copyright (c) 2022 ZettaScale Technology
+#
+# This program and the accompanying materials are made available under the
+# terms of the Eclipse Public License 2.0 which is available at
+# http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+# which is available at https://www.apache.org/licenses/LICENSE-2.0.
+#
+# SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+#
+# Contributors:
+#   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+#
+from typing import List, Optional, Callable, Any
+
+from .enums import CongestionControl, Priority
+from .keyexpr import KeyExpr
+from .closures import ListCollector
+from .value import IntoValue, Value, Change, Timestamp
+
+class Data:
+    "A key/value pair retrieved through ``Workspace.get``."
+    def __init__(self, path: KeyExpr, value: Value, timestamp: Optional[Timestamp]):
+        self.path = path
+        self.value = value
+        self.timestamp = timestamp
+
+    @property
+    def payload(self) -> bytes:
+        return self.value.payload
+
+    def __repr__(self):
+        return f"Data(path='{self.path}', value={self.value.payload!r}, timestamp={self.timestamp})"
+
+
+class Workspace:
+    """
+    A facade over a ``Session`` that resolves all paths relative to a prefix,
+    in the spirit of the workspaces of zenoh's former Python API.
+
+    Paths starting with ``/`` are taken as absolute (without the leading ``/``),
+    all others are appended to the workspace's prefix.
+
+    :Example:
+
+    >>> import zenoh
+    >>> s = zenoh.open({})
+    >>> w = s.workspace('demo/example')
+    >>> w.put('hello', 'Hello World!')
+    >>> for data in w.get('**'):
+    ...     print(data.path, data.payload)
+    """
+    def __init__(self, session, prefix: str = None):
+        self.session = session
+        self.prefix = None if not prefix else prefix.strip('/')
+
+    def path(self, path: str) -> str:
+        "Returns the key expression (or selector) ``path`` resolves to in this workspace."
+        if path.startswith('/') or self.prefix is None:
+            return path.lstrip('/')
+        return f"{self.prefix}/{path}" if path else self.prefix
+
+    def put(self, path: str, value: IntoValue, encoding=None, priority: Priority = None,
+            congestion_control: CongestionControl = None):
+        "Puts ``value`` on ``path``, with the ``priority`` and ``congestion_control`` of ``Session.put``."
+        self.session.put(self.path(path), value, encoding, priority=priority, congestion_control=congestion_control)
+
+    def delete(self, path: str, priority: Priority = None, congestion_control: CongestionControl = None):
+        "Deletes the values stored under ``path``."
+        self.session.delete(self.path(path), priority=priority, congestion_control=congestion_control)
+
+    def get(self, selector: str, timeout: float = None) -> List[Data]:
+        """
+        Queries ``selector``, waiting for all the replies.
+
+        Error replies are ignored.
+        """
+        replies = self.session.get(self.path(selector), ListCollector(timeout))()
+        return [Data(sample.key_expr, sample.value, sample.timestamp) for sample in (reply.ok for reply in replies if reply.is_ok)]
+
+    def subscribe(self, selector: str, callback: Callable[[Change], Any]):
+        """
+        Subscribes ``callback`` to the changes published on ``selector``.
+
+        The returned ``Subscriber`` must be kept alive for the subscription to last.
+        """
+        return self.session.declare_subscriber(self.path(selector), callback, changes=True)